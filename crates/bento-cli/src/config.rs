@@ -0,0 +1,554 @@
+use std::{fmt, fs, path::PathBuf, sync::Arc};
+
+use bento_types::{config::AppConfigTrait, network::Network};
+use clap::Args;
+use serde::de::DeserializeOwned;
+
+use crate::types::{Config, ProcessorsConfig};
+
+/// Prefix of the environment variables overriding config keys. Nested keys are separated by a
+/// double underscore, e.g. `BENTO__WORKER__STEP` overrides `worker.step`.
+pub const ENV_PREFIX: &str = "BENTO__";
+
+/// Environment variables read by the framework before layered configuration existed.
+const BUILTIN_ENV_ALIASES: &[(&str, &str)] = &[("NETWORK", "network"), ("RPC_URL", "rpc_url")];
+
+/// Top level sections known by the framework. Apps list theirs in [`AppConfigSource::sections`].
+const KNOWN_SECTIONS: &[&str] = &["network", "rpc_url", "worker", "server", "backfill", "webhooks", "processors"];
+
+/// An invalid value at a config key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Dotted path of the key, e.g. `worker.step`
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { key: key.into(), message: message.into() }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.key, self.message)
+        }
+    }
+}
+
+/// All errors found while loading a config, reported together.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+impl ConfigErrors {
+    pub fn push(&mut self, error: ConfigError) {
+        self.0.push(error);
+    }
+
+    pub fn extend(&mut self, errors: ConfigErrors) {
+        self.0.extend(errors.0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `Ok(value)` when no error was collected.
+    pub fn into_result<T>(self, value: T) -> Result<T, ConfigErrors> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<ConfigError> for ConfigErrors {
+    fn from(error: ConfigError) -> Self {
+        Self(vec![error])
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} config error(s)", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+/// Builds the app config handed to custom processor factories from the loaded config.
+pub type AppConfigLoader = fn(&Config) -> Result<Arc<dyn AppConfigTrait>, ConfigErrors>;
+
+/// How an app derives its config from the layered config file.
+#[derive(Debug, Clone, Copy)]
+pub struct AppConfigSource {
+    /// Environment variables mapped to config keys, e.g. `("LINX_ADDRESS", "processors.lending.linx_address")`
+    pub env_aliases: &'static [(&'static str, &'static str)],
+    /// Top level sections read by the app, e.g. `price_service`, so they are not reported as unknown
+    pub sections: &'static [&'static str],
+    pub load: AppConfigLoader,
+}
+
+/// Flags selecting and overriding the config file, shared by the commands that load it.
+#[derive(Args, Clone, Debug)]
+pub struct ConfigArgs {
+    /// Path to the config file
+    #[arg(short, long, default_value = "config.toml")]
+    pub config_path: String,
+
+    /// The network to run the command on
+    #[arg(short, long = "network", value_parser = ["devnet", "testnet", "mainnet"])]
+    pub network: Option<String>,
+
+    /// Override a config key, e.g. `--set worker.step=10000`. Can be repeated
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+}
+
+impl ConfigArgs {
+    /// A loader layering the environment and these flags over the config file.
+    pub fn loader(&self) -> ConfigLoader {
+        let mut loader = ConfigLoader::new(&self.config_path);
+        if let Some(network) = &self.network {
+            loader = loader.set("network", network);
+        }
+        for set in &self.set {
+            loader = match set.split_once('=') {
+                Some((key, value)) => loader.set(key.trim(), value.trim()),
+                None => loader.invalid_override(set),
+            };
+        }
+        loader
+    }
+}
+
+/// Loads a [`Config`] from layers, each overriding the previous one: the TOML file, the
+/// environment, then explicit overrides such as CLI flags.
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    path: PathBuf,
+    env: Option<Vec<(String, String)>>,
+    env_aliases: Vec<(String, String)>,
+    overrides: Vec<(String, String)>,
+    invalid_overrides: Vec<String>,
+}
+
+impl ConfigLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            env: None,
+            env_aliases: BUILTIN_ENV_ALIASES.iter().map(|(var, key)| (var.to_string(), key.to_string())).collect(),
+            overrides: Vec::new(),
+            invalid_overrides: Vec::new(),
+        }
+    }
+
+    /// Use these variables instead of the process environment.
+    pub fn with_env<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env = Some(vars.into_iter().map(|(k, v)| (k.into(), v.into())).collect());
+        self
+    }
+
+    /// Map extra environment variables to config keys.
+    pub fn env_aliases(mut self, aliases: &[(&str, &str)]) -> Self {
+        self.env_aliases.extend(aliases.iter().map(|(var, key)| (var.to_string(), key.to_string())));
+        self
+    }
+
+    /// Override a dotted config key. The value is parsed as a TOML value, falling back to a string.
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.overrides.push((key.into(), value.into()));
+        self
+    }
+
+    fn invalid_override(mut self, value: &str) -> Self {
+        self.invalid_overrides.push(value.to_string());
+        self
+    }
+
+    /// The merged TOML table of all layers.
+    pub fn load_table(&self) -> Result<toml::Table, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        let content = fs::read_to_string(&self.path).map_err(|err| {
+            ConfigError::new("", format!("Failed to read config file {}: {}", self.path.display(), err))
+        })?;
+        let mut table: toml::Table = toml::from_str(&content).map_err(|err| {
+            ConfigError::new("", format!("Failed to parse config file {}: {}", self.path.display(), err))
+        })?;
+
+        let env = match &self.env {
+            Some(env) => env.clone(),
+            None => std::env::vars().collect(),
+        };
+        // Aliases first so that prefixed variables win over them
+        for (var, key) in &self.env_aliases {
+            if let Some((_, value)) = env.iter().find(|(name, _)| name == var) {
+                set_key(&mut table, key, value, &mut errors);
+            }
+        }
+        for (var, value) in &env {
+            if let Some(path) = var.strip_prefix(ENV_PREFIX) {
+                let key = path.split("__").map(str::to_lowercase).collect::<Vec<_>>().join(".");
+                set_key(&mut table, &key, value, &mut errors);
+            }
+        }
+
+        for (key, value) in &self.overrides {
+            set_key(&mut table, key, value, &mut errors);
+        }
+        for set in &self.invalid_overrides {
+            errors.push(ConfigError::new("", format!("Invalid override `{}`, expected KEY=VALUE", set)));
+        }
+
+        errors.into_result(table)
+    }
+
+    /// Load and validate the config, reporting every error found.
+    pub fn load(&self) -> Result<Config, ConfigErrors> {
+        let table = self.load_table()?;
+        let config = Config::from_table(table)?;
+        config.validate().into_result(config)
+    }
+}
+
+/// Outcome of `config check`.
+#[derive(Debug, Default)]
+pub struct ConfigCheck {
+    pub errors: ConfigErrors,
+    pub warnings: Vec<String>,
+}
+
+/// Load the config like a command would and collect every error and warning instead of stopping
+/// at the first one.
+pub fn check_config(args: &ConfigArgs, app_config: Option<AppConfigSource>, processors: &[&str]) -> ConfigCheck {
+    let mut check = ConfigCheck::default();
+
+    let mut loader = args.loader();
+    if let Some(source) = &app_config {
+        loader = loader.env_aliases(source.env_aliases);
+    }
+    let table = match loader.load_table() {
+        Ok(table) => table,
+        Err(errors) => {
+            check.errors = errors;
+            return check;
+        }
+    };
+
+    let app_sections = app_config.map_or(&[][..], |source| source.sections);
+    check
+        .warnings
+        .extend(unknown_keys(&table, app_sections).into_iter().map(|key| format!("Unknown config key `{}`", key)));
+    if let Some(toml::Value::Table(sections)) = table.get("processors") {
+        check.warnings.extend(
+            sections
+                .keys()
+                .filter(|name| !processors.contains(&name.as_str()))
+                .map(|name| format!("No processor registered for `processors.{}`", name)),
+        );
+    }
+
+    match Config::from_table(table) {
+        Ok(config) => {
+            check.errors.extend(config.validate());
+            if let Some(source) = app_config {
+                if let Err(errors) = (source.load)(&config) {
+                    check.errors.extend(errors);
+                }
+            }
+        }
+        Err(errors) => check.errors.extend(errors),
+    }
+    check
+}
+
+/// Keys of `table` known neither by the framework nor in `app_sections`.
+pub fn unknown_keys(table: &toml::Table, app_sections: &[&str]) -> Vec<String> {
+    table
+        .keys()
+        .filter(|key| !KNOWN_SECTIONS.contains(&key.as_str()) && !app_sections.contains(&key.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+fn set_key(table: &mut toml::Table, key: &str, raw: &str, errors: &mut ConfigErrors) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let Some(last) = parts.pop().filter(|last| !last.is_empty()) else {
+        errors.push(ConfigError::new(key, "Invalid config key"));
+        return;
+    };
+
+    let mut current = table;
+    for part in parts {
+        let entry = current.entry(part.to_string()).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        match entry {
+            toml::Value::Table(inner) => current = inner,
+            _ => {
+                errors.push(ConfigError::new(key, format!("`{}` is not a table", part)));
+                return;
+            }
+        }
+    }
+    current.insert(last.to_string(), parse_value(raw));
+}
+
+fn section<T: DeserializeOwned>(table: &toml::Table, key: &str, errors: &mut ConfigErrors) -> Option<T> {
+    let value = table.get(key).cloned().unwrap_or_else(|| toml::Value::Table(toml::Table::new()));
+    match value.try_into() {
+        Ok(section) => Some(section),
+        Err(err) => {
+            errors.push(ConfigError::new(key, err.to_string().trim()));
+            None
+        }
+    }
+}
+
+fn optional_section<T: DeserializeOwned>(table: &toml::Table, key: &str, errors: &mut ConfigErrors) -> Option<T> {
+    table.contains_key(key).then(|| section(table, key, errors)).flatten()
+}
+
+impl Config {
+    /// Deserialize every section of `table`, collecting the errors of all of them.
+    pub fn from_table(table: toml::Table) -> Result<Config, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        let network = optional_section(&table, "network", &mut errors);
+        let rpc_url = optional_section(&table, "rpc_url", &mut errors);
+        let worker = section(&table, "worker", &mut errors);
+        let server = section(&table, "server", &mut errors);
+        let backfill = section(&table, "backfill", &mut errors);
+        let webhooks = section(&table, "webhooks", &mut errors);
+        let processors = optional_section(&table, "processors", &mut errors);
+        let price_service = optional_section(&table, "price_service", &mut errors);
+        let points = optional_section(&table, "points", &mut errors);
+
+        match (worker, server, backfill, webhooks) {
+            (Some(worker), Some(server), Some(backfill), Some(webhooks)) if errors.is_empty() => {
+                Ok(Config { network, rpc_url, worker, server, backfill, webhooks, processors, price_service, points })
+            }
+            _ => Err(errors),
+        }
+    }
+
+    /// Deserialize the `[processors.<name>]` section into the processor's settings type.
+    pub fn processor_section<T: DeserializeOwned>(&self, name: &str) -> Result<T, ConfigError> {
+        match &self.processors {
+            Some(processors) => processors.section(name),
+            None => ProcessorsConfig { processors: Default::default() }.section(name),
+        }
+    }
+
+    /// The network to connect to, using `rpc_url` as its node when set.
+    pub fn resolve_network(&self) -> anyhow::Result<Network> {
+        let network = self.network.clone().ok_or_else(|| {
+            anyhow::anyhow!("No network configured. Set `network` in the config file, NETWORK or --network")
+        })?;
+        Ok(match &self.rpc_url {
+            Some(url) => Network::Custom(url.clone(), network.into()),
+            None => network.into(),
+        })
+    }
+
+    /// Check the values of the framework sections.
+    pub fn validate(&self) -> ConfigErrors {
+        let mut errors = ConfigErrors::default();
+
+        if let Some(network) = &self.network {
+            if self.rpc_url.is_none() && !["devnet", "testnet", "mainnet"].contains(&network.as_str()) {
+                errors.push(ConfigError::new("network", "Expected one of devnet, testnet or mainnet"));
+            }
+        }
+
+        for (key, value) in [
+            ("worker.step", self.worker.step),
+            ("worker.request_interval", self.worker.request_interval),
+            ("worker.gap_threshold", self.worker.gap_threshold),
+            ("backfill.step", self.backfill.step),
+        ] {
+            if value == 0 {
+                errors.push(ConfigError::new(key, "Must be greater than 0"));
+            }
+        }
+        if self.worker.gap_check_interval == Some(0) {
+            errors.push(ConfigError::new("worker.gap_check_interval", "Must be greater than 0, omit it to disable"));
+        }
+        if self.backfill.workers == 0 {
+            errors.push(ConfigError::new("backfill.workers", "Must be greater than 0"));
+        }
+        for (key, value) in [
+            ("server.ip_requests_per_minute", self.server.ip_requests_per_minute),
+            ("server.key_requests_per_minute", self.server.key_requests_per_minute),
+        ] {
+            if value == Some(0) {
+                errors.push(ConfigError::new(key, "Must be greater than 0, omit it to disable"));
+            }
+        }
+
+        if let Some(points) = &self.points {
+            if !(0.0..=1.0).contains(&points.referral_percentage) {
+                errors.push(ConfigError::new("points.referral_percentage", "Must be between 0 and 1"));
+            }
+            if chrono::NaiveTime::parse_from_str(&points.calculation_time, "%H:%M").is_err() {
+                errors.push(ConfigError::new("points.calculation_time", "Expected a time in HH:MM format"));
+            }
+        }
+
+        errors
+    }
+}
+
+impl ProcessorsConfig {
+    /// Deserialize the `[processors.<name>]` section into the processor's settings type.
+    /// A missing section deserializes from an empty table.
+    pub fn section<T: DeserializeOwned>(&self, name: &str) -> Result<T, ConfigError> {
+        let values = self.processors.get(name).map(|section| section.config.clone()).unwrap_or_default();
+        serde_json::from_value(serde_json::Value::Object(values.into_iter().collect()))
+            .map_err(|err| ConfigError::new(format!("processors.{}", name), err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const CONFIG: &str = r#"
+        [worker]
+        request_interval = 500
+        step = 60000
+        backstep = 300000
+
+        [backfill]
+        request_interval = 1000
+        workers = 2
+        step = 1800000
+        backstep = 600000
+
+        [processors.lending]
+        linx_group = 0
+    "#;
+
+    fn write_config(content: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().expect("Failed to create config file");
+        file.write_all(content.as_bytes()).expect("Failed to write config file");
+        file
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let file = write_config(CONFIG);
+        let config = ConfigLoader::new(file.path())
+            .with_env([
+                ("BENTO__WORKER__STEP", "10000"),
+                ("BENTO__PROCESSORS__LENDING__LINX_ADDRESS", "linx"),
+                ("NETWORK", "testnet"),
+                ("LINX_GROUP", "2"),
+            ])
+            .env_aliases(&[("LINX_GROUP", "processors.lending.linx_group")])
+            .set("worker.step", "20000")
+            .set("network", "mainnet")
+            .load()
+            .expect("Config should load");
+
+        assert_eq!(config.worker.step, 20000);
+        assert_eq!(config.worker.request_interval, 500);
+        assert_eq!(config.network.as_deref(), Some("mainnet"));
+
+        let lending = &config.processors.unwrap().processors["lending"].config;
+        assert_eq!(lending["linx_address"], serde_json::json!("linx"));
+        assert_eq!(lending["linx_group"], serde_json::json!(2));
+    }
+
+    #[test]
+    fn test_reports_all_errors() {
+        let file = write_config(
+            r#"
+            [worker]
+            request_interval = "fast"
+            step = 0
+            backstep = 1
+
+            [backfill]
+            request_interval = 1
+            step = 1
+            backstep = 1
+            "#,
+        );
+        let errors = ConfigLoader::new(file.path()).with_env(Vec::<(String, String)>::new()).load().unwrap_err();
+        let keys: Vec<_> = errors.0.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["worker", "backfill"]);
+
+        let file = write_config(&CONFIG.replace("workers = 2", "workers = 0"));
+        let errors = ConfigLoader::new(file.path())
+            .with_env(Vec::<(String, String)>::new())
+            .set("worker.step", "0")
+            .set("network", "moon")
+            .set("server.ip_requests_per_minute", "0")
+            .load()
+            .unwrap_err();
+        let keys: Vec<_> = errors.0.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["network", "worker.step", "backfill.workers", "server.ip_requests_per_minute"]);
+    }
+
+    #[test]
+    fn test_invalid_override() {
+        let file = write_config(CONFIG);
+        let args = ConfigArgs {
+            config_path: file.path().to_string_lossy().to_string(),
+            network: None,
+            set: vec!["worker.step".to_string(), "worker.step.value=1".to_string()],
+        };
+        let errors = args.loader().with_env(Vec::<(String, String)>::new()).load().unwrap_err();
+        assert_eq!(errors.0.len(), 2);
+        assert_eq!(errors.0[0].key, "worker.step.value");
+        assert!(errors.0[1].message.contains("expected KEY=VALUE"));
+    }
+
+    #[test]
+    fn test_processor_section() {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct LendingSettings {
+            linx_group: u32,
+        }
+
+        let file = write_config(CONFIG);
+        let config = ConfigLoader::new(file.path()).with_env(Vec::<(String, String)>::new()).load().unwrap();
+        let settings: LendingSettings = config.processor_section("lending").unwrap();
+        assert_eq!(settings.linx_group, 0);
+
+        let error = config.processor_section::<LendingSettings>("transfers").err().unwrap();
+        assert_eq!(error.key, "processors.transfers");
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let table: toml::Table =
+            toml::from_str("network = 'testnet'\n[worker]\n[price_service]\n[typo]\n").expect("Valid TOML");
+        assert_eq!(unknown_keys(&table, &[]), vec!["price_service", "typo"]);
+        assert_eq!(unknown_keys(&table, &["price_service"]), vec!["typo"]);
+    }
+}
@@ -0,0 +1,80 @@
+use bento_core::workers::gaps::GapReport;
+
+use crate::display_timestamp;
+
+/// Render a gap report as plain text tables.
+pub fn render_report(report: &GapReport) -> String {
+    let mut out = String::new();
+
+    if report.is_empty() {
+        out.push_str("No gaps found\n");
+        return out;
+    }
+
+    out.push_str(&format!("Height gaps: {} ({} missing blocks)\n", report.height_gaps.len(), report.missing_blocks()));
+    if !report.height_gaps.is_empty() {
+        out.push_str(&format!(
+            "{:<8} {:>12} {:>12} {:>10}  {:<19}\n",
+            "CHAIN", "FROM", "TO", "MISSING", "AFTER BLOCK (UTC)"
+        ));
+        for gap in &report.height_gaps {
+            out.push_str(&format!(
+                "{:<8} {:>12} {:>12} {:>10}  {:<19}\n",
+                format!("{}->{}", gap.chain_from, gap.chain_to),
+                gap.from_height,
+                gap.to_height,
+                gap.missing_blocks(),
+                display_timestamp(gap.from_ts)
+            ));
+        }
+    }
+
+    out.push_str(&format!("\nTimestamp gaps: {}\n", report.timestamp_gaps.len()));
+    if !report.timestamp_gaps.is_empty() {
+        out.push_str(&format!("{:<8} {:<19}  {:<19}  {:>10}\n", "CHAIN", "FROM (UTC)", "TO (UTC)", "LENGTH (s)"));
+        for gap in &report.timestamp_gaps {
+            out.push_str(&format!(
+                "{:<8} {:<19}  {:<19}  {:>10}\n",
+                format!("{}->{}", gap.chain_from, gap.chain_to),
+                display_timestamp(gap.from_ts),
+                display_timestamp(gap.to_ts),
+                gap.duration_ms() / 1000
+            ));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bento_types::repository::{HeightGap, TimestampGap};
+
+    #[test]
+    fn test_render_report() {
+        assert_eq!(render_report(&GapReport::default()), "No gaps found\n");
+
+        let report = GapReport {
+            height_gaps: vec![HeightGap {
+                chain_from: 0,
+                chain_to: 1,
+                from_height: 10,
+                to_height: 14,
+                from_ts: 1_700_000_000_000,
+                to_ts: 1_700_000_080_000,
+            }],
+            timestamp_gaps: vec![TimestampGap {
+                chain_from: 2,
+                chain_to: 3,
+                from_ts: 1_700_000_000_000,
+                to_ts: 1_700_000_900_000,
+            }],
+        };
+
+        let table = render_report(&report);
+        assert!(table.contains("Height gaps: 1 (5 missing blocks)"));
+        assert!(table.lines().any(|line| line.starts_with("0->1") && line.contains("2023-11-14 22:13:20")));
+        assert!(table.lines().any(|line| line.starts_with("2->3") && line.ends_with("900")));
+    }
+}
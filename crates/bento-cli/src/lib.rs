@@ -1,20 +1,56 @@
+pub mod config;
+pub mod gaps;
+pub mod status;
 pub mod types;
-use crate::types::*;
-use bento_types::network::Network;
+use crate::{config::*, types::*};
+use bento_types::{
+    config::AppConfigTrait,
+    export::{load_export_columns, ExportPlan, ExportRequest, ExportTables},
+    network::Network,
+    repository::{
+        create_api_key, create_webhook_subscription, disable_webhook_subscription, get_webhook_subscription,
+        list_api_keys, list_webhook_dead_letters, list_webhook_subscriptions, replay_webhook_dead_letters,
+        revoke_api_key,
+    },
+    NewWebhookSubscriptionModel, WebhookSubscriptionModel,
+};
 use clap::Parser;
 
 use anyhow::{Context, Result};
 use bento_core::{
     config::ProcessorConfig,
     new_db_pool,
+    notify::Notifier,
+    processors::token_processor::TokenMetadataResolver,
+    webhooks::{WebhookConfig, WebhookDispatcher},
     worker::{BackfillOptions, SyncOptions},
-    workers::worker::Worker,
-    Client, ProcessorFactory,
+    workers::{
+        gaps::{detect_gaps, GapCheckOptions, GapOptions},
+        worker::Worker,
+    },
+    Client, ProcessorFactory, MIGRATIONS_SUPPORTED,
+};
+use bento_server::{
+    auth::{AuthConfig, RateLimit},
+    handler::HealthConfig,
+    start, AppState, Config as ServerConfig,
 };
-use bento_server::{start, AppState, Config as ServerConfig};
-use std::{collections::HashMap, fs, path::Path, sync::Arc};
+use diesel_migrations::EmbeddedMigrations;
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::watch;
 use utoipa_axum::router::OpenApiRouter;
 
+/// How often a running worker checks whether its config file changed.
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Format a millisecond timestamp as UTC date and time for the command reports.
+pub(crate) fn display_timestamp(ts: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ts)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
 /// Get database URL constructed from POSTGRES_* environment variables
 pub fn get_database_url() -> Result<String> {
     let user = std::env::var("POSTGRES_USER").context(
@@ -56,58 +92,74 @@ pub fn get_network() -> Result<Network> {
     }
 }
 
-async fn new_worker_from_config(
-    _config: &Config,
-    processor_factories: &HashMap<String, ProcessorFactory>,
-    include_default_processors: bool,
-    workers: usize,
-    sync_options: Option<SyncOptions>,
-    backfill_options: Option<BackfillOptions>,
+/// The processors run by the workers of this app.
+fn processor_configs(
+    options: &RunOptions,
     app_config: Option<Arc<dyn bento_types::config::AppConfigTrait>>,
-) -> Result<Worker> {
+) -> Vec<ProcessorConfig> {
     let mut processors = Vec::new();
 
-    if include_default_processors {
+    if options.include_default_processors {
         processors.extend(vec![
             ProcessorConfig::BlockProcessor,
             ProcessorConfig::EventProcessor,
             ProcessorConfig::TxProcessor,
+            ProcessorConfig::AddressProcessor,
+            ProcessorConfig::TokenProcessor,
+            ProcessorConfig::MiningProcessor,
         ]);
     }
 
-    for (processor_name, processor_factory) in processor_factories.iter() {
-        let processor_config = ProcessorConfig::Custom {
-            name: processor_name.clone(),
-            factory: *processor_factory,
-            config: app_config.clone(),
-        };
+    for (processor_name, processor_factory) in options.processor_factories.iter() {
+        let mut processor_config =
+            ProcessorConfig::custom(processor_name.clone(), *processor_factory, app_config.clone());
+        if let Some(migrations) = options.processor_migrations.get(processor_name) {
+            processor_config = processor_config.with_migrations(migrations);
+        }
         processors.push(processor_config);
     }
+    processors
+}
 
-    let network = get_network()?;
+async fn new_worker_from_config(
+    config: &Config,
+    options: &RunOptions,
+    workers: usize,
+    sync_options: Option<SyncOptions>,
+    backfill_options: Option<BackfillOptions>,
+    app_config: Option<Arc<dyn bento_types::config::AppConfigTrait>>,
+) -> Result<Worker> {
+    let processors = processor_configs(options, app_config);
+    let network = config.resolve_network()?;
 
-    let worker =
+    let mut worker =
         Worker::new(processors, get_database_url()?, network, None, sync_options, backfill_options, workers).await?;
+    if let Some(migrations) = options.migrations {
+        worker = worker.with_migrations(migrations);
+    }
     Ok(worker)
 }
 
 pub async fn new_realtime_worker_from_config(
     config: &Config,
-    processor_factories: &HashMap<String, ProcessorFactory>,
-    include_default_processors: bool,
+    options: &RunOptions,
     app_config: Option<Arc<dyn bento_types::config::AppConfigTrait>>,
 ) -> Result<Worker> {
     let workers: usize = 2;
     let step = config.worker.step;
     let backstep = config.worker.backstep;
     let request_interval = config.worker.request_interval;
+    let gap_check = config.worker.gap_check_interval.map(|interval| GapCheckOptions {
+        interval,
+        lookback: config.worker.gap_lookback,
+        threshold: config.worker.gap_threshold,
+    });
 
     new_worker_from_config(
         config,
-        processor_factories,
-        include_default_processors,
+        options,
         workers,
-        Some(SyncOptions { step, backstep, request_interval }),
+        Some(SyncOptions { step, backstep, request_interval, gap_check }),
         None,
         app_config,
     )
@@ -117,9 +169,9 @@ pub async fn new_realtime_worker_from_config(
 pub async fn new_backfill_worker_from_config(
     start_ts: Option<u64>,
     stop_ts: Option<u64>,
+    job: Option<String>,
     config: &Config,
-    processor_factories: &HashMap<String, ProcessorFactory>,
-    include_default_processors: bool,
+    options: &RunOptions,
     app_config: Option<Arc<dyn bento_types::config::AppConfigTrait>>,
 ) -> Result<Worker> {
     let workers = config.backfill.workers;
@@ -129,43 +181,143 @@ pub async fn new_backfill_worker_from_config(
 
     new_worker_from_config(
         config,
-        processor_factories,
-        include_default_processors,
+        options,
         workers,
         None,
-        Some(BackfillOptions { start_ts, stop_ts, step, backstep, request_interval }),
+        Some(BackfillOptions { start_ts, stop_ts, step, backstep, request_interval, job }),
         app_config,
     )
     .await
 }
 
-pub async fn new_server_config_from_config(_config: &Config) -> Result<ServerConfig> {
+pub async fn new_server_config_from_config(config: &Config) -> Result<ServerConfig> {
     let database_url = get_database_url()?;
     let db_pool = new_db_pool(&database_url, None).await?;
 
-    let network = get_network()?;
+    let network = config.resolve_network()?;
     let client = Arc::new(Client::new(network));
 
     let api_host = std::env::var("API_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
     let api_port =
         std::env::var("API_PORT").unwrap_or_else(|_| "8080".to_string()).parse().context("Invalid API_PORT value")?;
 
-    let server_config = ServerConfig { db_client: db_pool, node_client: client, api_host, api_port };
+    let auth = AuthConfig {
+        ip_rate_limit: config.server.ip_requests_per_minute.map(RateLimit::per_minute),
+        key_rate_limit: config.server.key_requests_per_minute.map(RateLimit::per_minute),
+        trust_forwarded_for: config.server.trust_forwarded_for,
+    };
+
+    let defaults = HealthConfig::default();
+    let health = HealthConfig {
+        max_chain_lag: config.server.health_max_chain_lag.unwrap_or(defaults.max_chain_lag),
+        max_checkpoint_age: config
+            .server
+            .health_max_checkpoint_age
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.max_checkpoint_age),
+        check_timeout: config.server.health_check_timeout.map(Duration::from_millis).unwrap_or(defaults.check_timeout),
+        processors: None,
+    };
+
+    let server_config = ServerConfig {
+        db_client: db_pool,
+        database_url,
+        node_client: client,
+        api_host,
+        api_port,
+        auth,
+        health,
+        exports: ExportTables::core(),
+    };
     Ok(server_config)
 }
 
+/// What an app adds to the command line interface, passed to [`run_command`].
+///
+/// ```ignore
+/// let options = RunOptions::new(processor_factories)
+///     .with_router(router)
+///     .with_migrations(&MIGRATIONS)
+///     .with_processor_migrations("lending", &LENDING_MIGRATIONS)
+///     .with_export_tables(ExportTables::core().table(ExportTable::new("lending_events", "block_time")));
+/// run_command(options).await?;
+/// ```
+pub struct RunOptions {
+    /// Custom processor factories by processor name
+    pub processor_factories: HashMap<String, ProcessorFactory>,
+    /// Routes merged into the server
+    pub router: Option<OpenApiRouter<AppState>>,
+    /// Whether the default processors (block, event and tx) run next to the custom ones
+    pub include_default_processors: bool,
+    /// Source of the app config handed to custom processors, built from the layered config
+    /// (file, environment, then CLI flags)
+    pub app_config: Option<AppConfigSource>,
+    /// App migrations, applied after the framework migrations whenever a worker starts or the
+    /// `migrate` command runs
+    pub migrations: Option<&'static EmbeddedMigrations>,
+    /// Migrations of custom processors by processor name, applied after the app migrations
+    pub processor_migrations: HashMap<String, &'static EmbeddedMigrations>,
+    /// Tables served by the export endpoints and the `export` command
+    pub export_tables: ExportTables,
+}
+
+impl RunOptions {
+    /// Options running `processor_factories` next to the default processors, exporting the core
+    /// tables
+    pub fn new(processor_factories: HashMap<String, ProcessorFactory>) -> Self {
+        Self {
+            processor_factories,
+            router: None,
+            include_default_processors: true,
+            app_config: None,
+            migrations: None,
+            processor_migrations: HashMap::new(),
+            export_tables: ExportTables::core(),
+        }
+    }
+
+    pub fn with_router(mut self, router: OpenApiRouter<AppState>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    pub fn without_default_processors(mut self) -> Self {
+        self.include_default_processors = false;
+        self
+    }
+
+    pub fn with_app_config(mut self, app_config: AppConfigSource) -> Self {
+        self.app_config = Some(app_config);
+        self
+    }
+
+    pub fn with_migrations(mut self, migrations: &'static EmbeddedMigrations) -> Self {
+        self.migrations = Some(migrations);
+        self
+    }
+
+    /// Register the migrations of the custom processor `name`, see
+    /// [`ProcessorConfig::with_migrations`]
+    pub fn with_processor_migrations<S: Into<String>>(
+        mut self,
+        name: S,
+        migrations: &'static EmbeddedMigrations,
+    ) -> Self {
+        self.processor_migrations.insert(name.into(), migrations);
+        self
+    }
+
+    pub fn with_export_tables(mut self, export_tables: ExportTables) -> Self {
+        self.export_tables = export_tables;
+        self
+    }
+}
+
 /// Main function to run the command line interface
 ///
 /// This function serves as the entry point for the Bento application's CLI.
 /// It handles parsing command-line arguments and executing the appropriate
-/// functionality based on the provided commands and options.
-///
-/// # Arguments
-///
-/// * `processor_factories` - A HashMap containing custom processor factories,
-///   where the key is the processor name and the value is the processor factory function.
-/// * `include_default_processors` - A boolean flag indicating whether to include
-///   the default processors (block, event, and tx) in addition to any custom processors.
+/// functionality based on the provided commands and the app's [`RunOptions`].
 ///
 /// # Returns
 ///
@@ -178,61 +330,107 @@ pub async fn new_server_config_from_config(_config: &Config) -> Result<ServerCon
 ///   * `Server` - Runs in server mode.
 ///   * `Worker` - Runs in worker mode with specified processors.
 ///   * `Backfill` - Performs data backfilling for specified processors.
-///   * `BackfillStatus` - Displays backfill status for a specific processor.
+/// * `Migrate` - Applies pending framework, app and processor migrations, then exits.
+/// * `Status` - Displays node tips, indexed blocks, processor checkpoints and gaps.
+/// * `Reindex` - Purges and reprocesses a timestamp range for the selected processors.
+/// * `Gaps` - Detects missing heights and periods without blocks, or fills them.
+/// * `Config` - Checks the layered config and reports every error at once.
+/// * `ApiKeys` - Creates, lists and revokes the API keys of the server.
+/// * `Export` - Writes the rows of a registered table in a time window as CSV, NDJSON or Parquet.
+/// * `Webhooks` - Creates, lists and disables webhook subscriptions, and inspects or replays
+///   their dead letters.
 ///
 /// # Examples
 ///
+/// ```ignore
+/// let processor_factories = HashMap::new();
+/// run_command(RunOptions::new(processor_factories)).await?;
 /// ```
-// / let processor_factories = HashMap::new();
-// / run_command(processor_factories, true).await?;
-/// ```
-pub async fn run_command(
-    processor_factories: HashMap<String, ProcessorFactory>,
-    router: Option<OpenApiRouter<AppState>>,
-    include_default_processors: bool,
-    app_config: Option<Arc<dyn bento_types::config::AppConfigTrait>>,
-) -> Result<()> {
+pub async fn run_command(options: RunOptions) -> Result<()> {
     tracing_subscriber::fmt::init();
 
+    let app_config = options.app_config;
+
     let cli = Cli::parse();
     match cli.command {
         Commands::Run(run) => match run.mode {
             RunMode::Server(args) => {
-                let config = args.clone().into();
-                let server_config = new_server_config_from_config(&config).await?;
+                let (config, _) = load_configs(&args.config, app_config)?;
+                if !config.worker.notify {
+                    eprintln!(
+                        "worker.notify is disabled: /v1/stream will not emit anything and cached responses are \
+                         only refreshed when they expire"
+                    );
+                }
+                let mut server_config = new_server_config_from_config(&config).await?;
+                server_config.health.processors =
+                    Some(processor_configs(&options, None).iter().map(|p| p.name().to_string()).collect());
+                server_config.exports = options.export_tables;
 
                 println!("Server is ready and running on http://{}", server_config.api_endpoint());
                 println!("Swagger UI is available at http://{}/swagger-ui", server_config.api_endpoint());
 
-                start(server_config, router).await?;
+                start(server_config, options.router).await?;
             }
             RunMode::Worker(args) => {
-                let config = args.clone().into();
-
-                println!("⚙️  Running real-time indexer with config: {}", args.config_path);
-
-                let worker = new_realtime_worker_from_config(
-                    &config,
-                    &processor_factories,
-                    include_default_processors,
-                    app_config.clone(),
-                )
-                .await?;
+                let app_config_source = app_config;
+                let (config, app_config) = load_configs(&args.config, app_config_source)?;
+
+                println!("⚙️  Running real-time indexer with config: {}", args.config.config_path);
+
+                let mut worker = new_realtime_worker_from_config(&config, &options, app_config.clone()).await?;
+
+                let notifier = match config.webhooks.enabled {
+                    true => Some(Notifier::new(config.worker.notify).with_webhooks()),
+                    false => config.worker.notify.then(|| Notifier::new(true)),
+                };
+                let webhooks = config
+                    .webhooks
+                    .enabled
+                    .then(|| WebhookDispatcher::new(worker.db_pool.clone(), webhook_config(&config.webhooks)))
+                    .transpose()?;
+
+                let (app_config_updates, app_config_receiver) = watch::channel(app_config);
+                worker = worker.with_app_config_updates(app_config_receiver);
+                if let Some(notifier) = notifier {
+                    worker = worker.with_notifier(notifier);
+                }
+                let config_reloader = spawn_config_reloader(
+                    args.config.clone(),
+                    app_config_source,
+                    app_config_updates,
+                    CONFIG_POLL_INTERVAL,
+                );
+                let token_metadata = worker
+                    .processor_configs
+                    .iter()
+                    .any(|p| matches!(p, ProcessorConfig::TokenProcessor))
+                    .then(|| TokenMetadataResolver::new(worker.db_pool.clone(), worker.client.clone()));
 
                 println!("🚀 Starting real-time indexer");
 
-                worker.run().await?;
+                let token_metadata = token_metadata.map(TokenMetadataResolver::spawn);
+                let webhooks = webhooks.map(WebhookDispatcher::spawn);
+                let result = worker.run().await;
+                if let Some(token_metadata) = token_metadata {
+                    token_metadata.abort();
+                }
+                if let Some(webhooks) = webhooks {
+                    webhooks.abort();
+                }
+                config_reloader.abort();
+                result?;
             }
             RunMode::Backfill(args) => {
-                let config = args.clone().into();
+                let (config, app_config) = load_configs(&args.config, app_config)?;
 
                 let worker = new_backfill_worker_from_config(
                     args.start,
                     args.stop,
+                    args.job.clone(),
                     &config,
-                    &processor_factories,
-                    include_default_processors,
-                    app_config.clone(),
+                    &options,
+                    app_config,
                 )
                 .await?;
 
@@ -240,14 +438,363 @@ pub async fn run_command(
                 worker.run().await?;
             }
         },
+        Commands::Migrate(args) => {
+            if !MIGRATIONS_SUPPORTED {
+                anyhow::bail!("Built without migration support, enable the libpq feature of bento-core");
+            }
+            let (config, app_config) = load_configs(&args.config, app_config)?;
+
+            let worker = new_realtime_worker_from_config(&config, &options, app_config).await?;
+
+            println!("Applying pending migrations...");
+            worker.run_migrations().await?;
+            println!("Migrations are up to date");
+        }
+        Commands::Reindex(args) => {
+            if args.from >= args.to {
+                anyhow::bail!("--from must be before --to");
+            }
+            let (config, app_config) = load_configs(&args.config, app_config)?;
+
+            let mut worker =
+                new_backfill_worker_from_config(Some(args.from), Some(args.to), None, &config, &options, app_config)
+                    .await?;
+
+            if !args.processors.is_empty() {
+                let unknown: Vec<_> = args
+                    .processors
+                    .iter()
+                    .filter(|name| !worker.processor_configs.iter().any(|p| p.name() == name.as_str()))
+                    .collect();
+                if !unknown.is_empty() {
+                    anyhow::bail!("Unknown processors: {:?}", unknown);
+                }
+                worker.processor_configs.retain(|p| args.processors.iter().any(|name| name == p.name()));
+            }
+
+            worker.run_migrations().await?;
+
+            if args.all && options.include_default_processors {
+                println!(
+                    "⚠️  --all includes the core processors: blocks, transactions and events are purged and rebuilt"
+                );
+            }
+            let names: Vec<_> = worker.processor_configs.iter().map(|p| p.name()).collect();
+            println!("Reindexing {} to {} for processors: {:?}", args.from, args.to, names);
+            worker.reindex(args.from, args.to, config.backfill.step).await?;
+            println!("Reindex complete");
+        }
+        Commands::Gaps(gaps) => match gaps.mode {
+            GapsMode::Detect(args) => {
+                let (config, _) = load_configs(&args.config, app_config)?;
+                let db_pool = new_db_pool(&get_database_url()?, None).await?;
+
+                let opts = GapOptions {
+                    min_height: args.min_height,
+                    since_ts: args.since,
+                    threshold: config.worker.gap_threshold,
+                };
+                let report = detect_gaps(&db_pool, &opts).await?;
+                if args.json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    print!("{}", gaps::render_report(&report));
+                }
+            }
+            GapsMode::Fill(args) => {
+                let (config, app_config) = load_configs(&args.config, app_config)?;
+
+                let worker = new_backfill_worker_from_config(None, None, None, &config, &options, app_config).await?;
+                worker.run_migrations().await?;
+
+                let opts = GapOptions {
+                    min_height: args.min_height,
+                    since_ts: args.since,
+                    threshold: config.worker.gap_threshold,
+                };
+                let report = detect_gaps(&worker.db_pool, &opts).await?;
+                if report.is_empty() {
+                    println!("No gaps found");
+                    return Ok(());
+                }
+
+                let summary = worker.fill_gaps(&report, worker.workers).await?;
+                if args.json {
+                    println!("{}", serde_json::to_string_pretty(&summary)?);
+                } else {
+                    println!(
+                        "Filled {} heights ({} failed) and {} timestamp ranges ({} failed)",
+                        summary.heights_filled, summary.heights_failed, summary.ranges_filled, summary.ranges_failed
+                    );
+                }
+            }
+        },
+        Commands::Config(command) => match command.mode {
+            ConfigMode::Check(args) => {
+                let mut processors: Vec<&str> = options.processor_factories.keys().map(String::as_str).collect();
+                processors.sort();
+
+                let check = check_config(&args, app_config, &processors);
+                for warning in &check.warnings {
+                    println!("warning: {}", warning);
+                }
+                if !check.errors.is_empty() {
+                    anyhow::bail!("{}", check.errors);
+                }
+                println!("{} is valid", args.config_path);
+            }
+        },
+        Commands::Status(args) => {
+            let (config, _) = load_configs(&args.config, app_config)?;
+            let db_pool = new_db_pool(&get_database_url()?, None).await?;
+            let client = Client::new(config.resolve_network()?);
+
+            let report = status::collect_status(&db_pool, &client).await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", status::render_table(&report));
+            }
+        }
+        Commands::ApiKeys(command) => {
+            let db_pool = new_db_pool(&get_database_url()?, None).await?;
+            match command.mode {
+                ApiKeysMode::Create(args) => {
+                    let (model, key) = create_api_key(db_pool, &args.name, args.requests_per_minute).await?;
+                    println!("Created API key {} (id {})", model.name, model.id);
+                    println!("{}", key);
+                    println!("Store it now, it cannot be shown again");
+                }
+                ApiKeysMode::List => {
+                    for key in list_api_keys(db_pool).await? {
+                        let limit = key.requests_per_minute.map_or("default".to_string(), |n| format!("{}/min", n));
+                        let status = key.revoked_at.map_or("active".to_string(), |at| format!("revoked {}", at));
+                        println!("{:<30} {:<12} created {}  {}", key.name, limit, key.created_at, status);
+                    }
+                }
+                ApiKeysMode::Revoke(args) => {
+                    revoke_api_key(db_pool, &args.name).await?;
+                    println!("Revoked API key {}", args.name);
+                }
+            }
+        }
+        Commands::Export(args) => {
+            let table = options.export_tables.get(&args.table).with_context(|| {
+                format!("Unknown export table {}, registered: {}", args.table, table_names(&options.export_tables))
+            })?;
+            let db_pool = new_db_pool(&get_database_url()?, None).await?;
+
+            let columns = load_export_columns(&db_pool, table).await?;
+            let request = ExportRequest {
+                from_ts: args.from,
+                to_ts: args.to,
+                filters: args.filters.into_iter().collect(),
+                format: args.format,
+            };
+            let mut chunks = ExportPlan::new(table.clone(), columns, request)?.spawn(db_pool);
+
+            let mut output: Box<dyn AsyncWrite + Unpin> = match &args.output {
+                Some(path) => Box::new(tokio::fs::File::create(path).await?),
+                None => Box::new(tokio::io::stdout()),
+            };
+            let mut written = 0;
+            while let Some(chunk) = chunks.recv().await {
+                let chunk = chunk?;
+                output.write_all(&chunk).await?;
+                written += chunk.len();
+            }
+            output.flush().await?;
+
+            if let Some(path) = &args.output {
+                eprintln!("Exported {} bytes of {} to {}", written, args.table, path.display());
+            }
+        }
+        Commands::Webhooks(command) => {
+            let db_pool = new_db_pool(&get_database_url()?, None).await?;
+            match command.mode {
+                WebhooksMode::Create(args) => {
+                    let subscription = NewWebhookSubscriptionModel {
+                        name: args.name,
+                        url: args.url,
+                        notification_type: args.notification_type,
+                        contract_address: args.contract_address,
+                        event_index: args.event_index,
+                        address: args.address,
+                        topic: args.topic,
+                        ..Default::default()
+                    };
+                    let model = create_webhook_subscription(db_pool, subscription).await?;
+                    println!("Created webhook subscription {} (id {})", model.name, model.id);
+                    println!("{}", model.secret);
+                    println!("Store the signing secret now, it cannot be shown again");
+                }
+                WebhooksMode::List => {
+                    for subscription in list_webhook_subscriptions(db_pool).await? {
+                        let status =
+                            subscription.disabled_at.map_or("active".to_string(), |at| format!("disabled {}", at));
+                        println!(
+                            "{:<30} {:<50} {:<30} created {}  {}",
+                            subscription.name,
+                            subscription.url,
+                            webhook_filters(&subscription),
+                            subscription.created_at,
+                            status
+                        );
+                    }
+                }
+                WebhooksMode::Disable(args) => {
+                    disable_webhook_subscription(db_pool, &args.name).await?;
+                    println!("Disabled webhook subscription {}", args.name);
+                }
+                WebhooksMode::DeadLetters(args) => {
+                    let subscription = get_webhook_subscription(db_pool.clone(), &args.name)
+                        .await?
+                        .with_context(|| format!("No webhook subscription named {}", args.name))?;
+                    for letter in list_webhook_dead_letters(db_pool, subscription.id, args.limit).await? {
+                        println!(
+                            "{}  {} attempts  {}  {}",
+                            letter.failed_at,
+                            letter.attempts,
+                            letter.last_error.as_deref().unwrap_or("-"),
+                            letter.payload
+                        );
+                    }
+                }
+                WebhooksMode::Replay(args) => {
+                    let subscription = get_webhook_subscription(db_pool.clone(), &args.name)
+                        .await?
+                        .with_context(|| format!("No webhook subscription named {}", args.name))?;
+                    let replayed = replay_webhook_dead_letters(db_pool, subscription.id).await?;
+                    println!("Requeued {} dead letters of {}", replayed, args.name);
+                }
+            }
+        }
     }
     Ok(())
 }
 
+/// Dispatcher settings from the `[webhooks]` section, defaults for the unset ones.
+fn webhook_config(config: &WebhooksConfig) -> WebhookConfig {
+    let defaults = WebhookConfig::default();
+    WebhookConfig {
+        max_attempts: config.max_attempts.unwrap_or(defaults.max_attempts),
+        initial_backoff: config.initial_backoff.map(Duration::from_millis).unwrap_or(defaults.initial_backoff),
+        max_backoff: config.max_backoff.map(Duration::from_millis).unwrap_or(defaults.max_backoff),
+        timeout: config.timeout.map(Duration::from_millis).unwrap_or(defaults.timeout),
+        ..defaults
+    }
+}
+
+fn webhook_filters(subscription: &WebhookSubscriptionModel) -> String {
+    let filters: Vec<String> = [
+        ("type", subscription.notification_type.clone()),
+        ("contract", subscription.contract_address.clone()),
+        ("event", subscription.event_index.map(|index| index.to_string())),
+        ("address", subscription.address.clone()),
+        ("topic", subscription.topic.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| format!("{}={}", name, value)))
+    .collect();
+
+    match filters.is_empty() {
+        true => "all".to_string(),
+        false => filters.join(","),
+    }
+}
+
+fn table_names(tables: &ExportTables) -> String {
+    tables.iter().map(|t| t.name.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+/// Load the config file with the environment layered over it.
 pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Config> {
-    let content = fs::read_to_string(path).context("Failed to read config file")?;
-    let config: Config = toml::from_str(&content).context("Failed to parse config file")?;
-    Ok(config)
+    Ok(ConfigLoader::new(path.as_ref()).load()?)
+}
+
+/// Watch the config file every `poll_interval`, and SIGHUP on unix, and send the app config rebuilt from the reloaded
+/// config to the worker. A config that fails to load is reported and the current one is kept.
+fn spawn_config_reloader(
+    args: ConfigArgs,
+    app_config: Option<AppConfigSource>,
+    updates: watch::Sender<Option<Arc<dyn AppConfigTrait>>>,
+    poll_interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let modified = |path: &str| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    // Taken before spawning, so a change made before the task first runs is not missed
+    let mut last_modified = modified(&args.config_path);
+    tokio::spawn(async move {
+        let mut hangup = HangupSignal::new();
+
+        loop {
+            let reason = tokio::select! {
+                _ = tokio::time::sleep(poll_interval) => {
+                    let current = modified(&args.config_path);
+                    if current == last_modified {
+                        continue;
+                    }
+                    last_modified = current;
+                    "config file changed"
+                }
+                _ = hangup.recv() => "received SIGHUP",
+            };
+
+            match load_configs(&args, app_config) {
+                Ok((_, app_config)) => {
+                    updates.send_replace(app_config);
+                    tracing::info!("Reloaded processor config ({}), applying from the next batch", reason);
+                }
+                Err(err) => tracing::warn!("Failed to reload config ({}), keeping the current one: {:#}", reason, err),
+            }
+        }
+    })
+}
+
+#[cfg(unix)]
+struct HangupSignal(Option<tokio::signal::unix::Signal>);
+
+#[cfg(unix)]
+impl HangupSignal {
+    fn new() -> Self {
+        Self(tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok())
+    }
+
+    async fn recv(&mut self) {
+        match &mut self.0 {
+            Some(signal) => {
+                signal.recv().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct HangupSignal;
+
+#[cfg(not(unix))]
+impl HangupSignal {
+    fn new() -> Self {
+        Self
+    }
+
+    async fn recv(&mut self) {
+        std::future::pending().await
+    }
+}
+
+/// Load the layered config selected by `args` and the app config built from it.
+fn load_configs(
+    args: &ConfigArgs,
+    app_config: Option<AppConfigSource>,
+) -> Result<(Config, Option<Arc<dyn AppConfigTrait>>)> {
+    let mut loader = args.loader();
+    if let Some(source) = &app_config {
+        loader = loader.env_aliases(source.env_aliases);
+    }
+    let config = loader.load()?;
+    let app_config = app_config.map(|source| (source.load)(&config)).transpose()?;
+    Ok((config, app_config))
 }
 
 #[cfg(test)]
@@ -289,10 +836,17 @@ mod tests {
         let config_path = create_test_config_file(temp_dir.path(), config_content);
 
         // Create CLI args with the path to our test config
-        let args =
-            CliArgs { config_path: config_path.to_string_lossy().to_string(), network: Some("testnet".to_string()) };
+        let args = CliArgs {
+            config: ConfigArgs {
+                config_path: config_path.to_string_lossy().to_string(),
+                network: Some("testnet".to_string()),
+                set: vec![],
+            },
+        };
 
-        let config: Config = args.clone().into();
+        let (config, app_config) = load_configs(&args.config, None).expect("Failed to load config");
+        assert!(app_config.is_none());
+        assert_eq!(config.network.as_deref(), Some("testnet"));
 
         // Verify the config was loaded correctly
         assert_eq!(config.worker.request_interval, 500);
@@ -311,12 +865,75 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Failed to read config file")]
     fn test_error_on_missing_config_file() {
-        let args =
-            CliArgs { config_path: "non_existent_config.toml".to_string(), network: Some("testnet".to_string()) };
+        let args = CliArgs {
+            config: ConfigArgs {
+                config_path: "non_existent_config.toml".to_string(),
+                network: Some("testnet".to_string()),
+                set: vec![],
+            },
+        };
+
+        let err = load_configs(&args.config, None).unwrap_err();
+        assert!(err.to_string().contains("Failed to read config file"));
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct TestAppConfig {
+        threshold: u64,
+    }
+
+    impl AppConfigTrait for TestAppConfig {
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    const TEST_APP_CONFIG: AppConfigSource = AppConfigSource {
+        env_aliases: &[],
+        sections: &[],
+        load: |config| Ok(Arc::new(config.processor_section::<TestAppConfig>("test")?)),
+    };
+
+    fn threshold(app_config: &Option<Arc<dyn AppConfigTrait>>) -> u64 {
+        app_config.as_ref().unwrap().as_any().downcast_ref::<TestAppConfig>().unwrap().threshold
+    }
+
+    /// Rewrite the config file with a distinct modification time, so the change is seen even
+    /// within the timestamp resolution of the file system.
+    fn rewrite_config(path: &Path, processor: &str, mtime_secs: u64) {
+        let content = format!(
+            "[worker]\nrequest_interval = 500\nstep = 60000\nbackstep = 300000\n\n[server]\n\n[backfill]\n\
+             request_interval = 1000\nworkers = 2\nstep = 1800000\nbackstep = 600000\n\n[processors.test]\n{}\n",
+            processor
+        );
+        std::fs::write(path, content).unwrap();
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::UNIX_EPOCH + Duration::from_secs(mtime_secs)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_config_reloader_applies_valid_changes_only() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let config_path = temp_dir.path().join("config.toml");
+        rewrite_config(&config_path, "threshold = 1", 1);
+
+        let args = ConfigArgs { config_path: config_path.to_string_lossy().to_string(), network: None, set: vec![] };
+        let (_, app_config) = load_configs(&args, Some(TEST_APP_CONFIG)).unwrap();
+        let (updates, mut receiver) = watch::channel(app_config);
+        let reloader = spawn_config_reloader(args, Some(TEST_APP_CONFIG), updates, Duration::from_millis(10));
+
+        rewrite_config(&config_path, "threshold = 2", 2);
+        tokio::time::timeout(Duration::from_secs(5), receiver.changed()).await.unwrap().unwrap();
+        assert_eq!(threshold(&receiver.borrow_and_update()), 2);
+
+        // An app config that fails to load is not sent, the worker keeps the last valid one
+        rewrite_config(&config_path, "threshold = \"high\"", 3);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!receiver.has_changed().unwrap());
+        assert_eq!(threshold(&receiver.borrow()), 2);
 
-        let _: Config = args.clone().into();
+        reloader.abort();
     }
 
     #[test]
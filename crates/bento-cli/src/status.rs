@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use bento_core::{Client, DbPool};
+use bento_trait::stage::BlockProvider;
+use bento_types::{
+    repository::{get_block_gap_summary, get_latest_block, get_processor_statuses, ChainGapSummary},
+    DEFAULT_GROUP_NUM,
+};
+use futures::future::try_join_all;
+use serde::Serialize;
+
+use crate::display_timestamp;
+
+/// Sync state of one chain: the node tip against the latest stored block.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainStatus {
+    pub chain_from: i64,
+    pub chain_to: i64,
+    pub node_height: Option<i64>,
+    pub indexed_height: Option<i64>,
+    /// Timestamp of the latest stored block in milliseconds
+    pub indexed_timestamp: Option<i64>,
+    pub lag_blocks: Option<i64>,
+    /// Set when the node could not be queried for this chain
+    pub error: Option<String>,
+}
+
+/// Checkpoint of one processor.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessorStatus {
+    pub processor: String,
+    /// Timestamp of the latest block stored by the processor in milliseconds
+    pub last_timestamp: i64,
+    pub lag_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    pub chains: Vec<ChainStatus>,
+    pub processors: Vec<ProcessorStatus>,
+    pub gaps: Vec<ChainGapSummary>,
+}
+
+/// Collect the sync status of every chain and processor.
+pub async fn collect_status(db_pool: &Arc<DbPool>, client: &Client) -> Result<StatusReport> {
+    let chains = (0..DEFAULT_GROUP_NUM)
+        .flat_map(|from| (0..DEFAULT_GROUP_NUM).map(move |to| (from, to)))
+        .map(|(from, to)| chain_status(db_pool, client, from, to));
+    let chains = try_join_all(chains).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let processors = get_processor_statuses(db_pool.clone())
+        .await?
+        .into_iter()
+        .map(|s| ProcessorStatus {
+            lag_ms: now - s.last_timestamp,
+            processor: s.processor,
+            last_timestamp: s.last_timestamp,
+        })
+        .collect();
+
+    let gaps = get_block_gap_summary(db_pool).await?;
+
+    Ok(StatusReport { chains, processors, gaps })
+}
+
+/// Compare the node tip of a chain with its latest stored block.
+async fn chain_status(db_pool: &Arc<DbPool>, client: &Client, chain_from: i64, chain_to: i64) -> Result<ChainStatus> {
+    let (latest, info) = tokio::join!(
+        get_latest_block(db_pool, chain_from, chain_to),
+        client.get_chain_info(chain_from as u32, chain_to as u32)
+    );
+    let latest = latest?;
+    let indexed_height = latest.as_ref().map(|b| b.height);
+    let indexed_timestamp = latest.as_ref().map(|b| b.timestamp.and_utc().timestamp_millis());
+
+    let (node_height, error) = match info {
+        Ok(info) => (Some(info.current_height), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+    // A stale node tip below the indexed height counts as caught up
+    let lag_blocks = node_height.map(|node| (node - indexed_height.unwrap_or(-1)).max(0));
+
+    Ok(ChainStatus { chain_from, chain_to, node_height, indexed_height, indexed_timestamp, lag_blocks, error })
+}
+
+fn display_opt(value: Option<i64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/// Render the report as plain text tables.
+pub fn render_table(report: &StatusReport) -> String {
+    let mut out = String::new();
+
+    out.push_str("Chains\n");
+    out.push_str(&format!(
+        "{:<8} {:>12} {:>12} {:>10}  {:<19}\n",
+        "CHAIN", "NODE", "INDEXED", "LAG", "LATEST BLOCK (UTC)"
+    ));
+    for chain in &report.chains {
+        out.push_str(&format!(
+            "{:<8} {:>12} {:>12} {:>10}  {:<19}",
+            format!("{}->{}", chain.chain_from, chain.chain_to),
+            display_opt(chain.node_height),
+            display_opt(chain.indexed_height),
+            display_opt(chain.lag_blocks),
+            chain.indexed_timestamp.map(display_timestamp).unwrap_or_else(|| "-".to_string()),
+        ));
+        if let Some(err) = &chain.error {
+            out.push_str(&format!("  node error: {}", err));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("\nProcessors\n");
+    if report.processors.is_empty() {
+        out.push_str("No processor checkpoints recorded\n");
+    } else {
+        out.push_str(&format!("{:<20} {:<19}  {:>12}\n", "PROCESSOR", "CHECKPOINT (UTC)", "LAG (s)"));
+        for processor in &report.processors {
+            out.push_str(&format!(
+                "{:<20} {:<19}  {:>12}\n",
+                processor.processor,
+                display_timestamp(processor.last_timestamp),
+                processor.lag_ms / 1000
+            ));
+        }
+    }
+
+    out.push_str("\nGaps\n");
+    if report.gaps.is_empty() {
+        out.push_str("No gaps found\n");
+    } else {
+        out.push_str(&format!("{:<8} {:>8} {:>16}\n", "CHAIN", "GAPS", "MISSING BLOCKS"));
+        for gap in &report.gaps {
+            out.push_str(&format!(
+                "{:<8} {:>8} {:>16}\n",
+                format!("{}->{}", gap.chain_from, gap.chain_to),
+                gap.gap_count,
+                gap.missing_blocks
+            ));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_table() {
+        let report = StatusReport {
+            chains: vec![ChainStatus {
+                chain_from: 0,
+                chain_to: 1,
+                node_height: Some(120),
+                indexed_height: Some(100),
+                indexed_timestamp: Some(1_700_000_000_000),
+                lag_blocks: Some(20),
+                error: None,
+            }],
+            processors: vec![],
+            gaps: vec![ChainGapSummary { chain_from: 0, chain_to: 1, gap_count: 2, missing_blocks: 7 }],
+        };
+
+        let table = render_table(&report);
+        assert!(table.contains("0->1"));
+        assert!(table.contains("2023-11-14 22:13:20"));
+        assert!(table.contains("No processor checkpoints recorded"));
+        assert!(table.lines().any(|line| line.starts_with("0->1") && line.ends_with('7')));
+    }
+}
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
+use bento_types::export::ExportFormat;
 use clap::{Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 
-use crate::load_config;
+use crate::config::ConfigArgs;
 
 #[derive(Parser)]
 #[command(name = "cli")]
@@ -16,6 +18,184 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     Run(RunCommand),
+    /// Apply pending framework and app migrations
+    Migrate(CliArgs),
+    /// Show per-chain and per-processor sync status
+    Status(StatusArgs),
+    /// Delete and rebuild the data of a timestamp range
+    Reindex(ReindexArgs),
+    /// Detect or fill missing blocks
+    Gaps(GapsCommand),
+    /// Inspect the layered configuration
+    Config(ConfigCommand),
+    /// Manage the API keys of the server
+    ApiKeys(ApiKeysCommand),
+    /// Export the rows of a registered table in a time window
+    Export(ExportArgs),
+    /// Manage webhook subscriptions and their dead letters
+    Webhooks(WebhooksCommand),
+}
+
+#[derive(Subcommand)]
+pub enum WebhooksMode {
+    /// Create a subscription and print its signing secret, it cannot be shown again
+    Create(WebhookCreateArgs),
+    /// List subscriptions, disabled ones included
+    List,
+    /// Disable a subscription, its pending deliveries are dropped
+    Disable(WebhookNameArgs),
+    /// Show the latest deliveries of a subscription that failed every attempt
+    DeadLetters(WebhookDeadLettersArgs),
+    /// Enqueue the dead letters of a subscription again
+    Replay(WebhookNameArgs),
+}
+
+#[derive(Args)]
+pub struct WebhooksCommand {
+    #[command(subcommand)]
+    pub mode: WebhooksMode,
+}
+
+#[derive(Args, Clone)]
+pub struct WebhookCreateArgs {
+    /// Unique name of the subscription
+    #[arg(long)]
+    pub name: String,
+
+    /// URL the notifications are posted to
+    #[arg(long)]
+    pub url: String,
+
+    /// Only deliver notifications of this kind: block, event, address_activity or custom
+    #[arg(long = "type")]
+    pub notification_type: Option<String>,
+
+    /// Only deliver events emitted by this contract
+    #[arg(long = "contract")]
+    pub contract_address: Option<String>,
+
+    /// Only deliver events with this index
+    #[arg(long = "event-index")]
+    pub event_index: Option<i32>,
+
+    /// Only deliver the activity of this address
+    #[arg(long)]
+    pub address: Option<String>,
+
+    /// Only deliver custom notifications of this topic
+    #[arg(long)]
+    pub topic: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct WebhookNameArgs {
+    /// Name of the subscription
+    #[arg(long)]
+    pub name: String,
+}
+
+#[derive(Args, Clone)]
+pub struct WebhookDeadLettersArgs {
+    /// Name of the subscription
+    #[arg(long)]
+    pub name: String,
+
+    /// Maximum number of dead letters shown
+    #[arg(long, default_value_t = 20)]
+    pub limit: i64,
+}
+
+#[derive(Args, Clone)]
+pub struct ExportArgs {
+    /// Name of the table, see `GET /v1/exports` for the registered ones
+    pub table: String,
+
+    /// Start of the window in milliseconds, inclusive
+    #[arg(long = "from")]
+    pub from: i64,
+
+    /// End of the window in milliseconds, exclusive
+    #[arg(long = "to")]
+    pub to: i64,
+
+    /// csv, ndjson or parquet
+    #[arg(long, default_value = "ndjson")]
+    pub format: ExportFormat,
+
+    /// Only export rows whose column has this text value, as `column=value`
+    #[arg(long = "filter", value_parser = parse_filter)]
+    pub filters: Vec<(String, String)>,
+
+    /// File to write, stdout when omitted
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+fn parse_filter(filter: &str) -> Result<(String, String), String> {
+    filter
+        .split_once('=')
+        .map(|(column, value)| (column.to_string(), value.to_string()))
+        .ok_or_else(|| format!("Invalid filter {filter}, expected column=value"))
+}
+
+#[derive(Subcommand)]
+pub enum ApiKeysMode {
+    /// Create a key and print it, it cannot be shown again
+    Create(ApiKeyCreateArgs),
+    /// List keys, revoked ones included
+    List,
+    /// Revoke a key
+    Revoke(ApiKeyNameArgs),
+}
+
+#[derive(Args)]
+pub struct ApiKeysCommand {
+    #[command(subcommand)]
+    pub mode: ApiKeysMode,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiKeyCreateArgs {
+    /// Unique name of the key, e.g. the client it is issued to
+    #[arg(long)]
+    pub name: String,
+
+    /// Requests per minute allowed to the key, `server.key_requests_per_minute` when omitted
+    #[arg(long = "requests-per-minute", value_parser = clap::value_parser!(i32).range(1..))]
+    pub requests_per_minute: Option<i32>,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiKeyNameArgs {
+    /// Name of the key
+    #[arg(long)]
+    pub name: String,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigMode {
+    /// Load the config file, environment and flags and report all errors
+    Check(ConfigArgs),
+}
+
+#[derive(Args)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    pub mode: ConfigMode,
+}
+
+#[derive(Subcommand)]
+pub enum GapsMode {
+    /// Report missing heights and periods without blocks
+    Detect(GapsArgs),
+    /// Sync the detected gaps through all registered processors
+    Fill(GapsArgs),
+}
+
+#[derive(Args)]
+pub struct GapsCommand {
+    #[command(subcommand)]
+    pub mode: GapsMode,
 }
 
 #[derive(Subcommand)]
@@ -27,20 +207,14 @@ pub enum RunMode {
 
 #[derive(Args, Clone)]
 pub struct CliArgs {
-    /// Path to the config file
-    #[arg(short, long, default_value = "config.toml")]
-    pub config_path: String,
-
-    /// The network to run the command on
-    #[arg(short, long = "network", value_parser = ["devnet", "testnet", "mainnet"])]
-    pub network: Option<String>,
+    #[command(flatten)]
+    pub config: ConfigArgs,
 }
 
 #[derive(Args, Clone)]
 pub struct BackfillArgs {
-    /// Path to the config file
-    #[arg(short, long, default_value = "config.toml")]
-    pub config_path: String,
+    #[command(flatten)]
+    pub config: ConfigArgs,
 
     /// The timestamp to start the backfill from
     #[arg(long = "start")]
@@ -49,25 +223,78 @@ pub struct BackfillArgs {
     /// The timestamp to stop the backfill at
     #[arg(long = "stop")]
     pub stop: Option<u64>,
+
+    /// Name of a backfill job shared between processes. Processes started with the same job,
+    /// start and stop split the range between them and resume it after a restart.
+    #[arg(long = "job")]
+    pub job: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct StatusArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    /// Print the status as JSON instead of tables
+    #[arg(long)]
+    pub json: bool,
 }
 
-impl From<CliArgs> for Config {
-    fn from(args: CliArgs) -> Self {
-        load_config(&args.config_path).expect("Failed to load config")
-    }
+#[derive(Args, Clone)]
+pub struct ReindexArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    /// The timestamp to start reindexing from
+    #[arg(long = "from")]
+    pub from: u64,
+
+    /// The timestamp to stop reindexing at
+    #[arg(long = "to")]
+    pub to: u64,
+
+    /// Processors to reindex
+    #[arg(long = "processor", required_unless_present = "all")]
+    pub processors: Vec<String>,
+
+    /// Reindex every registered processor, including the core ones rebuilding blocks,
+    /// transactions and events
+    #[arg(long, conflicts_with = "processors")]
+    pub all: bool,
 }
 
-impl From<BackfillArgs> for Config {
-    fn from(args: BackfillArgs) -> Self {
-        load_config(&args.config_path).expect("Failed to load config")
-    }
+#[derive(Args, Clone)]
+pub struct GapsArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    /// Only consider blocks at or above this height, e.g. for apps deployed after genesis
+    #[arg(long = "min-height", default_value_t = 0)]
+    pub min_height: i64,
+
+    /// Only consider blocks at or after this timestamp
+    #[arg(long = "since")]
+    pub since: Option<u64>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
+    /// Network name, one of devnet, testnet or mainnet
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Custom node URL, takes precedence over the network's default node
+    #[serde(default)]
+    pub rpc_url: Option<String>,
     pub worker: WorkerConfig,
+    #[serde(default)]
     pub server: ServerConfig,
     pub backfill: BackfillConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
     pub processors: Option<ProcessorsConfig>,
     pub price_service: Option<PriceServiceConfig>,
     pub points: Option<PointsConfig>,
@@ -78,10 +305,56 @@ pub struct WorkerConfig {
     pub request_interval: u64,
     pub step: u64,
     pub backstep: u64,
+    /// Interval in milliseconds between gap checks of the realtime worker, disabled when unset
+    #[serde(default)]
+    pub gap_check_interval: Option<u64>,
+    /// How far back in milliseconds each gap check of the realtime worker looks
+    #[serde(default = "default_gap_lookback")]
+    pub gap_lookback: u64,
+    /// Minimum duration in milliseconds without blocks on a chain reported as a gap
+    #[serde(default = "default_gap_threshold")]
+    pub gap_threshold: u64,
+    /// Publish what the realtime worker stores with Postgres `NOTIFY`, for the `/v1/stream`
+    /// subscribers and cache invalidation of the servers
+    #[serde(default = "default_notify")]
+    pub notify: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct ServerConfig {}
+fn default_notify() -> bool {
+    true
+}
+
+fn default_gap_lookback() -> u64 {
+    24 * 60 * 60 * 1000 // 1 day
+}
+
+fn default_gap_threshold() -> u64 {
+    bento_core::workers::gaps::DEFAULT_GAP_THRESHOLD
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ServerConfig {
+    /// Requests per minute allowed to each client IP without an API key, unlimited when unset
+    #[serde(default)]
+    pub ip_requests_per_minute: Option<u32>,
+    /// Requests per minute allowed to each API key without its own limit, unlimited when unset
+    #[serde(default)]
+    pub key_requests_per_minute: Option<u32>,
+    /// Take the client IP from the last `X-Forwarded-For` entry, only safe behind a proxy appending it
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+    /// Blocks a chain may trail the node by before `/v1/health/ready` fails, 3 when unset
+    #[serde(default)]
+    pub health_max_chain_lag: Option<i64>,
+    /// Age in milliseconds of a processor checkpoint before `/v1/health/ready` fails, 10 minutes
+    /// when unset
+    #[serde(default)]
+    pub health_max_checkpoint_age: Option<u64>,
+    /// Milliseconds each database and node call of `/v1/health/ready` may take, 2 seconds when
+    /// unset
+    #[serde(default)]
+    pub health_check_timeout: Option<u64>,
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BackfillConfig {
@@ -91,6 +364,25 @@ pub struct BackfillConfig {
     pub workers: usize,
 }
 
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct WebhooksConfig {
+    /// Enqueue webhook deliveries from the realtime worker and send them
+    #[serde(default)]
+    pub enabled: bool,
+    /// Attempts before a delivery is moved to the dead letters, 8 when unset
+    #[serde(default)]
+    pub max_attempts: Option<i32>,
+    /// Delay in milliseconds before the first retry, doubled after each failure, 10 seconds when unset
+    #[serde(default)]
+    pub initial_backoff: Option<u64>,
+    /// Maximum delay in milliseconds between retries, 1 hour when unset
+    #[serde(default)]
+    pub max_backoff: Option<u64>,
+    /// Timeout in milliseconds of each request, 10 seconds when unset
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProcessorsConfig {
     #[serde(flatten)]
@@ -1,11 +1,16 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use bento_trait::stage::ContractsProvider;
-use bento_types::{CallContractParams, CallContractResult, ContractState};
+use bento_types::{
+    CallContractParams, CallContractResult, ContractState, MultipleCallContract, MultipleCallContractResult,
+};
 use url::Url;
 
 use super::Client;
 
+/// Calls sent per `multicall-contract` request, the node rejects larger batches.
+pub const MAX_MULTICALL_CALLS: usize = 20;
+
 #[async_trait]
 impl ContractsProvider for Client {
     async fn call_contract(&self, params: CallContractParams) -> Result<CallContractResult> {
@@ -23,6 +28,30 @@ impl ContractsProvider for Client {
         let result: CallContractResult = serde_json::from_str(&body)?;
         Ok(result)
     }
+
+    /// Run the calls through `contracts/multicall-contract`, [`MAX_MULTICALL_CALLS`] per request.
+    async fn multicall_contract(&self, calls: Vec<CallContractParams>) -> Result<Vec<CallContractResult>> {
+        let url = Url::parse(&format!("{}/contracts/multicall-contract", self.base_url))?;
+        let mut results = Vec::with_capacity(calls.len());
+        for chunk in calls.chunks(MAX_MULTICALL_CALLS) {
+            let json_body = serde_json::to_string(&MultipleCallContract { calls: chunk })?;
+            let response =
+                self.inner.post(url.clone()).header("Content-Type", "application/json").body(json_body).send().await?;
+
+            let status = response.status();
+            let body = response.text().await?;
+            if !status.is_success() {
+                tracing::error!("multicall_contract failed (HTTP {}): {}", status, body);
+                anyhow::bail!("multicall_contract HTTP {}: {}", status, body);
+            }
+            let result: MultipleCallContractResult = serde_json::from_str(&body)?;
+            if result.results.len() != chunk.len() {
+                anyhow::bail!("multicall_contract returned {} results for {} calls", result.results.len(), chunk.len());
+            }
+            results.extend(result.results);
+        }
+        Ok(results)
+    }
 }
 
 impl Client {
@@ -1,8 +1,14 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use bento_trait::stage::TransactionProvider;
-use bento_types::Transaction;
-use serde::Deserialize;
+use bento_types::{
+    tx_builder::{
+        BuildExecuteScriptTx, BuildExecuteScriptTxResult, BuildTransferTx, BuildTransferTxResult, DecodeUnsignedTx,
+        DecodeUnsignedTxResult,
+    },
+    Transaction, TxStatus,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use url::Url;
 
 use super::Client;
@@ -54,15 +60,35 @@ impl Client {
     /// # Returns
     /// Transaction ID and routing groups on success
     pub async fn submit_transaction(&self, unsigned_tx: &str, signature: &str) -> Result<SubmitTxResponse> {
-        let endpoint = "transactions/submit";
-        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
-
         let payload = serde_json::json!({
             "unsignedTx": unsigned_tx,
             "signature": signature
         });
+        self.post_json("transactions/submit", &payload).await
+    }
+
+    /// Build an unsigned transfer from `from_public_key` to the destinations. Sign the returned
+    /// `unsigned_tx` with [`crate::signer::Signer`] and submit it, see [`Client::sign_and_submit`].
+    pub async fn build_transfer_tx(&self, request: &BuildTransferTx) -> Result<BuildTransferTxResult> {
+        self.post_json("transactions/build", request).await
+    }
+
+    /// Build an unsigned transaction running a compiled script, with its gas estimated by
+    /// simulating the execution.
+    pub async fn build_execute_script_tx(&self, request: &BuildExecuteScriptTx) -> Result<BuildExecuteScriptTxResult> {
+        self.post_json("contracts/unsigned-tx/execute-script", request).await
+    }
+
+    /// Decode a hex encoded unsigned transaction, e.g. to check what is signed.
+    pub async fn decode_unsigned_tx(&self, unsigned_tx: &str) -> Result<DecodeUnsignedTxResult> {
+        let request = DecodeUnsignedTx { unsigned_tx: unsigned_tx.to_string() };
+        self.post_json("transactions/decode-unsigned-tx", &request).await
+    }
 
-        let body = serde_json::to_string(&payload)?;
+    /// POST `body` to a node endpoint, failing with the `detail` of the node's error response.
+    async fn post_json<B: Serialize + ?Sized, R: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<R> {
+        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
+        let body = serde_json::to_string(body)?;
 
         let response = self.inner.post(url).header("Content-Type", "application/json").body(body).send().await?;
 
@@ -80,7 +106,36 @@ impl Client {
             return Err(anyhow::anyhow!("{}", error_message));
         }
 
-        let result: SubmitTxResponse = response.json().await?;
-        Ok(result)
+        Ok(response.json().await?)
+    }
+
+    /// Get the status of a transaction: confirmed with its block and confirmations, in the
+    /// mempool, or unknown to the node.
+    ///
+    /// # Arguments
+    /// * `tx_id` - The ID of the transaction
+    /// * `from_group` / `to_group` - Groups returned on submission, searched by the node when set
+    pub async fn get_transaction_status(
+        &self,
+        tx_id: &str,
+        from_group: Option<i32>,
+        to_group: Option<i32>,
+    ) -> Result<TxStatus> {
+        let mut url = Url::parse(&format!("{}/transactions/status", self.base_url))?;
+        url.query_pairs_mut().append_pair("txId", tx_id);
+        if let Some(from_group) = from_group {
+            url.query_pairs_mut().append_pair("fromGroup", &from_group.to_string());
+        }
+        if let Some(to_group) = to_group {
+            url.query_pairs_mut().append_pair("toGroup", &to_group.to_string());
+        }
+
+        let response = self.inner.get(url).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            anyhow::bail!("get_transaction_status HTTP {}: {}", status, body);
+        }
+        Ok(serde_json::from_str(&body)?)
     }
 }
@@ -1,7 +1,9 @@
+use std::fmt;
 use std::sync::Arc;
 
 use bento_trait::processor::{new_processor, DynProcessor, ProcessorTrait};
 use bento_types::config::AppConfigTrait;
+use diesel_migrations::EmbeddedMigrations;
 
 use crate::db::DbPool;
 
@@ -9,38 +11,110 @@ use crate::db::DbPool;
 pub type ProcessorFactory = fn(Arc<DbPool>, Option<Arc<dyn AppConfigTrait>>) -> Box<dyn ProcessorTrait>;
 
 /// Extensible processor configuration with support for custom processors
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum ProcessorConfig {
     /// Built-in processors
     BlockProcessor,
     EventProcessor,
     TxProcessor,
+    AddressProcessor,
+    TokenProcessor,
+    MiningProcessor,
 
     /// Custom processors with config
     Custom {
         name: String,
         factory: ProcessorFactory,
         config: Option<Arc<dyn AppConfigTrait>>,
+        /// Tables of the processor, applied after the framework migrations
+        migrations: Option<&'static EmbeddedMigrations>,
     },
 }
 
+impl fmt::Debug for ProcessorConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessorConfig::BlockProcessor => f.write_str("BlockProcessor"),
+            ProcessorConfig::EventProcessor => f.write_str("EventProcessor"),
+            ProcessorConfig::TxProcessor => f.write_str("TxProcessor"),
+            ProcessorConfig::AddressProcessor => f.write_str("AddressProcessor"),
+            ProcessorConfig::TokenProcessor => f.write_str("TokenProcessor"),
+            ProcessorConfig::MiningProcessor => f.write_str("MiningProcessor"),
+            ProcessorConfig::Custom { name, config, migrations, .. } => f
+                .debug_struct("Custom")
+                .field("name", name)
+                .field("config", config)
+                .field("migrations", &migrations.is_some())
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
 impl ProcessorConfig {
     pub fn name(&self) -> &str {
         match self {
             ProcessorConfig::BlockProcessor => "block",
             ProcessorConfig::EventProcessor => "event",
             ProcessorConfig::TxProcessor => "tx",
+            ProcessorConfig::AddressProcessor => "address",
+            ProcessorConfig::TokenProcessor => "token",
+            ProcessorConfig::MiningProcessor => "mining",
             ProcessorConfig::Custom { name, .. } => name,
         }
     }
 
+    /// Position of this processor when purging a range before a reindex. Built-in rows are
+    /// matched through the blocks and transactions of the range, so those are purged last.
+    pub fn purge_order(&self) -> u8 {
+        match self {
+            ProcessorConfig::Custom { .. } => 0,
+            ProcessorConfig::EventProcessor
+            | ProcessorConfig::AddressProcessor
+            | ProcessorConfig::TokenProcessor
+            | ProcessorConfig::MiningProcessor => 1,
+            ProcessorConfig::TxProcessor => 2,
+            ProcessorConfig::BlockProcessor => 3,
+        }
+    }
+
     /// Create a new custom processor config
     pub fn custom<S: Into<String>>(
         name: S,
         factory: ProcessorFactory,
         config: Option<Arc<dyn AppConfigTrait>>,
     ) -> Self {
-        Self::Custom { name: name.into(), factory, config }
+        Self::Custom { name: name.into(), factory, config, migrations: None }
+    }
+
+    /// The same custom processor creating its tables with `migrations`, run by
+    /// [`crate::Worker::run_migrations`]. Built-in tables are part of the framework migrations,
+    /// so built-in processors are returned unchanged.
+    pub fn with_migrations(self, migrations: &'static EmbeddedMigrations) -> Self {
+        match self {
+            ProcessorConfig::Custom { name, factory, config, .. } => {
+                ProcessorConfig::Custom { name, factory, config, migrations: Some(migrations) }
+            }
+            other => other,
+        }
+    }
+
+    /// Migrations registered with [`ProcessorConfig::with_migrations`]
+    pub fn migrations(&self) -> Option<&'static EmbeddedMigrations> {
+        match self {
+            ProcessorConfig::Custom { migrations, .. } => *migrations,
+            _ => None,
+        }
+    }
+
+    /// The same processor with `config` as its app config. Built-in processors take no config
+    /// and are returned unchanged.
+    pub fn with_app_config(&self, config: Option<Arc<dyn AppConfigTrait>>) -> Self {
+        match self {
+            ProcessorConfig::Custom { name, factory, migrations, .. } => {
+                ProcessorConfig::Custom { name: name.clone(), factory: *factory, config, migrations: *migrations }
+            }
+            other => other.clone(),
+        }
     }
 
     /// Build a processor from this config
@@ -53,6 +127,15 @@ impl ProcessorConfig {
                 new_processor(crate::processors::event_processor::EventProcessor::new(db_pool))
             }
             ProcessorConfig::TxProcessor => new_processor(crate::processors::tx_processor::TxProcessor::new(db_pool)),
+            ProcessorConfig::AddressProcessor => {
+                new_processor(crate::processors::address_processor::AddressProcessor::new(db_pool))
+            }
+            ProcessorConfig::TokenProcessor => {
+                new_processor(crate::processors::token_processor::TokenProcessor::new(db_pool))
+            }
+            ProcessorConfig::MiningProcessor => {
+                new_processor(crate::processors::mining_processor::MiningProcessor::new(db_pool))
+            }
             ProcessorConfig::Custom { factory, config, .. } => factory(db_pool, config.clone()),
         }
     }
@@ -1,13 +1,17 @@
 pub mod client;
 pub mod config;
 pub mod db;
+pub mod notify;
 pub mod processors;
+pub mod signer;
+pub mod webhooks;
 pub mod workers;
 pub mod ws;
 
 pub use client::*;
 pub use config::*;
 pub use db::*;
+pub use notify::*;
 pub use processors::*;
 pub use workers::*;
 pub use ws::*;
@@ -0,0 +1,155 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use bento_types::{
+    notifications::{IndexedNotification, INDEXED_NOTIFY_CHANNEL},
+    DbPool, NewWebhookDeliveryModel,
+};
+use diesel::sql_types::{Array, Text};
+use diesel_async::RunQueryDsl;
+use futures::StreamExt;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{broadcast, mpsc},
+};
+use tokio_postgres::AsyncMessage;
+
+use crate::{db::parse_db_url, webhooks::WebhookEnqueuer};
+
+/// Capacity of the broadcast channels carrying notifications. Subscribers falling further
+/// behind skip the oldest notifications.
+pub const NOTIFICATION_CAPACITY: usize = 1024;
+
+/// Postgres rejects NOTIFY payloads of 8000 bytes or more.
+const MAX_NOTIFY_PAYLOAD: usize = 7999;
+
+/// Delay before the listener reconnects after losing its connection.
+const LISTEN_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Publishes what the pipeline stores to the servers through Postgres `NOTIFY` (see
+/// [`spawn_listener`]) when `postgres` is set, and to webhook subscriptions when enabled.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    postgres: bool,
+    webhooks: Option<WebhookEnqueuer>,
+}
+
+impl Notifier {
+    pub fn new(postgres: bool) -> Self {
+        Self { postgres, webhooks: None }
+    }
+
+    /// Enqueue webhook deliveries of the notifications, see [`crate::webhooks`]
+    pub fn with_webhooks(mut self) -> Self {
+        self.webhooks = Some(WebhookEnqueuer::default());
+        self
+    }
+
+    /// Webhook deliveries of `notifications`, none when webhooks are disabled.
+    pub async fn webhook_deliveries(
+        &self,
+        db_pool: &Arc<DbPool>,
+        notifications: &[IndexedNotification],
+    ) -> Result<Vec<NewWebhookDeliveryModel>> {
+        match &self.webhooks {
+            Some(webhooks) => webhooks.deliveries(db_pool, notifications).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Send `notifications` with Postgres `NOTIFY`, when enabled.
+    pub async fn publish(&self, db_pool: &Arc<DbPool>, notifications: Vec<IndexedNotification>) -> Result<()> {
+        if notifications.is_empty() || !self.postgres {
+            return Ok(());
+        }
+
+        let payloads: Vec<String> = notifications
+            .iter()
+            .filter_map(|n| match serde_json::to_string(n) {
+                Ok(payload) if payload.len() <= MAX_NOTIFY_PAYLOAD => Some(payload),
+                Ok(_) => {
+                    tracing::warn!("Skipping {} notification too large for NOTIFY", n.kind());
+                    None
+                }
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to serialize notification");
+                    None
+                }
+            })
+            .collect();
+
+        let mut conn = db_pool.get().await?;
+        diesel::sql_query("SELECT pg_notify($1, payload) FROM unnest($2) AS payload")
+            .bind::<Text, _>(INDEXED_NOTIFY_CHANNEL)
+            .bind::<Array<Text>, _>(payloads)
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Forward the notifications other processes publish through Postgres to `sender`,
+/// reconnecting whenever the connection is lost. Runs until the task is aborted.
+pub fn spawn_listener(
+    database_url: String,
+    sender: broadcast::Sender<IndexedNotification>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = listen(&database_url, &sender).await {
+                tracing::error!(error = ?err, "Notification listener disconnected");
+            }
+            tokio::time::sleep(LISTEN_RETRY_INTERVAL).await;
+        }
+    })
+}
+
+async fn listen(database_url: &str, sender: &broadcast::Sender<IndexedNotification>) -> Result<()> {
+    let (url, tls) = parse_db_url(database_url)?;
+    if tls.is_enabled() {
+        let (client, connection) = tokio_postgres::connect(&url, tls.build_connector()?).await?;
+        forward(client, connection, sender).await
+    } else {
+        let (client, connection) = tokio_postgres::connect(&url, tokio_postgres::NoTls).await?;
+        forward(client, connection, sender).await
+    }
+}
+
+async fn forward<S, T>(
+    client: tokio_postgres::Client,
+    mut connection: tokio_postgres::Connection<S, T>,
+    sender: &broadcast::Sender<IndexedNotification>,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // The connection has to be polled for LISTEN to complete and to receive notifications
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let driver = tokio::spawn(async move {
+        let mut messages = futures::stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            if let AsyncMessage::Notification(notification) = message? {
+                if tx.send(notification).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok::<_, tokio_postgres::Error>(())
+    });
+
+    client.batch_execute(&format!("LISTEN {}", INDEXED_NOTIFY_CHANNEL)).await?;
+    tracing::info!("Listening for notifications on {}", INDEXED_NOTIFY_CHANNEL);
+
+    while let Some(notification) = rx.recv().await {
+        match serde_json::from_str::<IndexedNotification>(notification.payload()) {
+            Ok(notification) => {
+                let _ = sender.send(notification);
+            }
+            Err(err) => tracing::warn!(error = ?err, "Ignoring malformed notification"),
+        }
+    }
+
+    driver.await??;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bento_trait::processor::ProcessorTrait;
+use bento_types::{
+    convert_bwe_to_address_models,
+    processors::ProcessorOutput,
+    repository::{delete_address_models_in_range, insert_address_models},
+    BlockAndEvents, BlockRange,
+};
+use diesel_async::AsyncPgConnection;
+
+use super::builtin_processor;
+use crate::{config::ProcessorConfig, db::DbPool};
+
+/// Indexes transaction outputs and inputs by address, backing the address balance,
+/// UTXO and history endpoints.
+pub struct AddressProcessor {
+    connection_pool: Arc<DbPool>,
+}
+
+builtin_processor!(AddressProcessor);
+
+#[async_trait]
+impl ProcessorTrait for AddressProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::AddressProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(&self, blocks: Vec<BlockAndEvents>) -> Result<ProcessorOutput> {
+        let (outputs, inputs) = convert_bwe_to_address_models(&blocks)?;
+        Ok(ProcessorOutput::Address(outputs, inputs))
+    }
+
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
+        if let ProcessorOutput::Address(outputs, inputs) = output {
+            insert_address_models(conn, outputs, inputs).await?;
+        }
+        Ok(())
+    }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = delete_address_models_in_range(conn, range).await?;
+        tracing::info!("Purged {} address outputs and inputs", deleted);
+        Ok(())
+    }
+}
@@ -1,39 +1,24 @@
-use std::fmt::Debug;
 use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use bento_trait::processor::ProcessorTrait;
 use bento_types::{
-    convert_bwe_to_block_models, processors::ProcessorOutput, repository::insert_blocks_to_db, BlockAndEvents,
+    convert_bwe_to_block_models,
+    processors::ProcessorOutput,
+    repository::{delete_blocks_in_range, insert_blocks_to_db},
+    BlockAndEvents, BlockRange,
 };
+use diesel_async::AsyncPgConnection;
 
-use crate::{config::ProcessorConfig, db::DbPool, ProcessorFactory};
-
-pub fn processor_factory() -> ProcessorFactory {
-    |db_pool, _config: Option<Arc<dyn bento_types::config::AppConfigTrait>>| Box::new(BlockProcessor::new(db_pool))
-}
+use super::builtin_processor;
+use crate::{config::ProcessorConfig, db::DbPool};
 
 pub struct BlockProcessor {
     connection_pool: Arc<DbPool>,
 }
 
-impl BlockProcessor {
-    pub fn new(connection_pool: Arc<DbPool>) -> Self {
-        Self { connection_pool }
-    }
-}
-
-impl Debug for BlockProcessor {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let state = &self.connection_pool.state();
-        write!(
-            f,
-            "BlockProcessor {{ connections: {:?}  idle_connections: {:?} }}",
-            state.connections, state.idle_connections
-        )
-    }
-}
+builtin_processor!(BlockProcessor);
 
 #[async_trait]
 impl ProcessorTrait for BlockProcessor {
@@ -50,12 +35,18 @@ impl ProcessorTrait for BlockProcessor {
         Ok(ProcessorOutput::Block(models))
     }
 
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()> {
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
         if let ProcessorOutput::Block(blocks) = output {
             if !blocks.is_empty() {
-                insert_blocks_to_db(self.connection_pool.clone(), blocks).await?;
+                insert_blocks_to_db(conn, blocks).await?;
             }
         }
         Ok(())
     }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = delete_blocks_in_range(conn, range).await?;
+        tracing::info!("Purged {} blocks", deleted);
+        Ok(())
+    }
 }
@@ -1,39 +1,24 @@
-use std::fmt::Debug;
 use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use bento_trait::processor::ProcessorTrait;
 use bento_types::{
-    convert_bwe_to_event_models, processors::ProcessorOutput, repository::insert_events_to_db, BlockAndEvents,
+    convert_bwe_to_event_models,
+    processors::ProcessorOutput,
+    repository::{delete_events_in_range, insert_events_to_db},
+    BlockAndEvents, BlockRange,
 };
+use diesel_async::AsyncPgConnection;
 
-use crate::{config::ProcessorConfig, db::DbPool, ProcessorFactory};
-
-pub fn processor_factory() -> ProcessorFactory {
-    |db_pool, _config: Option<Arc<dyn bento_types::config::AppConfigTrait>>| Box::new(EventProcessor::new(db_pool))
-}
+use super::builtin_processor;
+use crate::{config::ProcessorConfig, db::DbPool};
 
 pub struct EventProcessor {
     connection_pool: Arc<DbPool>,
 }
 
-impl EventProcessor {
-    pub fn new(connection_pool: Arc<DbPool>) -> Self {
-        Self { connection_pool }
-    }
-}
-
-impl Debug for EventProcessor {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let state = &self.connection_pool.state();
-        write!(
-            f,
-            "EventProcessor {{ connections: {:?}  idle_connections: {:?} }}",
-            state.connections, state.idle_connections
-        )
-    }
-}
+builtin_processor!(EventProcessor);
 
 #[async_trait]
 impl ProcessorTrait for EventProcessor {
@@ -58,12 +43,18 @@ impl ProcessorTrait for EventProcessor {
         Ok(ProcessorOutput::Event(models))
     }
 
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()> {
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
         if let ProcessorOutput::Event(events) = output {
             if !events.is_empty() {
-                insert_events_to_db(self.connection_pool.clone(), events).await?;
+                insert_events_to_db(conn, events).await?;
             }
         }
         Ok(())
     }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = delete_events_in_range(conn, range).await?;
+        tracing::info!("Purged {} events", deleted);
+        Ok(())
+    }
 }
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bento_trait::processor::ProcessorTrait;
+use bento_types::{
+    convert_bwe_to_mining_models,
+    processors::ProcessorOutput,
+    repository::{delete_mining_models_in_range, insert_mining_models},
+    BlockAndEvents, BlockRange,
+};
+use diesel_async::AsyncPgConnection;
+
+use super::builtin_processor;
+use crate::{config::ProcessorConfig, db::DbPool};
+
+/// Records the miner and coinbase reward of every block, and the ghost uncles it references
+/// together with the uncle miners.
+pub struct MiningProcessor {
+    connection_pool: Arc<DbPool>,
+}
+
+builtin_processor!(MiningProcessor);
+
+#[async_trait]
+impl ProcessorTrait for MiningProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::MiningProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(&self, blocks: Vec<BlockAndEvents>) -> Result<ProcessorOutput> {
+        let (rewards, uncles) = convert_bwe_to_mining_models(&blocks)?;
+        Ok(ProcessorOutput::Mining(rewards, uncles))
+    }
+
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
+        if let ProcessorOutput::Mining(rewards, uncles) = output {
+            insert_mining_models(conn, rewards, uncles).await?;
+        }
+        Ok(())
+    }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = delete_mining_models_in_range(conn, range).await?;
+        tracing::info!("Purged {} block rewards and ghost uncles", deleted);
+        Ok(())
+    }
+}
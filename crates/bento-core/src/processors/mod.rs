@@ -1,3 +1,37 @@
+pub mod address_processor;
 pub mod block_processor;
 pub mod event_processor;
+pub mod mining_processor;
+pub mod token_processor;
 pub mod tx_processor;
+
+/// Implements what every built-in processor shares: `new` over its connection pool, a `Debug`
+/// showing the pool state, and a module level `processor_factory` ignoring the app config.
+macro_rules! builtin_processor {
+    ($processor:ident) => {
+        pub fn processor_factory() -> $crate::ProcessorFactory {
+            |db_pool, _config| Box::new($processor::new(db_pool))
+        }
+
+        impl $processor {
+            pub fn new(connection_pool: std::sync::Arc<$crate::db::DbPool>) -> Self {
+                Self { connection_pool }
+            }
+        }
+
+        impl std::fmt::Debug for $processor {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let state = &self.connection_pool.state();
+                write!(
+                    f,
+                    "{} {{ connections: {:?}  idle_connections: {:?} }}",
+                    stringify!($processor),
+                    state.connections,
+                    state.idle_connections
+                )
+            }
+        }
+    };
+}
+
+pub(crate) use builtin_processor;
@@ -0,0 +1,300 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use bento_trait::{processor::ProcessorTrait, stage::ContractsProvider};
+use bento_types::{
+    convert_bwe_to_token_models,
+    processors::ProcessorOutput,
+    repository::{
+        delete_token_models_in_range, get_tokens_without_metadata, insert_token_models, record_token_metadata_failure,
+        update_token_metadata,
+    },
+    utils::{address_from_contract_id, group_of_contract_id, hex_to_utf8_lossy},
+    BlockAndEvents, BlockRange, CallContractParams, CallContractResultType, DEFAULT_GROUP_NUM,
+};
+use diesel_async::AsyncPgConnection;
+
+use super::builtin_processor;
+use crate::{config::ProcessorConfig, db::DbPool};
+
+// Method indexes of the standard fungible token interface
+const GET_SYMBOL_METHOD_INDEX: u32 = 0;
+const GET_NAME_METHOD_INDEX: u32 = 1;
+const GET_DECIMALS_METHOD_INDEX: u32 = 2;
+
+/// Tokens resolved per poll of the metadata resolver
+const METADATA_BATCH_SIZE: i64 = 50;
+
+/// Delay between two polls of the metadata resolver when no token is left
+const METADATA_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Discovers tokens from transaction outputs and indexes per-token outputs and inputs,
+/// which back the token holder, supply and transfer endpoints.
+pub struct TokenProcessor {
+    connection_pool: Arc<DbPool>,
+}
+
+builtin_processor!(TokenProcessor);
+
+#[async_trait]
+impl ProcessorTrait for TokenProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::TokenProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(&self, blocks: Vec<BlockAndEvents>) -> Result<ProcessorOutput> {
+        let (tokens, outputs, inputs) = convert_bwe_to_token_models(&blocks)?;
+        Ok(ProcessorOutput::Token(tokens, outputs, inputs))
+    }
+
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
+        if let ProcessorOutput::Token(tokens, outputs, inputs) = output {
+            insert_token_models(conn, tokens, outputs, inputs).await?;
+        }
+        Ok(())
+    }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = delete_token_models_in_range(conn, range).await?;
+        tracing::info!("Purged {} token outputs and inputs", deleted);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenMetadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+}
+
+/// Read name, symbol and decimals from the token contract.
+///
+/// Tokens that don't implement the fungible token interface simply get `None` fields. An
+/// invalid token id or a failed request to the node is an error, so the lookup can be retried.
+pub async fn fetch_token_metadata(
+    client: &(dyn ContractsProvider + Send + Sync),
+    token_id: &str,
+) -> Result<TokenMetadata> {
+    let address = address_from_contract_id(token_id)?;
+    let group = group_of_contract_id(token_id, DEFAULT_GROUP_NUM as u32)?;
+
+    let symbol = call_token_getter(client, &address, group, GET_SYMBOL_METHOD_INDEX).await?;
+    let name = call_token_getter(client, &address, group, GET_NAME_METHOD_INDEX).await?;
+    let decimals = call_token_getter(client, &address, group, GET_DECIMALS_METHOD_INDEX).await?;
+
+    Ok(TokenMetadata {
+        name: name.and_then(|v| hex_to_utf8_lossy(&v).ok()),
+        symbol: symbol.and_then(|v| hex_to_utf8_lossy(&v).ok()),
+        decimals: decimals.and_then(|v| v.parse::<i32>().ok()),
+    })
+}
+
+/// Fetch the metadata of a token and persist it. Nothing is persisted when the fetch fails.
+pub async fn resolve_token_metadata(
+    db: Arc<DbPool>,
+    client: &(dyn ContractsProvider + Send + Sync),
+    token_id: &str,
+) -> Result<TokenMetadata> {
+    let metadata = fetch_token_metadata(client, token_id).await?;
+    update_token_metadata(db, token_id, metadata.name.clone(), metadata.symbol.clone(), metadata.decimals).await?;
+    Ok(metadata)
+}
+
+/// Call a getter without arguments and return the `value` of its single return value, or
+/// `None` when the call reverted.
+async fn call_token_getter(
+    client: &(dyn ContractsProvider + Send + Sync),
+    address: &str,
+    group: u32,
+    method_index: u32,
+) -> Result<Option<String>> {
+    let params = CallContractParams {
+        group,
+        world_state_block_hash: None,
+        tx_id: None,
+        address: address.to_string(),
+        method_index,
+        args: None,
+        interested_contracts: None,
+        input_assets: None,
+    };
+
+    let result = client
+        .call_contract(params)
+        .await
+        .with_context(|| format!("Token getter {} on {} failed", method_index, address))?;
+
+    match result.result_type {
+        CallContractResultType::CallContractSucceeded => Ok(result
+            .returns
+            .and_then(|returns| returns.into_iter().next())
+            .and_then(|v| v.get("value").and_then(|v| v.as_str()).map(|v| v.to_string()))),
+        CallContractResultType::CallContractFailed => {
+            tracing::debug!("Token getter {} on {} reverted: {:?}", method_index, address, result.error);
+            Ok(None)
+        }
+    }
+}
+
+/// Resolves the metadata of newly discovered tokens in the background, so API requests only
+/// read what is stored. A token whose lookup failed is retried later with a backoff.
+pub struct TokenMetadataResolver {
+    db_pool: Arc<DbPool>,
+    client: Arc<dyn ContractsProvider + Send + Sync>,
+}
+
+impl TokenMetadataResolver {
+    pub fn new(db_pool: Arc<DbPool>, client: Arc<dyn ContractsProvider + Send + Sync>) -> Self {
+        Self { db_pool, client }
+    }
+
+    /// Resolve pending tokens until the task is aborted
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.resolve_pending().await {
+                    // More tokens are probably pending
+                    Ok(resolved) if resolved as i64 == METADATA_BATCH_SIZE => continue,
+                    Ok(_) => {}
+                    Err(err) => tracing::warn!(error = ?err, "Failed to resolve token metadata"),
+                }
+                tokio::time::sleep(METADATA_POLL_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Resolve a batch of tokens never looked up, oldest first. A token whose lookup fails is
+    /// recorded for a later retry and the batch goes on, unless the node could not be reached.
+    /// Returns the number of tokens looked up.
+    pub async fn resolve_pending(&self) -> Result<usize> {
+        let token_ids = get_tokens_without_metadata(self.db_pool.clone(), METADATA_BATCH_SIZE).await?;
+        for token_id in &token_ids {
+            let Err(err) = resolve_token_metadata(self.db_pool.clone(), self.client.as_ref(), token_id).await else {
+                continue;
+            };
+            if is_connection_error(&err) {
+                return Err(err.context(format!("Failed to resolve the metadata of token {}", token_id)));
+            }
+            tracing::warn!(token_id = %token_id, error = ?err, "Failed to resolve token metadata, retrying later");
+            record_token_metadata_failure(self.db_pool.clone(), token_id).await?;
+        }
+        Ok(token_ids.len())
+    }
+}
+
+/// Whether the node could not be reached at all, as opposed to a request failing for one token.
+fn is_connection_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(err) = cause.downcast_ref::<reqwest_middleware::Error>() {
+            return match err {
+                reqwest_middleware::Error::Reqwest(err) => err.is_connect() || err.is_timeout(),
+                // Retries exhausted by the middleware
+                reqwest_middleware::Error::Middleware(_) => true,
+            };
+        }
+        cause.downcast_ref::<reqwest::Error>().is_some_and(|err| err.is_connect() || err.is_timeout())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bento_types::CallContractResult;
+    use mockall::*;
+    use serde_json::json;
+
+    mock! {
+        pub Client {}
+
+        #[async_trait]
+        impl ContractsProvider for Client {
+            async fn call_contract(&self, params: CallContractParams) -> Result<CallContractResult>;
+        }
+    }
+
+    fn returns(value: serde_json::Value) -> CallContractResult {
+        CallContractResult {
+            result_type: CallContractResultType::CallContractSucceeded,
+            error: None,
+            returns: Some(vec![value]),
+            gas_used: None,
+            contracts: None,
+            tx_inputs: None,
+            tx_outputs: None,
+            events: None,
+            debug_messages: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_metadata() {
+        let token_id = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+        let mut mock_client = MockClient::new();
+        mock_client.expect_call_contract().times(3).returning(|params| {
+            assert_eq!(params.group, 0);
+            match params.method_index {
+                GET_SYMBOL_METHOD_INDEX => Ok(returns(json!({"type": "ByteVec", "value": "4159494e"}))),
+                GET_NAME_METHOD_INDEX => Ok(returns(json!({"type": "ByteVec", "value": "4179696e"}))),
+                GET_DECIMALS_METHOD_INDEX => Ok(returns(json!({"type": "U256", "value": "18"}))),
+                _ => unreachable!(),
+            }
+        });
+
+        let metadata = fetch_token_metadata(&mock_client, token_id).await.unwrap();
+        assert_eq!(
+            metadata,
+            TokenMetadata { name: Some("Ayin".to_string()), symbol: Some("AYIN".to_string()), decimals: Some(18) }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_metadata_non_standard_token() {
+        let token_id = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+        let mut mock_client = MockClient::new();
+        mock_client.expect_call_contract().times(3).returning(|_| {
+            Ok(CallContractResult {
+                result_type: CallContractResultType::CallContractFailed,
+                error: Some("method not found".to_string()),
+                ..returns(json!(null))
+            })
+        });
+
+        let metadata = fetch_token_metadata(&mock_client, token_id).await.unwrap();
+        assert_eq!(metadata, TokenMetadata::default());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_metadata_node_error() {
+        let token_id = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+        let mut mock_client = MockClient::new();
+        mock_client.expect_call_contract().times(1).returning(|_| Err(anyhow::anyhow!("connection timed out")));
+
+        assert!(fetch_token_metadata(&mock_client, token_id).await.is_err());
+    }
+
+    #[test]
+    fn test_is_connection_error() {
+        let exhausted = reqwest_middleware::Error::Middleware(anyhow::anyhow!("retries exhausted"));
+        let err = anyhow::Error::from(exhausted).context("Token getter 0 on contract failed");
+        assert!(is_connection_error(&err));
+
+        let err = anyhow::anyhow!("call_contract HTTP 400 Bad Request: invalid contract").context("Token getter");
+        assert!(!is_connection_error(&err));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires network"]
+    async fn test_is_connection_error_refused() {
+        // Nothing listens on port 1
+        let refused = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
+        let err = anyhow::Error::from(refused).context("Token getter 0 on contract failed");
+        assert!(is_connection_error(&err));
+    }
+}
@@ -1,38 +1,24 @@
-use std::fmt::Debug;
 use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use bento_trait::processor::ProcessorTrait;
 use bento_types::{
-    convert_bwe_to_tx_models, processors::ProcessorOutput, repository::insert_txs_to_db, BlockAndEvents,
+    convert_bwe_to_tx_models,
+    processors::ProcessorOutput,
+    repository::{delete_txs_in_range, insert_txs_to_db},
+    BlockAndEvents, BlockRange,
 };
+use diesel_async::AsyncPgConnection;
 
-use crate::{config::ProcessorConfig, db::DbPool, ProcessorFactory};
-pub fn processor_factory() -> ProcessorFactory {
-    |db_pool, _config: Option<Arc<dyn bento_types::config::AppConfigTrait>>| Box::new(TxProcessor::new(db_pool))
-}
+use super::builtin_processor;
+use crate::{config::ProcessorConfig, db::DbPool};
 
 pub struct TxProcessor {
     connection_pool: Arc<DbPool>,
 }
 
-impl TxProcessor {
-    pub fn new(connection_pool: Arc<DbPool>) -> Self {
-        Self { connection_pool }
-    }
-}
-
-impl Debug for TxProcessor {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let state = &self.connection_pool.state();
-        write!(
-            f,
-            "TxProcessor {{ connections: {:?}  idle_connections: {:?} }}",
-            state.connections, state.idle_connections
-        )
-    }
-}
+builtin_processor!(TxProcessor);
 
 #[async_trait]
 impl ProcessorTrait for TxProcessor {
@@ -49,12 +35,18 @@ impl ProcessorTrait for TxProcessor {
         Ok(ProcessorOutput::Tx(models))
     }
 
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()> {
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
         if let ProcessorOutput::Tx(models) = output {
             if !models.is_empty() {
-                insert_txs_to_db(self.connection_pool.clone(), models).await?;
+                insert_txs_to_db(conn, models).await?;
             }
         }
         Ok(())
     }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = delete_txs_in_range(conn, range).await?;
+        tracing::info!("Purged {} transactions", deleted);
+        Ok(())
+    }
 }
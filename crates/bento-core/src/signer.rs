@@ -0,0 +1,135 @@
+//! Signing of the unsigned transactions built by the node.
+//!
+//! A transaction is signed by signing its id, the Blake2b-256 hash of the unsigned transaction
+//! bytes, with the secp256k1 key of the sender. [`Client::sign_and_submit`] computes the id
+//! itself rather than trusting the one returned by the node.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+use crate::client::{Client, SubmitTxResponse};
+
+/// Address type byte of a pay-to-public-key-hash address.
+pub const P2PKH: u8 = 0x00;
+
+/// Signs transactions for one key. Implement it to sign with a key kept outside the process,
+/// e.g. in a KMS.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Hex encoded compressed public key, the `from_public_key` of built transactions
+    fn public_key(&self) -> String;
+
+    /// Sign a transaction id, returning the hex encoded 64 byte compact signature
+    async fn sign_tx_id(&self, tx_id: &[u8; 32]) -> Result<String>;
+}
+
+/// Id of a hex encoded unsigned transaction.
+pub fn tx_id(unsigned_tx: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(unsigned_tx).map_err(|e| anyhow!("Invalid unsigned tx hex: {}", e))?;
+    Ok(blake2b(&bytes))
+}
+
+/// Base58 address of `address_type` for a public key, e.g. [`P2PKH`].
+pub fn address_from_public_key(public_key: &[u8], address_type: u8) -> String {
+    // No checksum, just the type byte and the public key hash
+    let mut address = Vec::with_capacity(33);
+    address.push(address_type);
+    address.extend_from_slice(&blake2b(public_key));
+    bs58::encode(address).into_string()
+}
+
+fn blake2b(bytes: &[u8]) -> [u8; 32] {
+    Blake2b::<U32>::digest(bytes).into()
+}
+
+/// Signs with a private key held in memory.
+pub struct PrivateKeySigner {
+    secret_key: SecretKey,
+    public_key: PublicKey,
+}
+
+impl PrivateKeySigner {
+    /// Signer of a 32 byte private key
+    pub fn new(private_key: &[u8]) -> Result<Self> {
+        if private_key.len() != 32 {
+            return Err(anyhow!("Private key must be 32 bytes"));
+        }
+        let secret_key = SecretKey::from_slice(private_key).map_err(|e| anyhow!("Invalid private key: {}", e))?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret_key);
+        Ok(Self { secret_key, public_key })
+    }
+
+    /// Signer of a hex encoded private key
+    pub fn from_hex(private_key: &str) -> Result<Self> {
+        Self::new(&hex::decode(private_key.trim()).map_err(|e| anyhow!("Invalid private key hex: {}", e))?)
+    }
+
+    /// P2PKH address of the key
+    pub fn address(&self) -> String {
+        address_from_public_key(&self.public_key.serialize(), P2PKH)
+    }
+}
+
+impl std::fmt::Debug for PrivateKeySigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivateKeySigner").field("address", &self.address()).finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Signer for PrivateKeySigner {
+    fn public_key(&self) -> String {
+        hex::encode(self.public_key.serialize())
+    }
+
+    async fn sign_tx_id(&self, tx_id: &[u8; 32]) -> Result<String> {
+        let message = Message::from_digest(*tx_id);
+        let signature = Secp256k1::signing_only().sign_ecdsa(&message, &self.secret_key);
+        Ok(hex::encode(signature.serialize_compact()))
+    }
+}
+
+impl Client {
+    /// Sign a hex encoded unsigned transaction, e.g. the `unsigned_tx` of
+    /// [`Client::build_transfer_tx`], and submit it.
+    pub async fn sign_and_submit(&self, signer: &dyn Signer, unsigned_tx: &str) -> Result<SubmitTxResponse> {
+        let signature = signer.sign_tx_id(&tx_id(unsigned_tx)?).await?;
+        self.submit_transaction(unsigned_tx, &signature).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::ecdsa::Signature;
+
+    const PRIVATE_KEY: &str = "7babd8a9b3af814757fde3d801afcf9a94d1d9e35863c31db75e05202136e1b8";
+
+    #[test]
+    fn test_address() {
+        let signer = PrivateKeySigner::from_hex(PRIVATE_KEY).unwrap();
+        assert_eq!(signer.address(), "1EJCtZP3HZP5rDX5v2o32woqLTxp6GS4GoLQGpzVPQm6E");
+        assert_eq!(signer.public_key().len(), 66);
+        assert!(!format!("{:?}", signer).contains(PRIVATE_KEY));
+
+        assert!(PrivateKeySigner::new(&[1, 2, 3]).is_err());
+        assert!(PrivateKeySigner::new(&[0; 32]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_tx_id() {
+        let signer = PrivateKeySigner::from_hex(PRIVATE_KEY).unwrap();
+        let id = tx_id("00010203").unwrap();
+        let signature = signer.sign_tx_id(&id).await.unwrap();
+
+        let signature = Signature::from_compact(&hex::decode(signature).unwrap()).unwrap();
+        let public_key = PublicKey::from_slice(&hex::decode(signer.public_key()).unwrap()).unwrap();
+        assert!(Secp256k1::verification_only()
+            .verify_ecdsa(&Message::from_digest(id), &signature, &public_key)
+            .is_ok());
+
+        assert!(tx_id("not hex").is_err());
+    }
+}
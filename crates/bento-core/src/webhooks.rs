@@ -0,0 +1,316 @@
+//! Webhook delivery of the notifications published by the pipeline.
+//!
+//! The storage stage enqueues a delivery per matching subscription in the transaction storing a
+//! processor output, so every stored notification is delivered at least once.
+//! A [`WebhookDispatcher`] then posts the deliveries, retrying failures with exponential backoff
+//! and moving deliveries out of attempts to `webhook_dead_letters`.
+//!
+//! Each request carries:
+//! - `x-bento-delivery`: the notification key, the same across retries and each time the
+//!   notification is enqueued again, to skip duplicates
+//! - `x-bento-timestamp`: the send time in milliseconds
+//! - `x-bento-signature`: `sha256=` and the hex HMAC-SHA256 of `"{timestamp}.{body}"` keyed
+//!   with the subscription secret
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use bento_types::notifications::IndexedNotification;
+use bento_types::repository::{
+    claim_webhook_deliveries, complete_webhook_delivery, dead_letter_webhook_delivery,
+    get_active_webhook_subscriptions, get_webhook_subscriptions_by_ids, retry_webhook_delivery,
+};
+use bento_types::{DbPool, NewWebhookDeliveryModel, WebhookDeliveryModel, WebhookSubscriptionModel};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+pub const DELIVERY_ID_HEADER: &str = "x-bento-delivery";
+pub const TIMESTAMP_HEADER: &str = "x-bento-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-bento-signature";
+
+/// How long the active subscriptions are cached by the storage stage, so a new subscription
+/// receives notifications after at most this long.
+const SUBSCRIPTIONS_TTL: Duration = Duration::from_secs(30);
+
+/// Active subscriptions and when they were fetched
+type CachedSubscriptions = Option<(Arc<Vec<WebhookSubscriptionModel>>, Instant)>;
+
+/// Matches notifications against the active subscriptions to build their deliveries.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookEnqueuer {
+    subscriptions: Arc<Mutex<CachedSubscriptions>>,
+}
+
+impl WebhookEnqueuer {
+    async fn subscriptions(&self, db_pool: &Arc<DbPool>) -> Result<Arc<Vec<WebhookSubscriptionModel>>> {
+        let now = Instant::now();
+        if let Some((subscriptions, fetched)) = &*self.subscriptions.lock().unwrap_or_else(|e| e.into_inner()) {
+            if now.duration_since(*fetched) < SUBSCRIPTIONS_TTL {
+                return Ok(subscriptions.clone());
+            }
+        }
+
+        let subscriptions = Arc::new(get_active_webhook_subscriptions(db_pool).await?);
+        *self.subscriptions.lock().unwrap_or_else(|e| e.into_inner()) = Some((subscriptions.clone(), now));
+        Ok(subscriptions)
+    }
+
+    /// Deliveries of `notifications` to the active subscriptions, to be enqueued with
+    /// [`bento_types::repository::enqueue_webhook_deliveries`].
+    pub async fn deliveries(
+        &self,
+        db_pool: &Arc<DbPool>,
+        notifications: &[IndexedNotification],
+    ) -> Result<Vec<NewWebhookDeliveryModel>> {
+        if notifications.is_empty() {
+            return Ok(Vec::new());
+        }
+        let subscriptions = self.subscriptions(db_pool).await?;
+        deliveries_for(&subscriptions, notifications)
+    }
+}
+
+/// Key of a notification, the same each time the batch producing it is stored.
+pub fn notification_key(notification: &IndexedNotification) -> Result<String> {
+    Ok(hex::encode(Sha256::digest(serde_json::to_vec(notification)?)))
+}
+
+/// Deliveries of `notifications` to the `subscriptions` they match.
+pub fn deliveries_for(
+    subscriptions: &[WebhookSubscriptionModel],
+    notifications: &[IndexedNotification],
+) -> Result<Vec<NewWebhookDeliveryModel>> {
+    let mut deliveries = Vec::new();
+    for notification in notifications {
+        let matching: Vec<_> = subscriptions.iter().filter(|s| s.matches(notification)).collect();
+        if matching.is_empty() {
+            continue;
+        }
+        let notification_key = notification_key(notification)?;
+        let payload = serde_json::to_value(notification)?;
+        deliveries.extend(matching.into_iter().map(|subscription| NewWebhookDeliveryModel {
+            subscription_id: subscription.id,
+            notification_key: notification_key.clone(),
+            payload: payload.clone(),
+        }));
+    }
+    Ok(deliveries)
+}
+
+/// Value of the [`SIGNATURE_HEADER`] of a request sent at `timestamp` with `body`.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Attempts before a delivery is moved to the dead letters
+    pub max_attempts: i32,
+    /// Delay before the first retry, doubled after each failed attempt
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Timeout of each request
+    pub timeout: Duration,
+    /// Interval between polls when no delivery is due
+    pub poll_interval: Duration,
+    /// Deliveries claimed and sent concurrently
+    pub batch_size: i64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            initial_backoff: Duration::from_secs(10),
+            max_backoff: Duration::from_secs(60 * 60),
+            timeout: Duration::from_secs(10),
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Delay before the attempt following `attempts` failed ones.
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1).max(0) as u32);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Sends the enqueued deliveries. Several dispatchers can run against the same database.
+pub struct WebhookDispatcher {
+    db_pool: Arc<DbPool>,
+    client: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db_pool: Arc<DbPool>, config: WebhookConfig) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(config.timeout).build()?;
+        Ok(Self { db_pool, client, config })
+    }
+
+    /// Deliver until the task is aborted.
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.dispatch_once().await {
+                    Ok(0) => tokio::time::sleep(self.config.poll_interval).await,
+                    Ok(_) => {}
+                    Err(err) => {
+                        tracing::error!(error = ?err, "Failed to dispatch webhook deliveries");
+                        tokio::time::sleep(self.config.poll_interval).await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Send the due deliveries, returning how many were attempted.
+    pub async fn dispatch_once(&self) -> Result<usize> {
+        // Deliveries still out when the lease ends are claimed again
+        let lease = self.config.timeout * 2 + Duration::from_secs(30);
+        let lease_until = chrono::Utc::now().naive_utc() + chrono::Duration::from_std(lease)?;
+        let deliveries = claim_webhook_deliveries(&self.db_pool, self.config.batch_size, lease_until).await?;
+        if deliveries.is_empty() {
+            return Ok(0);
+        }
+
+        let mut ids: Vec<i64> = deliveries.iter().map(|d| d.subscription_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let subscriptions: HashMap<i64, WebhookSubscriptionModel> =
+            get_webhook_subscriptions_by_ids(&self.db_pool, &ids).await?.into_iter().map(|s| (s.id, s)).collect();
+
+        let attempted = deliveries.len();
+        futures::future::join_all(deliveries.into_iter().map(|delivery| {
+            let subscription = subscriptions.get(&delivery.subscription_id);
+            async move {
+                if let Err(err) = self.attempt(delivery, subscription).await {
+                    tracing::error!(error = ?err, "Failed to record webhook delivery");
+                }
+            }
+        }))
+        .await;
+        Ok(attempted)
+    }
+
+    async fn attempt(
+        &self,
+        delivery: WebhookDeliveryModel,
+        subscription: Option<&WebhookSubscriptionModel>,
+    ) -> Result<()> {
+        let Some(subscription) = subscription.filter(|s| s.disabled_at.is_none()) else {
+            // Disabled since the notification was enqueued
+            return complete_webhook_delivery(&self.db_pool, delivery.id).await;
+        };
+
+        match self.send(&delivery, subscription).await {
+            Ok(()) => complete_webhook_delivery(&self.db_pool, delivery.id).await,
+            Err(err) if delivery.attempts + 1 >= self.config.max_attempts => {
+                tracing::warn!(
+                    subscription = %subscription.name,
+                    delivery = delivery.id,
+                    error = %err,
+                    "Webhook delivery failed its last attempt"
+                );
+                dead_letter_webhook_delivery(&self.db_pool, &delivery, &err.to_string()).await
+            }
+            Err(err) => {
+                let next_attempt_at = chrono::Utc::now().naive_utc()
+                    + chrono::Duration::from_std(self.config.backoff(delivery.attempts + 1))?;
+                tracing::debug!(subscription = %subscription.name, delivery = delivery.id, error = %err, "Webhook delivery failed");
+                retry_webhook_delivery(&self.db_pool, delivery.id, &err.to_string(), next_attempt_at).await
+            }
+        }
+    }
+
+    async fn send(&self, delivery: &WebhookDeliveryModel, subscription: &WebhookSubscriptionModel) -> Result<()> {
+        let body = serde_json::to_vec(&json!({
+            "delivery_id": delivery.notification_key,
+            "subscription": subscription.name,
+            "notification": delivery.payload,
+        }))?;
+        let timestamp = chrono::Utc::now().timestamp_millis();
+
+        let response = self
+            .client
+            .post(&subscription.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(DELIVERY_ID_HEADER, &delivery.notification_key)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&subscription.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("{} responded {}", subscription.url, response.status()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(id: i64, contract_address: Option<&str>, event_index: Option<i32>) -> WebhookSubscriptionModel {
+        WebhookSubscriptionModel {
+            id,
+            name: format!("sub-{id}"),
+            url: "http://localhost/hook".to_string(),
+            secret: "whsec_test".to_string(),
+            notification_type: None,
+            contract_address: contract_address.map(str::to_string),
+            event_index,
+            address: None,
+            topic: None,
+            created_at: chrono::Utc::now().naive_utc(),
+            disabled_at: None,
+        }
+    }
+
+    fn event(contract_address: &str, event_index: i32) -> IndexedNotification {
+        IndexedNotification::Event {
+            id: format!("{contract_address}-{event_index}"),
+            tx_id: "tx".to_string(),
+            contract_address: contract_address.to_string(),
+            event_index,
+        }
+    }
+
+    #[test]
+    fn test_deliveries_for_matching_subscriptions() {
+        let subscriptions = vec![subscription(1, Some("market"), Some(3)), subscription(2, Some("market"), None)];
+        let notifications = vec![event("market", 3), event("market", 1), event("pool", 3)];
+
+        let deliveries = deliveries_for(&subscriptions, &notifications).unwrap();
+        let targets: Vec<i64> = deliveries.iter().map(|d| d.subscription_id).collect();
+        assert_eq!(targets, vec![1, 2, 2]);
+
+        // Enqueuing the same notification again gives the same key
+        assert_eq!(deliveries[0].notification_key, notification_key(&notifications[0]).unwrap());
+    }
+
+    #[test]
+    fn test_signature_and_backoff() {
+        let signature = sign("secret", 1_700_000_000_000, b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_ne!(signature, sign("other", 1_700_000_000_000, b"{}"));
+
+        let config = WebhookConfig::default();
+        assert_eq!(config.backoff(1), Duration::from_secs(10));
+        assert_eq!(config.backoff(3), Duration::from_secs(40));
+        assert_eq!(config.backoff(30), config.max_backoff);
+    }
+}
@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use bento_types::{
+    repository::{get_height_gaps, get_timestamp_gaps, HeightGap, TimestampGap},
+    BlockRange, RichBlockEntry, MAX_TIMESTAMP_RANGE,
+};
+use serde::Serialize;
+
+use crate::db::DbPool;
+
+/// Default minimum duration without blocks on a chain reported as a timestamp gap (10 minutes).
+pub const DEFAULT_GAP_THRESHOLD: u64 = 10 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GapOptions {
+    /// Ignore blocks below this height, e.g. for apps deployed after genesis
+    pub min_height: i64,
+    /// Ignore blocks older than this timestamp in milliseconds
+    pub since_ts: Option<u64>,
+    /// Minimum duration in milliseconds without blocks on a chain to report a timestamp gap
+    pub threshold: u64,
+}
+
+impl Default for GapOptions {
+    fn default() -> Self {
+        Self { min_height: 0, since_ts: None, threshold: DEFAULT_GAP_THRESHOLD }
+    }
+}
+
+/// Options of the gap check run periodically by the realtime worker.
+#[derive(Debug, Clone, Copy)]
+pub struct GapCheckOptions {
+    /// Interval between two checks in milliseconds
+    pub interval: u64,
+    /// How far back each check looks, in milliseconds
+    pub lookback: u64,
+    /// Minimum duration in milliseconds without blocks on a chain to report a timestamp gap
+    pub threshold: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GapReport {
+    pub height_gaps: Vec<HeightGap>,
+    pub timestamp_gaps: Vec<TimestampGap>,
+}
+
+impl GapReport {
+    pub fn is_empty(&self) -> bool {
+        self.height_gaps.is_empty() && self.timestamp_gaps.is_empty()
+    }
+
+    pub fn missing_blocks(&self) -> i64 {
+        self.height_gaps.iter().map(HeightGap::missing_blocks).sum()
+    }
+
+    /// Timestamp ranges to fetch for the timestamp gaps that are not explained by a height gap
+    /// of the same chain, merged where they overlap.
+    pub fn timestamp_ranges(&self) -> Vec<BlockRange> {
+        let ranges = self
+            .timestamp_gaps
+            .iter()
+            .filter(|gap| {
+                !self.height_gaps.iter().any(|h| {
+                    h.chain_from == gap.chain_from
+                        && h.chain_to == gap.chain_to
+                        && h.from_ts <= gap.from_ts
+                        && h.to_ts >= gap.to_ts
+                })
+            })
+            .map(|gap| BlockRange { from_ts: gap.from_ts as u64, to_ts: gap.to_ts as u64 })
+            .collect();
+        merge_ranges(ranges)
+    }
+}
+
+/// Sort `ranges` and merge the ones that overlap.
+fn merge_ranges(mut ranges: Vec<BlockRange>) -> Vec<BlockRange> {
+    ranges.sort_by_key(|r| r.from_ts);
+
+    let mut merged: Vec<BlockRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.from_ts <= last.to_ts => last.to_ts = last.to_ts.max(range.to_ts),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Outcome of filling a [`GapReport`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GapFillSummary {
+    pub heights_filled: usize,
+    pub heights_failed: usize,
+    pub ranges_filled: usize,
+    pub ranges_failed: usize,
+}
+
+/// One timestamp range fetch of a gap fill, with the height gaps it is expected to cover.
+#[derive(Debug, Clone)]
+pub struct GapFillJob {
+    pub range: BlockRange,
+    pub gaps: Vec<HeightGap>,
+}
+
+impl GapFillJob {
+    /// Whether the block belongs to one of the job's gaps, i.e. is on a gap's chain and height.
+    pub fn wants(&self, block: &RichBlockEntry) -> bool {
+        self.gaps.iter().any(|gap| {
+            gap.chain_from == block.chain_from
+                && gap.chain_to == block.chain_to
+                && (gap.from_height..=gap.to_height).contains(&block.height)
+        })
+    }
+}
+
+/// Plan the timestamp range fetches covering `gaps`. The ranges between the blocks around each
+/// gap are merged where they overlap, so one fetch serves every chain missing blocks in that
+/// period, and split into chunks of at most [`MAX_TIMESTAMP_RANGE`].
+pub fn plan_gap_fill(gaps: &[HeightGap]) -> Vec<GapFillJob> {
+    let ranges = gaps.iter().map(|gap| BlockRange { from_ts: gap.from_ts as u64, to_ts: gap.to_ts as u64 }).collect();
+
+    let mut jobs = Vec::new();
+    for range in merge_ranges(ranges) {
+        let mut from_ts = range.from_ts;
+        loop {
+            let to_ts = std::cmp::min(from_ts + MAX_TIMESTAMP_RANGE, range.to_ts);
+            let gaps: Vec<HeightGap> =
+                gaps.iter().filter(|gap| gap.from_ts as u64 <= to_ts && gap.to_ts as u64 >= from_ts).cloned().collect();
+            jobs.push(GapFillJob { range: BlockRange { from_ts, to_ts }, gaps });
+            if to_ts >= range.to_ts {
+                break;
+            }
+            from_ts = to_ts;
+        }
+    }
+    jobs
+}
+
+/// Detect height holes and timestamp gaps in the stored blocks.
+pub async fn detect_gaps(db_pool: &Arc<DbPool>, opts: &GapOptions) -> Result<GapReport> {
+    let since_ts = opts.since_ts.unwrap_or(0) as i64;
+
+    tracing::info!(
+        "Detecting gaps (min height {}, since {}, threshold {}ms)",
+        opts.min_height,
+        since_ts,
+        opts.threshold
+    );
+
+    let height_gaps = get_height_gaps(db_pool, opts.min_height, since_ts).await?;
+    let timestamp_gaps = get_timestamp_gaps(db_pool, opts.threshold as i64, since_ts).await?;
+
+    let report = GapReport { height_gaps, timestamp_gaps };
+    tracing::info!(
+        "Found {} height gaps ({} missing blocks) and {} timestamp gaps",
+        report.height_gaps.len(),
+        report.missing_blocks(),
+        report.timestamp_gaps.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn height_gap(chain: (i64, i64), from_ts: i64, to_ts: i64) -> HeightGap {
+        HeightGap { chain_from: chain.0, chain_to: chain.1, from_height: 10, to_height: 12, from_ts, to_ts }
+    }
+
+    fn timestamp_gap(chain: (i64, i64), from_ts: i64, to_ts: i64) -> TimestampGap {
+        TimestampGap { chain_from: chain.0, chain_to: chain.1, from_ts, to_ts }
+    }
+
+    #[test]
+    fn test_plan_gap_fill_merges_and_splits() {
+        let gaps = vec![
+            height_gap((0, 0), 1_000, 2_000),
+            height_gap((1, 2), 1_500, 3_000),
+            height_gap((3, 3), 10_000, 10_000 + MAX_TIMESTAMP_RANGE as i64 + 5_000),
+        ];
+
+        let jobs = plan_gap_fill(&gaps);
+        assert_eq!(jobs.len(), 3);
+
+        assert_eq!((jobs[0].range.from_ts, jobs[0].range.to_ts), (1_000, 3_000));
+        assert_eq!(jobs[0].gaps.len(), 2);
+
+        assert_eq!((jobs[1].range.from_ts, jobs[1].range.to_ts), (10_000, 10_000 + MAX_TIMESTAMP_RANGE));
+        assert_eq!(
+            (jobs[2].range.from_ts, jobs[2].range.to_ts),
+            (10_000 + MAX_TIMESTAMP_RANGE, 10_000 + MAX_TIMESTAMP_RANGE + 5_000)
+        );
+        assert!(jobs[1..].iter().all(|job| job.gaps.len() == 1 && job.gaps[0].chain_from == 3));
+    }
+
+    #[test]
+    fn test_timestamp_ranges_skip_height_gaps_and_merge() {
+        let report = GapReport {
+            height_gaps: vec![height_gap((0, 0), 100, 200)],
+            timestamp_gaps: vec![
+                timestamp_gap((0, 0), 100, 200),
+                timestamp_gap((0, 1), 100, 200),
+                timestamp_gap((1, 1), 150, 300),
+                timestamp_gap((2, 2), 500, 600),
+            ],
+        };
+
+        let ranges = report.timestamp_ranges();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!((ranges[0].from_ts, ranges[0].to_ts), (100, 300));
+        assert_eq!((ranges[1].from_ts, ranges[1].to_ts), (500, 600));
+        assert_eq!(report.missing_blocks(), 3);
+    }
+}
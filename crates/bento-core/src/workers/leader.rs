@@ -0,0 +1,185 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use bento_types::DbPool;
+use diesel::{
+    sql_types::{BigInt, Bool},
+    QueryableByName,
+};
+use diesel_async::{pooled_connection::bb8::PooledConnection, AsyncPgConnection, RunQueryDsl};
+
+/// Advisory lock key of the realtime sync leader.
+pub const SYNC_LEADER_LOCK_KEY: i64 = 0x62_656e_746f_0001;
+
+#[derive(QueryableByName)]
+struct LockResult {
+    #[diesel(sql_type = Bool)]
+    locked: bool,
+}
+
+/// Leadership held through a Postgres session-level advisory lock.
+///
+/// The lock lives as long as the connection that took it, so the connection is kept out of the
+/// pool until the lock is released. If the session dies, Postgres frees the lock and another
+/// instance can take over.
+pub struct LeaderLock {
+    conn: Option<PooledConnection<'static, AsyncPgConnection>>,
+    key: i64,
+}
+
+impl LeaderLock {
+    /// Try to take the lock, returning `None` if another session holds it.
+    pub async fn try_acquire(db_pool: &Arc<DbPool>, key: i64) -> Result<Option<Self>> {
+        let mut conn = db_pool.get_owned().await?;
+        let result: LockResult = diesel::sql_query("SELECT pg_try_advisory_lock($1) AS locked")
+            .bind::<BigInt, _>(key)
+            .get_result(&mut conn)
+            .await?;
+        Ok(result.locked.then_some(Self { conn: Some(conn), key }))
+    }
+
+    /// Wait until the lock is ours, retrying every `retry_interval`.
+    pub async fn acquire(db_pool: &Arc<DbPool>, key: i64, retry_interval: Duration) -> Result<Self> {
+        let mut waiting = false;
+        loop {
+            if let Some(lock) = Self::try_acquire(db_pool, key).await? {
+                tracing::info!("Acquired leader lock {}", key);
+                return Ok(lock);
+            }
+            if !waiting {
+                tracing::info!("Another instance holds leader lock {}, standing by", key);
+                waiting = true;
+            }
+            tokio::time::sleep(retry_interval).await;
+        }
+    }
+
+    /// Whether the session that took the lock is still alive and holding it.
+    pub async fn is_held(&mut self) -> bool {
+        let Some(conn) = self.conn.as_mut() else {
+            return false;
+        };
+        let result: Result<LockResult, _> = diesel::sql_query(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM pg_locks
+                WHERE locktype = 'advisory'
+                  AND pid = pg_backend_pid()
+                  AND granted
+                  AND ((classid::BIGINT << 32) | objid::BIGINT) = $1
+                  AND objsubid = 1
+            ) AS locked
+            "#,
+        )
+        .bind::<BigInt, _>(self.key)
+        .get_result(conn)
+        .await;
+        match result {
+            Ok(result) => result.locked,
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to check leader lock {}", self.key);
+                false
+            }
+        }
+    }
+
+    /// Release the lock and return the connection to the pool.
+    pub async fn release(mut self) -> Result<()> {
+        if let Some(mut conn) = self.conn.take() {
+            unlock(&mut conn, self.key).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LeaderLock {
+    fn drop(&mut self) {
+        // The connection goes back to the pool, so it must not keep the lock with it
+        if let Some(mut conn) = self.conn.take() {
+            let key = self.key;
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(async move {
+                    if let Err(err) = unlock(&mut conn, key).await {
+                        tracing::warn!(error = ?err, "Failed to release leader lock {}", key);
+                    }
+                });
+            }
+        }
+    }
+}
+
+async fn unlock(conn: &mut AsyncPgConnection, key: i64) -> Result<()> {
+    diesel::sql_query("SELECT pg_advisory_unlock($1) AS locked")
+        .bind::<BigInt, _>(key)
+        .get_result::<LockResult>(conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bento_types::repository::test_utils::create_test_pool;
+
+    const TEST_LOCK_KEY: i64 = 0x62_656e_746f_ff01;
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_single_leader() {
+        let db = create_test_pool().await;
+
+        let mut leader = LeaderLock::try_acquire(&db, TEST_LOCK_KEY).await.unwrap().expect("lock is free");
+        assert!(leader.is_held().await);
+        assert!(LeaderLock::try_acquire(&db, TEST_LOCK_KEY).await.unwrap().is_none());
+
+        leader.release().await.unwrap();
+        let mut next = LeaderLock::try_acquire(&db, TEST_LOCK_KEY).await.unwrap().expect("lock was released");
+        assert!(next.is_held().await);
+        next.release().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_standby_takes_over_dropped_leader() {
+        let db = create_test_pool().await;
+
+        let leader = LeaderLock::try_acquire(&db, TEST_LOCK_KEY + 1).await.unwrap().expect("lock is free");
+        let standby = tokio::spawn({
+            let db = db.clone();
+            async move { LeaderLock::acquire(&db, TEST_LOCK_KEY + 1, Duration::from_millis(50)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!standby.is_finished());
+
+        drop(leader);
+        let mut next = tokio::time::timeout(Duration::from_secs(5), standby).await.unwrap().unwrap().unwrap();
+        assert!(next.is_held().await);
+        next.release().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_int4_pair_lock_is_not_held() {
+        let db = create_test_pool().await;
+        let key = TEST_LOCK_KEY + 2;
+
+        let mut leader = LeaderLock::try_acquire(&db, key).await.unwrap().expect("lock is free");
+        let conn = leader.conn.as_mut().unwrap();
+        unlock(conn, key).await.unwrap();
+        // Same classid and objid as the bigint key, taken as a pair of int4 keys instead
+        diesel::sql_query("SELECT pg_advisory_lock(($1 >> 32)::INT4, ($1 & 4294967295)::INT4)")
+            .bind::<BigInt, _>(key)
+            .execute(conn)
+            .await
+            .unwrap();
+        assert!(!leader.is_held().await);
+
+        let conn = leader.conn.as_mut().unwrap();
+        diesel::sql_query("SELECT pg_advisory_unlock(($1 >> 32)::INT4, ($1 & 4294967295)::INT4)")
+            .bind::<BigInt, _>(key)
+            .execute(conn)
+            .await
+            .unwrap();
+        leader.release().await.unwrap();
+    }
+}
@@ -1,4 +1,6 @@
 pub mod fetch;
+pub mod gaps;
+pub mod leader;
 pub mod pipeline;
 pub mod stage;
 pub mod worker;
@@ -1,9 +1,9 @@
 use std::sync::Arc;
 
-use crate::Client;
+use crate::{notify::Notifier, Client};
 use anyhow::Result;
 use bento_trait::{processor::DynProcessor, stage::StageHandler};
-use bento_types::{BlockBatch, DbPool, StageMessage};
+use bento_types::{repository::upsert_processor_status, BlockBatch, DbPool, StageMessage};
 use tokio::sync::mpsc;
 
 use super::stage::{ProcessorStage, StorageStage};
@@ -16,12 +16,12 @@ pub struct Pipeline {
 }
 
 impl Pipeline {
-    pub fn new(client: Arc<Client>, db_pool: Arc<DbPool>, processor: DynProcessor) -> Self {
+    pub fn new(client: Arc<Client>, db_pool: Arc<DbPool>, processor: DynProcessor, notifier: Option<Notifier>) -> Self {
         let processor = Arc::new(processor);
         Self {
             client,
             processor: Arc::new(ProcessorStage { processor: processor.clone() }),
-            storage: Arc::new(StorageStage { db_pool, processor }),
+            storage: Arc::new(StorageStage { db_pool, processor, notifier }),
         }
     }
 
@@ -30,6 +30,8 @@ impl Pipeline {
         let (process_tx, process_rx) = mpsc::channel(channel_capacity);
         let (storage_tx, storage_rx) = mpsc::channel(channel_capacity);
 
+        let checkpoint = batches.iter().flat_map(|batch| batch.blocks.iter()).map(|b| b.block.timestamp).max();
+
         // Send the fetched batches to the processor
         for batch in batches {
             process_tx.send(StageMessage::Batch(batch)).await?;
@@ -90,6 +92,11 @@ impl Pipeline {
         process_result??;
         storage_result??;
 
+        if let Some(last_timestamp) = checkpoint {
+            upsert_processor_status(self.storage.db_pool.clone(), self.storage.processor.name(), last_timestamp)
+                .await?;
+        }
+
         tracing::debug!("Pipeline execution completed successfully");
         Ok(())
     }
@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use bento_trait::{processor::DynProcessor, stage::StageHandler};
-use bento_types::{DbPool, StageMessage};
+use bento_types::{notifications::IndexedNotification, repository::enqueue_webhook_deliveries, DbPool, StageMessage};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection};
+
+use crate::notify::Notifier;
 
 pub struct ProcessorStage {
     pub processor: Arc<DynProcessor>,
@@ -30,11 +33,13 @@ impl StageHandler for ProcessorStage {
 pub struct StorageStage {
     pub db_pool: Arc<DbPool>,
     pub processor: Arc<DynProcessor>,
+    /// Publishes the stored output to stream subscribers and webhooks
+    pub notifier: Option<Notifier>,
 }
 
 impl StorageStage {
-    pub fn new(db_pool: Arc<DbPool>, processor: Arc<DynProcessor>) -> Self {
-        Self { db_pool, processor }
+    pub fn new(db_pool: Arc<DbPool>, processor: Arc<DynProcessor>, notifier: Option<Notifier>) -> Self {
+        Self { db_pool, processor, notifier }
     }
 }
 
@@ -43,7 +48,33 @@ impl StageHandler for StorageStage {
     async fn handle(&self, msg: StageMessage) -> Result<StageMessage> {
         match msg {
             StageMessage::Processed(output) => {
-                self.processor.store_output(output).await?;
+                let notifications = self.notifier.as_ref().map(|_| IndexedNotification::from_output(&output));
+                let deliveries = match (&self.notifier, &notifications) {
+                    (Some(notifier), Some(notifications)) => {
+                        notifier.webhook_deliveries(&self.db_pool, notifications).await?
+                    }
+                    _ => Vec::new(),
+                };
+
+                // Webhooks are delivered at least once, so their deliveries commit with the output
+                let mut conn = self.db_pool.get().await?;
+                conn.transaction(|conn| {
+                    async move {
+                        self.processor.store_output(conn, output).await?;
+                        enqueue_webhook_deliveries(conn, &deliveries).await?;
+                        Ok::<_, anyhow::Error>(())
+                    }
+                    .scope_boxed()
+                })
+                .await?;
+                drop(conn);
+
+                if let (Some(notifier), Some(notifications)) = (&self.notifier, notifications) {
+                    // Subscribers are best effort, a failed publish must not fail the batch
+                    if let Err(err) = notifier.publish(&self.db_pool, notifications).await {
+                        tracing::warn!(processor = self.processor.name(), error = ?err, "Failed to publish notifications");
+                    }
+                }
                 Ok(StageMessage::Complete)
             }
             _ => Ok(msg),
@@ -2,33 +2,64 @@ use crate::{
     client::Client,
     config::ProcessorConfig,
     db::{new_db_pool, DbPool},
+    notify::Notifier,
 };
-use anyhow::Result;
-use bento_trait::stage::BlockProvider;
+use anyhow::{Context, Result};
+use bento_trait::{processor::DynProcessor, stage::BlockProvider};
 use bento_types::{
+    config::AppConfigTrait,
+    models::BackfillRangeModel,
     network::Network,
-    repository::{get_blocks_at_height, get_max_block_timestamp},
+    repository::{
+        claim_backfill_range, complete_backfill_range, count_pending_backfill_ranges, get_blocks_at_height,
+        get_max_block_timestamp, insert_backfill_ranges, release_backfill_range, renew_backfill_claim,
+    },
     BlockBatch, BlockRange, DEFAULT_GROUP_NUM,
 };
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection};
+use diesel_migrations::EmbeddedMigrations;
+use futures::StreamExt;
+
+use super::{
+    fetch::{fetch_chunk, fetch_parallel},
+    gaps::{detect_gaps, plan_gap_fill, GapCheckOptions, GapFillJob, GapFillSummary, GapOptions, GapReport},
+    leader::{LeaderLock, SYNC_LEADER_LOCK_KEY},
+    pipeline::Pipeline,
+};
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::watch, time::sleep as tokio_sleep};
 
-use super::{fetch::fetch_parallel, pipeline::Pipeline};
-use std::{sync::Arc, time::Duration};
-use tokio::time::sleep as tokio_sleep;
+/// How often a standby worker checks whether it can become the sync leader.
+const LEADER_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// Claims of a shared backfill not renewed for this long are considered abandoned and can be
+/// taken over.
+const BACKFILL_CLAIM_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// How often a worker renews the claim of the range it is backfilling.
+const BACKFILL_CLAIM_RENEW_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct SyncOptions {
     pub step: u64,
     pub backstep: u64,
     pub request_interval: u64,
+    /// Periodic gap detection and filling, disabled when `None`
+    pub gap_check: Option<GapCheckOptions>,
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone)]
 pub struct BackfillOptions {
     pub start_ts: Option<u64>,
     pub stop_ts: Option<u64>,
     pub request_interval: u64,
     pub step: u64,
     pub backstep: u64,
+    /// Name of a shared backfill job. Processes using the same job split its ranges between them.
+    pub job: Option<String>,
 }
 
 pub struct Worker {
@@ -39,6 +70,15 @@ pub struct Worker {
     pub sync_opts: Option<SyncOptions>,
     pub backfill_opts: Option<BackfillOptions>,
     pub workers: usize,
+    /// Identifies this instance in backfill range claims
+    pub instance_id: String,
+    /// App migrations applied after the framework migrations, in registration order, then those
+    /// of the processors, see [`ProcessorConfig::with_migrations`]
+    pub migrations: Vec<&'static EmbeddedMigrations>,
+    /// Reloaded app config of the custom processors, picked up from the next batch on
+    pub app_config_updates: Option<watch::Receiver<Option<Arc<dyn AppConfigTrait>>>>,
+    /// Publishes stored blocks, events and address activity to stream subscribers
+    pub notifier: Option<Notifier>,
 }
 
 impl Worker {
@@ -60,16 +100,50 @@ impl Worker {
             backfill_opts,
             client: Arc::new(Client::new(network)),
             workers,
+            instance_id: uuid::Uuid::new_v4().to_string(),
+            migrations: Vec::new(),
+            app_config_updates: None,
+            notifier: None,
         })
     }
 
+    /// Register app migrations to be applied before the worker starts syncing
+    pub fn with_migrations(mut self, migrations: &'static EmbeddedMigrations) -> Self {
+        self.migrations.push(migrations);
+        self
+    }
+
+    /// Rebuild the custom processors with the latest app config sent on `updates` before each
+    /// batch. Sync state and the DB pool are kept.
+    pub fn with_app_config_updates(mut self, updates: watch::Receiver<Option<Arc<dyn AppConfigTrait>>>) -> Self {
+        self.app_config_updates = Some(updates);
+        self
+    }
+
+    /// Publish what the pipeline stores through `notifier`
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// The processor configs to build the processors of the next batch from.
+    pub fn current_processor_configs(&self) -> Vec<ProcessorConfig> {
+        match &self.app_config_updates {
+            Some(updates) => {
+                let app_config = updates.borrow().clone();
+                self.processor_configs.iter().map(|config| config.with_app_config(app_config.clone())).collect()
+            }
+            None => self.processor_configs.clone(),
+        }
+    }
+
     pub async fn run(&self) -> Result<()> {
-        self.run_migrations().await;
+        self.run_migrations().await?;
 
-        match self.backfill_opts {
+        match &self.backfill_opts {
             Some(opts) => {
                 tracing::info!("Starting backfill with options: {:?}", opts);
-                self.run_backfill(opts).await?;
+                self.run_backfill(opts.clone()).await?;
             }
             None => {
                 tracing::info!("Starting sync with options: {:?}", self.sync_opts);
@@ -106,6 +180,10 @@ impl Worker {
             backfill_opts.request_interval
         );
 
+        if let Some(job) = &backfill_opts.job {
+            return self.run_shared_backfill(job, start_ts, stop_ts, &backfill_opts).await;
+        }
+
         let mut current_ts = start_ts;
         while current_ts < stop_ts {
             let chunk_end = std::cmp::min(current_ts + backfill_opts.step, stop_ts);
@@ -123,12 +201,158 @@ impl Worker {
         Ok(())
     }
 
+    /// Backfills [start_ts, stop_ts] as part of a job shared with other processes.
+    /// The range is split into `step` sized chunks and each process claims chunks until none are left.
+    async fn run_shared_backfill(
+        &self,
+        job: &str,
+        start_ts: u64,
+        stop_ts: u64,
+        backfill_opts: &BackfillOptions,
+    ) -> Result<()> {
+        let mut ranges = Vec::new();
+        let mut current_ts = start_ts;
+        while current_ts < stop_ts {
+            let chunk_end = std::cmp::min(current_ts + backfill_opts.step, stop_ts);
+            ranges.push(BackfillRangeModel::new(job, current_ts as i64, chunk_end as i64));
+            current_ts = chunk_end;
+        }
+        insert_backfill_ranges(self.db_pool.clone(), ranges).await?;
+
+        while let Some(range) =
+            claim_backfill_range(self.db_pool.clone(), job, &self.instance_id, BACKFILL_CLAIM_TIMEOUT).await?
+        {
+            tracing::info!("Claimed range {} to {} of backfill job {}", range.from_ts, range.to_ts, job);
+
+            let result = tokio::select! {
+                result = self.sync_range(range.from_ts as u64, range.to_ts as u64) => result,
+                never = self.keep_backfill_claim(&range) => match never {},
+            };
+            if let Err(err) = result {
+                release_backfill_range(self.db_pool.clone(), &range, &self.instance_id).await?;
+                return Err(err);
+            }
+            if !complete_backfill_range(self.db_pool.clone(), &range, &self.instance_id).await? {
+                tracing::warn!(
+                    "Range {} to {} of backfill job {} was taken over by another worker",
+                    range.from_ts,
+                    range.to_ts,
+                    job
+                );
+            }
+
+            let pending = count_pending_backfill_ranges(self.db_pool.clone(), job).await?;
+            tracing::info!("{} ranges of backfill job {} left", pending, job);
+
+            tokio_sleep(Duration::from_millis(backfill_opts.request_interval)).await;
+        }
+
+        tracing::info!("No ranges left to claim in backfill job {}", job);
+        Ok(())
+    }
+
+    /// Keeps the claim on `range` alive while it is being backfilled. Never returns: it stops
+    /// renewing once the claim is lost and is dropped when the range is done.
+    async fn keep_backfill_claim(&self, range: &BackfillRangeModel) -> Infallible {
+        loop {
+            tokio_sleep(BACKFILL_CLAIM_RENEW_INTERVAL).await;
+            match renew_backfill_claim(self.db_pool.clone(), range, &self.instance_id).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::warn!(
+                        "Lost the claim on range {} to {} of backfill job {}",
+                        range.from_ts,
+                        range.to_ts,
+                        range.job
+                    );
+                    return std::future::pending().await;
+                }
+                Err(err) => tracing::warn!(error = ?err, "Failed to renew backfill claim"),
+            }
+        }
+    }
+
+    /// Deletes what the configured processors stored for [from_ts, to_ts] and processes the
+    /// range again, one `step` sized chunk at a time. The blocks of a chunk are fetched and
+    /// processed before anything is deleted, then all processors purge the chunk and store it
+    /// again in a single transaction, so a failure leaves the previous rows in place.
+    pub async fn reindex(&self, from_ts: u64, to_ts: u64, step: u64) -> Result<()> {
+        let processors: Vec<_> =
+            purge_ordered(&self.processor_configs).map(|config| config.build_processor(self.db_pool.clone())).collect();
+
+        let mut current_ts = from_ts;
+        while current_ts < to_ts {
+            let chunk_end = std::cmp::min(current_ts + step, to_ts);
+            let range = BlockRange { from_ts: current_ts, to_ts: chunk_end };
+
+            let batches = fetch_parallel(self.client.clone(), range, self.workers)
+                .await
+                .with_context(|| format!("Failed to fetch blocks from {} to {}", current_ts, chunk_end))?;
+            self.reindex_chunk(&processors, range, &batches)
+                .await
+                .with_context(|| format!("Failed to reindex blocks from {} to {}", current_ts, chunk_end))?;
+
+            tracing::info!("Reindexed blocks from {} to {}", current_ts, chunk_end);
+            current_ts = chunk_end;
+        }
+
+        Ok(())
+    }
+
+    /// Processes `batches` with `processors`, given in purge order, then replaces what they
+    /// stored for `range` in a single transaction.
+    async fn reindex_chunk(
+        &self,
+        processors: &[DynProcessor],
+        range: BlockRange,
+        batches: &[BlockBatch],
+    ) -> Result<()> {
+        let mut outputs = Vec::new();
+        for processor in processors {
+            for batch in batches {
+                let output = processor
+                    .process_blocks(batch.blocks.clone())
+                    .await
+                    .with_context(|| format!("Processor {} failed", processor.name()))?;
+                outputs.push((processor, output));
+            }
+        }
+
+        let mut conn = self.db_pool.get().await?;
+        conn.transaction(|conn| {
+            async move {
+                for processor in processors {
+                    processor.purge_range(conn, &range).await?;
+                }
+                // Rows referencing the blocks and transactions go in after them
+                for (processor, output) in outputs.into_iter().rev() {
+                    processor.store_output(conn, output).await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    /// Runs the realtime sync loop. Only one instance syncs at a time: the others stand by
+    /// until the leader lock is released or its holder dies.
     pub async fn run_sync(&self) -> Result<()> {
         let request_interval = self.sync_opts.unwrap().request_interval;
         // let step = self.sync_opts.unwrap().step;
         let backstep = self.sync_opts.unwrap().backstep;
 
+        let gap_check = self.sync_opts.unwrap().gap_check;
+        let mut last_gap_check = Instant::now();
+
+        let mut leader = LeaderLock::acquire(&self.db_pool, SYNC_LEADER_LOCK_KEY, LEADER_RETRY_INTERVAL).await?;
+
         loop {
+            if !leader.is_held().await {
+                tracing::warn!("Lost sync leadership, waiting to be elected again");
+                leader = LeaderLock::acquire(&self.db_pool, SYNC_LEADER_LOCK_KEY, LEADER_RETRY_INTERVAL).await?;
+            }
+
             tracing::info!("Syncing...");
             let latest_remote_ts = self.get_latest_block_timestamp_from_node(0, 0).await?;
             let latest_local_ts = get_max_block_timestamp(&self.db_pool)
@@ -144,7 +368,30 @@ impl Worker {
                 latest_local_ts - backstep
             };
 
-            self.sync_range(start_ts, latest_remote_ts).await?;
+            let range = BlockRange { from_ts: start_ts, to_ts: latest_remote_ts };
+            let batches = match fetch_parallel(self.client.clone(), range, self.workers).await {
+                Ok(batches) => batches,
+                Err(err) => {
+                    tracing::error!(range = ?range, error = ?err, "Failed to fetch blocks, skipping range");
+                    Vec::new()
+                }
+            };
+
+            // Another instance may have been elected while we were fetching
+            if !leader.is_held().await {
+                tracing::warn!("Lost sync leadership, dropping blocks from {} to {}", start_ts, latest_remote_ts);
+                continue;
+            }
+            self.process_batches(batches).await?;
+
+            if let Some(gap_check) = gap_check {
+                if last_gap_check.elapsed() >= Duration::from_millis(gap_check.interval) && leader.is_held().await {
+                    last_gap_check = Instant::now();
+                    if let Err(err) = self.check_gaps(&gap_check).await {
+                        tracing::error!(error = ?err, "Gap check failed");
+                    }
+                }
+            }
 
             let sleep_duration = Duration::from_millis(request_interval);
             tracing::info!(
@@ -157,8 +404,124 @@ impl Worker {
         }
     }
 
+    /// Detects and fills the gaps of the last `lookback` milliseconds.
+    async fn check_gaps(&self, gap_check: &GapCheckOptions) -> Result<()> {
+        let since_ts = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(gap_check.lookback);
+        let opts = GapOptions { min_height: 0, since_ts: Some(since_ts), threshold: gap_check.threshold };
+        let report = detect_gaps(&self.db_pool, &opts).await?;
+        if report.is_empty() {
+            return Ok(());
+        }
+
+        let summary = self.fill_gaps(&report, self.workers).await?;
+        tracing::info!("Gap check done: {:?}", summary);
+        Ok(())
+    }
+
+    /// Fills the gaps of `report`, running at most `concurrency` fetches at a time.
+    ///
+    /// Missing heights are fetched as timestamp ranges between the blocks around each gap, keeping
+    /// only the blocks of the affected chains. Heights that are still missing afterwards are
+    /// synced one by one on their chain. Timestamp gaps not explained by a missing height are
+    /// synced as timestamp ranges. Failures are logged and counted rather than aborting the
+    /// remaining gaps.
+    pub async fn fill_gaps(&self, report: &GapReport, concurrency: usize) -> Result<GapFillSummary> {
+        let concurrency = concurrency.max(1);
+        let mut summary = GapFillSummary::default();
+
+        let jobs = plan_gap_fill(&report.height_gaps);
+        tracing::info!(
+            "Filling {} missing blocks with {} range fetches ({} concurrent)",
+            report.missing_blocks(),
+            jobs.len(),
+            concurrency
+        );
+
+        let filled: HashSet<(i64, i64, i64)> = futures::stream::iter(jobs.iter())
+            .map(|job| self.fill_gap_job(job))
+            .buffer_unordered(concurrency)
+            .fold(HashSet::new(), |mut filled, heights| async move {
+                filled.extend(heights);
+                filled
+            })
+            .await;
+        summary.heights_filled = filled.len();
+
+        let remaining: Vec<(u32, u32, u64)> = report
+            .height_gaps
+            .iter()
+            .flat_map(|gap| {
+                (gap.from_height..=gap.to_height)
+                    .filter(|height| !filled.contains(&(gap.chain_from, gap.chain_to, *height)))
+                    .map(|height| (gap.chain_from as u32, gap.chain_to as u32, height as u64))
+            })
+            .collect();
+
+        if !remaining.is_empty() {
+            tracing::warn!("{} heights were not found in the range fetches, syncing them by height", remaining.len());
+
+            let results: Vec<bool> = futures::stream::iter(remaining)
+                .map(|(chain_from, chain_to, height)| async move {
+                    match self.sync_at_height(height, Some(vec![(chain_from, chain_to)])).await {
+                        Ok(()) => true,
+                        Err(err) => {
+                            tracing::error!(height, chain = ?(chain_from, chain_to), error = ?err, "Failed to fill height");
+                            false
+                        }
+                    }
+                })
+                .buffer_unordered(concurrency)
+                .collect()
+                .await;
+            summary.heights_filled += results.iter().filter(|ok| **ok).count();
+            summary.heights_failed += results.iter().filter(|ok| !**ok).count();
+        }
+
+        for range in report.timestamp_ranges() {
+            tracing::info!("Filling timestamp gap from {} to {}", range.from_ts, range.to_ts);
+            match self.try_sync_range(range.from_ts, range.to_ts).await {
+                Ok(()) => summary.ranges_filled += 1,
+                Err(err) => {
+                    summary.ranges_failed += 1;
+                    tracing::error!(range = ?range, error = ?err, "Failed to fill timestamp gap");
+                }
+            }
+        }
+
+        if summary.heights_failed > 0 || summary.ranges_failed > 0 {
+            tracing::warn!("Some gaps could not be filled, they will be retried by the next gap check or fill");
+        }
+        Ok(summary)
+    }
+
+    /// Fetches the range of `job` and processes the blocks that belong to its gaps, returning the
+    /// (chain_from, chain_to, height) of the blocks stored. Errors are logged and yield nothing,
+    /// leaving the heights to the by-height fallback.
+    async fn fill_gap_job(&self, job: &GapFillJob) -> Vec<(i64, i64, i64)> {
+        let batch = match fetch_chunk(self.client.clone(), job.range).await {
+            Ok(batch) => batch,
+            Err(err) => {
+                tracing::error!(range = ?job.range, error = ?err, "Failed to fetch gap range");
+                return Vec::new();
+            }
+        };
+
+        let blocks: Vec<_> = batch.blocks.into_iter().filter(|b| job.wants(&b.block)).collect();
+        if blocks.is_empty() {
+            return Vec::new();
+        }
+
+        let heights = blocks.iter().map(|b| (b.block.chain_from, b.block.chain_to, b.block.height)).collect();
+        if let Err(err) = self.run_pipeline(vec![BlockBatch { blocks, range: job.range }]).await {
+            tracing::error!(range = ?job.range, error = ?err, "Failed to process gap range");
+            return Vec::new();
+        }
+        heights
+    }
+
     /// Syncs the blocks in the range [start_ts, stop_ts].
     /// This method will fetch blocks in batches and process them using the configured processors.
+    /// A range whose blocks cannot be fetched is logged and skipped.
     async fn sync_range(&self, start_ts: u64, stop_ts: u64) -> Result<()> {
         let range = BlockRange { from_ts: start_ts, to_ts: stop_ts };
 
@@ -176,6 +539,24 @@ impl Worker {
             }
         };
 
+        self.process_batches(batches).await
+    }
+
+    /// Like [`Self::sync_range`], but fails when the blocks cannot be fetched so that the caller
+    /// can count the range as not synced.
+    async fn try_sync_range(&self, start_ts: u64, stop_ts: u64) -> Result<()> {
+        let range = BlockRange { from_ts: start_ts, to_ts: stop_ts };
+
+        tracing::info!("Syncing blocks in range: {:?}", range);
+
+        let batches = fetch_parallel(self.client.clone(), range, self.workers)
+            .await
+            .with_context(|| format!("Failed to fetch blocks from {} to {}", start_ts, stop_ts))?;
+
+        self.process_batches(batches).await
+    }
+
+    async fn process_batches(&self, batches: Vec<BlockBatch>) -> Result<()> {
         if batches.is_empty() {
             tracing::warn!("No blocks found in the specified range");
             return Ok(());
@@ -193,8 +574,6 @@ impl Worker {
     /// * `chains_to_sync` - Optional list of specific (chain_from, chain_to) pairs to fetch. If None, fetches from all 16 chains.
     ///
     pub async fn sync_at_height(&self, height: u64, chains_to_sync: Option<Vec<(u32, u32)>>) -> Result<()> {
-        use anyhow::Context;
-
         let groups = match chains_to_sync {
             Some(chains) => chains,
             None => self.get_groups(),
@@ -272,7 +651,7 @@ impl Worker {
 
     async fn run_pipeline(&self, batches: Vec<BlockBatch>) -> Result<()> {
         let tasks: Vec<_> = self
-            .processor_configs
+            .current_processor_configs()
             .iter()
             .map(|processor_config| {
                 let pool_clone = self.db_pool.clone();
@@ -280,7 +659,7 @@ impl Worker {
                 let processor = processor_config.build_processor(pool_clone.clone());
                 let processor_name = processor.name().to_string();
                 let batches_clone = batches.clone();
-                let pipeline = Pipeline::new(client_clone, pool_clone, processor);
+                let pipeline = Pipeline::new(client_clone, pool_clone, processor, self.notifier.clone());
 
                 async move {
                     pipeline.run(batches_clone).await.map_err(|err| {
@@ -310,12 +689,250 @@ impl Worker {
     // feature enabled (which uses libpq under the hood, hence why we named the feature
     // this way).
     #[cfg(feature = "libpq")]
-    async fn run_migrations(&self) {
-        use crate::db::run_pending_migrations;
-        use diesel::{pg::PgConnection, Connection};
+    pub async fn run_migrations(&self) -> Result<()> {
+        use crate::db::run_migrations_with_lock;
+
+        tracing::info!("Running migrations");
+        let db_url = self.db_url.clone();
+        let migrations = self.app_migrations();
+        tokio::task::spawn_blocking(move || run_migrations_with_lock(&db_url, &migrations)).await?
+    }
+
+    /// The worker's migrations followed by those of its processors, each set once even when
+    /// several processors share it.
+    #[cfg(feature = "libpq")]
+    fn app_migrations(&self) -> Vec<&'static diesel_migrations::EmbeddedMigrations> {
+        let mut migrations = self.migrations.clone();
+        for processor_migrations in self.processor_configs.iter().filter_map(ProcessorConfig::migrations) {
+            if !migrations.iter().any(|m| std::ptr::eq(*m, processor_migrations)) {
+                migrations.push(processor_migrations);
+            }
+        }
+        migrations
+    }
+
+    /// Without the libpq feature migrations are skipped, see [`crate::MIGRATIONS_SUPPORTED`]
+    #[cfg(not(feature = "libpq"))]
+    pub async fn run_migrations(&self) -> Result<()> {
+        tracing::warn!("Built without the libpq feature, skipping migrations");
+        Ok(())
+    }
+}
+
+/// `configs` in the order their rows must be purged, see [`ProcessorConfig::purge_order`].
+fn purge_ordered(configs: &[ProcessorConfig]) -> impl Iterator<Item = &ProcessorConfig> {
+    let mut configs: Vec<_> = configs.iter().collect();
+    configs.sort_by_key(|config| config.purge_order());
+    configs.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use bento_trait::processor::{new_processor, ProcessorTrait};
+    use bento_types::{
+        processors::ProcessorOutput, repository::test_utils::create_test_pool, schema::processor_status, BlockAndEvents,
+    };
+    use diesel::{ExpressionMethods, QueryDsl};
+    use diesel_async::{AsyncPgConnection, RunQueryDsl};
+    use std::sync::Mutex;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    /// Records its purges and stores. `marker` is written to `processor_status` when purging,
+    /// so a rolled back transaction can be observed.
+    #[derive(Debug)]
+    struct RecordingProcessor {
+        name: &'static str,
+        db: Arc<DbPool>,
+        log: Log,
+        marker: Option<&'static str>,
+        fail_store: bool,
+    }
+
+    #[async_trait]
+    impl ProcessorTrait for RecordingProcessor {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn connection_pool(&self) -> &Arc<DbPool> {
+            &self.db
+        }
+
+        async fn process_blocks(&self, _blocks: Vec<BlockAndEvents>) -> Result<ProcessorOutput> {
+            Ok(ProcessorOutput::Block(Vec::new()))
+        }
+
+        async fn store_output(&self, _conn: &mut AsyncPgConnection, _output: ProcessorOutput) -> Result<()> {
+            self.log.lock().unwrap().push(format!("store {}", self.name));
+            if self.fail_store {
+                anyhow::bail!("store failed");
+            }
+            Ok(())
+        }
+
+        async fn purge_range(&self, conn: &mut AsyncPgConnection, _range: &BlockRange) -> Result<()> {
+            self.log.lock().unwrap().push(format!("purge {}", self.name));
+            if let Some(marker) = self.marker {
+                diesel::insert_into(processor_status::table)
+                    .values((processor_status::processor.eq(marker), processor_status::last_timestamp.eq(0)))
+                    .execute(conn)
+                    .await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Keeps the default `purge_range`
+    #[derive(Debug)]
+    struct NoPurgeProcessor(Arc<DbPool>);
+
+    #[async_trait]
+    impl ProcessorTrait for NoPurgeProcessor {
+        fn name(&self) -> &'static str {
+            "no-purge"
+        }
+
+        fn connection_pool(&self) -> &Arc<DbPool> {
+            &self.0
+        }
+
+        async fn process_blocks(&self, _blocks: Vec<BlockAndEvents>) -> Result<ProcessorOutput> {
+            Ok(ProcessorOutput::Block(Vec::new()))
+        }
+
+        async fn store_output(&self, _conn: &mut AsyncPgConnection, _output: ProcessorOutput) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn test_worker() -> Worker {
+        Worker {
+            db_pool: create_test_pool().await,
+            client: Arc::new(Client::new(Network::default())),
+            processor_configs: Vec::new(),
+            db_url: String::new(),
+            sync_opts: None,
+            backfill_opts: None,
+            workers: 1,
+            instance_id: "test".to_string(),
+            migrations: Vec::new(),
+            app_config_updates: None,
+            notifier: None,
+        }
+    }
+
+    fn recording(worker: &Worker, name: &'static str, log: &Log) -> RecordingProcessor {
+        RecordingProcessor { name, db: worker.db_pool.clone(), log: log.clone(), marker: None, fail_store: false }
+    }
+
+    const RANGE: BlockRange = BlockRange { from_ts: 0, to_ts: 1 };
+
+    fn batches() -> Vec<BlockBatch> {
+        vec![BlockBatch { blocks: Vec::new(), range: RANGE }]
+    }
+
+    #[test]
+    fn test_purge_order() {
+        fn noop(db: Arc<DbPool>, _: Option<Arc<dyn AppConfigTrait>>) -> Box<dyn ProcessorTrait> {
+            Box::new(NoPurgeProcessor(db))
+        }
+        let configs = vec![
+            ProcessorConfig::BlockProcessor,
+            ProcessorConfig::TxProcessor,
+            ProcessorConfig::EventProcessor,
+            ProcessorConfig::custom("app", noop, None),
+        ];
+        let names: Vec<_> = purge_ordered(&configs).map(|c| c.name()).collect();
+        assert_eq!(names, vec!["app", "event", "tx", "block"]);
+    }
+
+    #[cfg(feature = "libpq")]
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_app_migrations_are_deduplicated() {
+        use diesel_migrations::{embed_migrations, EmbeddedMigrations};
+
+        static SHARED: EmbeddedMigrations = embed_migrations!("../bento-types/migrations");
+        static OTHER: EmbeddedMigrations = embed_migrations!("../bento-types/migrations");
+        fn noop(db: Arc<DbPool>, _: Option<Arc<dyn AppConfigTrait>>) -> Box<dyn ProcessorTrait> {
+            Box::new(NoPurgeProcessor(db))
+        }
+
+        let mut worker = test_worker().await;
+        worker.migrations = vec![&SHARED];
+        worker.processor_configs = vec![
+            ProcessorConfig::custom("a", noop, None).with_migrations(&SHARED),
+            ProcessorConfig::custom("b", noop, None).with_migrations(&OTHER),
+            ProcessorConfig::custom("c", noop, None).with_migrations(&OTHER),
+            ProcessorConfig::BlockProcessor,
+        ];
+
+        // Identical contents, but distinct sets are kept apart
+        let migrations = worker.app_migrations();
+        assert_eq!(migrations.len(), 2);
+        assert!(std::ptr::eq(migrations[0], &SHARED));
+        assert!(std::ptr::eq(migrations[1], &OTHER));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_reindex_chunk_purges_in_order_and_stores_in_reverse() {
+        let worker = test_worker().await;
+        let log = Log::default();
+        let processors = vec![
+            new_processor(recording(&worker, "app", &log)),
+            new_processor(recording(&worker, "tx", &log)),
+            new_processor(recording(&worker, "block", &log)),
+        ];
+
+        worker.reindex_chunk(&processors, RANGE, &batches()).await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["purge app", "purge tx", "purge block", "store block", "store tx", "store app"]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_reindex_chunk_rolls_back_on_store_failure() {
+        const MARKER: &str = "test-reindex-rollback";
+        let worker = test_worker().await;
+        let log = Log::default();
+        let processors = vec![
+            new_processor(RecordingProcessor { marker: Some(MARKER), ..recording(&worker, "app", &log) }),
+            new_processor(RecordingProcessor { fail_store: true, ..recording(&worker, "block", &log) }),
+        ];
+
+        assert!(worker.reindex_chunk(&processors, RANGE, &batches()).await.is_err());
+        assert!(log.lock().unwrap().contains(&"purge app".to_string()));
+
+        // The purge ran in the failed transaction, so its row is gone
+        let mut conn = worker.db_pool.get().await.unwrap();
+        let count: i64 = processor_status::table
+            .filter(processor_status::processor.eq(MARKER))
+            .count()
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
 
-        tracing::info!("Running migrations: {:?}", self.db_url);
-        let mut conn = PgConnection::establish(&self.db_url).expect("migrations failed!");
-        run_pending_migrations(&mut conn);
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_reindex_chunk_needs_purge_support() {
+        let worker = test_worker().await;
+        let log = Log::default();
+        let processors = vec![
+            new_processor(recording(&worker, "app", &log)),
+            new_processor(NoPurgeProcessor(worker.db_pool.clone())),
+        ];
+
+        let err = worker.reindex_chunk(&processors, RANGE, &batches()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Processor no-purge does not support reindexing");
+        // Nothing is stored once a purge fails
+        assert!(!log.lock().unwrap().iter().any(|entry| entry.starts_with("store")));
     }
 }
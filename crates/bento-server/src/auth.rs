@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use bento_types::repository::{get_active_api_key, hash_api_key};
+use bento_types::{ApiKeyModel, DbPool};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityRequirement, SecurityScheme};
+use utoipa::openapi::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::error::AppError;
+
+/// Header carrying the API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Name of the API key security scheme in the OpenAPI document.
+pub const API_KEY_SCHEME: &str = "api_key";
+
+/// How long a valid key is cached, so a revoked key keeps working for at most this long.
+const KEY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Database lookups of uncached keys allowed to each client IP. Valid keys are cached, so this
+/// only throttles clients trying keys that do not exist.
+const KEY_LOOKUP_RATE_LIMIT: RateLimit = RateLimit { requests: 30, per: Duration::from_secs(60) };
+
+/// Buckets kept before idle ones are evicted.
+const MAX_BUCKETS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Requests allowed in a burst, refilled evenly over `per`
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn per_minute(requests: u32) -> Self {
+        Self { requests, per: Duration::from_secs(60) }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self { requests, per: Duration::from_secs(1) }
+    }
+
+    fn tokens_per_sec(&self) -> f64 {
+        self.requests as f64 / self.per.as_secs_f64()
+    }
+}
+
+/// Server-wide limits, applied to every request before the route policies.
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    /// Limit of each client IP sending requests without an API key
+    pub ip_rate_limit: Option<RateLimit>,
+    /// Limit of each API key without its own `requests_per_minute`
+    pub key_rate_limit: Option<RateLimit>,
+    /// Take the client IP from the last `X-Forwarded-For` entry, the one appended by the proxy in
+    /// front of the server. Only safe behind a proxy setting it, as clients can send the header
+    pub trust_forwarded_for: bool,
+}
+
+/// The client of a request, added to the request extensions by the auth layer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Caller {
+    ApiKey { id: i64, name: String },
+    Anonymous(IpAddr),
+}
+
+impl Caller {
+    fn bucket_key(&self) -> String {
+        match self {
+            Caller::ApiKey { id, .. } => format!("key:{id}"),
+            Caller::Anonymous(ip) => format!("ip:{ip}"),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    per: Duration,
+}
+
+/// Token buckets keyed by caller.
+#[derive(Clone, Default)]
+pub struct TokenBuckets(Arc<Mutex<HashMap<String, Bucket>>>);
+
+impl TokenBuckets {
+    /// Take a token from the bucket of `key`, or return the seconds until one is available.
+    pub fn check(&self, key: &str, limit: RateLimit, now: Instant) -> Result<(), u64> {
+        let mut buckets = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_BUCKETS {
+            // Idle longer than their period, these buckets are full again anyway
+            buckets.retain(|_, b| now.duration_since(b.updated) < b.per);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: limit.requests as f64,
+            updated: now,
+            per: limit.per,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.tokens_per_sec()).min(limit.requests as f64);
+        bucket.updated = now;
+        bucket.per = limit.per;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / limit.tokens_per_sec()).ceil() as u64)
+        }
+    }
+}
+
+/// A valid key and when it was looked up
+type CachedKey = (ApiKeyModel, Instant);
+
+/// State of the server-wide auth layer.
+#[derive(Clone)]
+pub struct AuthState {
+    db: Arc<DbPool>,
+    config: AuthConfig,
+    buckets: TokenBuckets,
+    /// Valid keys by key hash. Unknown keys are not cached, the lookup limit throttles them
+    keys: Arc<Mutex<HashMap<String, CachedKey>>>,
+}
+
+impl AuthState {
+    pub fn new(db: Arc<DbPool>, config: AuthConfig) -> Self {
+        Self { db, config, buckets: TokenBuckets::default(), keys: Default::default() }
+    }
+
+    /// Look up `key`, charging the lookup bucket of `ip` whenever the database is queried.
+    async fn lookup_key(&self, key: &str, ip: IpAddr) -> Result<Option<ApiKeyModel>, AppError> {
+        let now = Instant::now();
+        let hash = hash_api_key(key);
+        if let Some((model, fetched)) = self.keys.lock().unwrap_or_else(|e| e.into_inner()).get(&hash) {
+            if now.duration_since(*fetched) < KEY_CACHE_TTL {
+                return Ok(Some(model.clone()));
+            }
+        }
+
+        self.buckets.check(&format!("lookup:{ip}"), KEY_LOOKUP_RATE_LIMIT, now).map_err(AppError::TooManyRequests)?;
+
+        let Some(model) = get_active_api_key(&self.db, key).await? else {
+            return Ok(None);
+        };
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        if keys.len() >= MAX_BUCKETS {
+            keys.retain(|_, (_, fetched)| now.duration_since(*fetched) < KEY_CACHE_TTL);
+        }
+        keys.insert(hash, (model.clone(), now));
+        Ok(Some(model))
+    }
+
+    fn client_ip(&self, request: &Request) -> IpAddr {
+        // Entries before the last one come from the client and can be forged
+        let forwarded = self
+            .config
+            .trust_forwarded_for
+            .then(|| request.headers().get_all("x-forwarded-for").iter().next_back())
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+
+        forwarded
+            .or_else(|| request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip()))
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+    }
+}
+
+/// Resolve the [`Caller`] of the request and apply the server-wide limits. An unknown or
+/// revoked API key is rejected, and each client IP can only try a few uncached keys a minute.
+pub async fn authenticate(
+    State(auth): State<AuthState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let key = request.headers().get(API_KEY_HEADER).map(|value| value.to_str().unwrap_or_default().to_string());
+
+    let (caller, limit) = match key {
+        Some(key) => {
+            let model = auth
+                .lookup_key(&key, auth.client_ip(&request))
+                .await?
+                .ok_or_else(|| AppError::Unauthorized("Invalid API key".to_string()))?;
+            // Keys are created with a positive limit, others fall back to the default one
+            let limit = model
+                .requests_per_minute
+                .filter(|n| *n > 0)
+                .map(|n| RateLimit::per_minute(n as u32))
+                .or(auth.config.key_rate_limit);
+            (Caller::ApiKey { id: model.id, name: model.name }, limit)
+        }
+        None => (Caller::Anonymous(auth.client_ip(&request)), auth.config.ip_rate_limit),
+    };
+
+    if let Some(limit) = limit {
+        auth.buckets.check(&caller.bucket_key(), limit, Instant::now()).map_err(AppError::TooManyRequests)?;
+    }
+
+    request.extensions_mut().insert(caller);
+    Ok(next.run(request).await)
+}
+
+/// Access rules of a group of routes, applied with [`RoutePolicy::apply`] where the routes are
+/// registered:
+///
+/// ```ignore
+/// RoutePolicy::default().require_api_key().rate_limit(RateLimit::per_minute(10)).apply(
+///     OpenApiRouter::new().route("/transactions/v1/submit", post(submit_swap_handler)),
+/// )
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RoutePolicy {
+    require_api_key: bool,
+    rate_limit: Option<RateLimit>,
+}
+
+impl RoutePolicy {
+    /// Reject requests without a valid API key
+    pub fn require_api_key(mut self) -> Self {
+        self.require_api_key = true;
+        self
+    }
+
+    /// Limit each caller of these routes, on top of the server-wide limits
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    pub fn apply<S>(self, router: OpenApiRouter<S>) -> OpenApiRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let buckets = TokenBuckets::default();
+        router.layer(middleware::from_fn(move |request: Request, next: Next| {
+            let policy = self.clone();
+            let buckets = buckets.clone();
+            async move { policy.enforce(&buckets, request, next).await }
+        }))
+    }
+
+    async fn enforce(&self, buckets: &TokenBuckets, request: Request, next: Next) -> Result<Response, AppError> {
+        let caller = request.extensions().get::<Caller>().cloned();
+
+        if self.require_api_key && !matches!(caller, Some(Caller::ApiKey { .. })) {
+            return Err(AppError::Unauthorized(format!("An API key is required in the {API_KEY_HEADER} header")));
+        }
+        if let (Some(limit), Some(caller)) = (self.rate_limit, &caller) {
+            buckets.check(&caller.bucket_key(), limit, Instant::now()).map_err(AppError::TooManyRequests)?;
+        }
+        Ok(next.run(request).await)
+    }
+}
+
+/// Document the optional API key header in the OpenAPI document.
+pub fn document_security(api: &mut OpenApi) {
+    let components = api.components.get_or_insert_with(Default::default);
+    components.add_security_scheme(
+        API_KEY_SCHEME,
+        SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+            API_KEY_HEADER,
+            "Optional on most routes, raises the rate limits. Required by routes that say so.",
+        ))),
+    );
+    api.security =
+        Some(vec![SecurityRequirement::default(), SecurityRequirement::new(API_KEY_SCHEME, Vec::<String>::new())]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_refills() {
+        let buckets = TokenBuckets::default();
+        let limit = RateLimit::per_second(2);
+        let start = Instant::now();
+
+        assert!(buckets.check("ip:1", limit, start).is_ok());
+        assert!(buckets.check("ip:1", limit, start).is_ok());
+        assert_eq!(buckets.check("ip:1", limit, start), Err(1));
+
+        // Other callers have their own bucket
+        assert!(buckets.check("ip:2", limit, start).is_ok());
+
+        // Half a second refills one token
+        let later = start + Duration::from_millis(500);
+        assert!(buckets.check("ip:1", limit, later).is_ok());
+        assert!(buckets.check("ip:1", limit, later).is_err());
+    }
+
+    /// Never connected, the pool only has to exist
+    fn unused_db() -> Arc<DbPool> {
+        Arc::new(diesel_async::pooled_connection::bb8::Pool::builder().build_unchecked(
+            diesel_async::pooled_connection::AsyncDieselConnectionManager::new("postgres://localhost/unused"),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_invalid_keys_are_throttled() {
+        let auth = AuthState::new(unused_db(), AuthConfig::default());
+        let ip = IpAddr::from([203, 0, 113, 7]);
+        let now = Instant::now();
+
+        // Use up the lookups of the IP, as a client spraying random keys would
+        for _ in 0..KEY_LOOKUP_RATE_LIMIT.requests {
+            auth.buckets.check(&format!("lookup:{ip}"), KEY_LOOKUP_RATE_LIMIT, now).unwrap();
+        }
+        let result = auth.lookup_key("random-key", ip).await;
+        assert!(matches!(result, Err(AppError::TooManyRequests(_))));
+
+        // A cached valid key is still accepted from the same IP
+        let model = ApiKeyModel {
+            id: 1,
+            name: "valid".to_string(),
+            key_hash: hash_api_key("valid-key"),
+            requests_per_minute: None,
+            created_at: chrono::Utc::now().naive_utc(),
+            revoked_at: None,
+        };
+        auth.keys.lock().unwrap().insert(model.key_hash.clone(), (model, now));
+        assert_eq!(auth.lookup_key("valid-key", ip).await.unwrap().map(|m| m.id), Some(1));
+
+        // Other IPs keep their own lookups
+        let other = auth.buckets.check("lookup:198.51.100.1", KEY_LOOKUP_RATE_LIMIT, now);
+        assert!(other.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_client_ip_from_last_forwarded_entry() {
+        let db = unused_db();
+        let request = |forwarded: &[&str]| {
+            let mut builder = Request::builder().uri("/");
+            for value in forwarded {
+                builder = builder.header("x-forwarded-for", *value);
+            }
+            let mut request = builder.body(axum::body::Body::empty()).unwrap();
+            request.extensions_mut().insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 443))));
+            request
+        };
+
+        let trusted = AuthState::new(db.clone(), AuthConfig { trust_forwarded_for: true, ..Default::default() });
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        assert_eq!(trusted.client_ip(&request(&["1.2.3.4, 203.0.113.7"])), client);
+        assert_eq!(trusted.client_ip(&request(&["1.2.3.4", "203.0.113.7"])), client);
+        assert_eq!(trusted.client_ip(&request(&[])), IpAddr::from([10, 0, 0, 1]));
+
+        let untrusted = AuthState::new(db, AuthConfig::default());
+        assert_eq!(untrusted.client_ip(&request(&["203.0.113.7"])), IpAddr::from([10, 0, 0, 1]));
+    }
+}
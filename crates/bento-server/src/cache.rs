@@ -0,0 +1,278 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, Bytes};
+use axum::extract::Request;
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use bento_types::notifications::IndexedNotification;
+use futures::StreamExt;
+use tokio::sync::broadcast::{self, error::RecvError};
+use utoipa_axum::router::OpenApiRouter;
+
+/// Responses kept before expired ones are evicted.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Largest response body that is cached, larger ones are passed through.
+const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(Clone)]
+struct CachedResponse {
+    body: Bytes,
+    content_type: Option<HeaderValue>,
+    etag: HeaderValue,
+    expires: Instant,
+    topics: Arc<[String]>,
+}
+
+/// In-memory cache of GET responses, shared through [`crate::AppState`]. Routes opt in with
+/// [`CachePolicy::apply`]; entries expire after their TTL or when a notification of one of their
+/// topics arrives, e.g. a custom notification published when a periodic job finishes a tick.
+#[derive(Clone, Default)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<HashMap<String, CachedResponse>>>,
+}
+
+impl ResponseCache {
+    fn get(&self, key: &str, now: Instant) -> Option<CachedResponse> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(key).filter(|entry| entry.expires > now).cloned()
+    }
+
+    fn insert(&self, key: String, entry: CachedResponse, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, entry| entry.expires > now);
+        }
+        entries.insert(key, entry);
+    }
+
+    /// Drop the responses cached under `topic`.
+    pub fn invalidate(&self, topic: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| !entry.topics.iter().any(|t| t == topic));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Invalidate the topics of the custom notifications received on `notifications`.
+    pub fn spawn_invalidator(
+        &self,
+        mut notifications: broadcast::Receiver<IndexedNotification>,
+    ) -> tokio::task::JoinHandle<()> {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match notifications.recv().await {
+                    Ok(IndexedNotification::Custom { topic, .. }) => cache.invalidate(&topic),
+                    Ok(_) => {}
+                    // Missed notifications may have invalidated anything
+                    Err(RecvError::Lagged(_)) => cache.clear(),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+fn etag_of(body: &[u8]) -> HeaderValue {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    HeaderValue::from_str(&format!("\"{:016x}\"", hasher.finish())).expect("hex is a valid header value")
+}
+
+/// Whether an `If-None-Match` header value matches `etag`.
+fn etag_matches(if_none_match: Option<&HeaderValue>, etag: &HeaderValue) -> bool {
+    if_none_match
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == "*" || tag.trim().as_bytes() == etag.as_bytes()))
+}
+
+fn not_modified(etag: HeaderValue) -> Response {
+    (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+}
+
+/// Caching of a group of routes, applied where the routes are registered:
+///
+/// ```ignore
+/// CachePolicy::new(Duration::from_secs(300))
+///     .invalidated_by("jobs.market-state-snapshots")
+///     .apply(OpenApiRouter::new().route("/lending/v1/stats", get(get_lending_stats)))
+/// ```
+///
+/// Successful GET responses are cached per path and query and carry an `ETag`; a request with a
+/// matching `If-None-Match` gets a `304 Not Modified`.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    ttl: Duration,
+    topics: Vec<String>,
+}
+
+impl CachePolicy {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, topics: Vec::new() }
+    }
+
+    /// Drop the cached responses when a notification of `topic` arrives
+    pub fn invalidated_by(mut self, topic: impl Into<String>) -> Self {
+        self.topics.push(topic.into());
+        self
+    }
+
+    pub fn apply<S>(self, router: OpenApiRouter<S>) -> OpenApiRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let topics: Arc<[String]> = self.topics.into();
+        let ttl = self.ttl;
+        router.layer(middleware::from_fn(move |request: Request, next: Next| {
+            let topics = topics.clone();
+            async move { cached(ttl, topics, request, next).await }
+        }))
+    }
+}
+
+async fn cached(ttl: Duration, topics: Arc<[String]>, request: Request, next: Next) -> Response {
+    let Some(cache) = request.extensions().get::<ResponseCache>().cloned() else {
+        return next.run(request).await;
+    };
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    let key = request.uri().path_and_query().map_or_else(|| request.uri().path().to_string(), |p| p.to_string());
+    let now = Instant::now();
+    if let Some(entry) = cache.get(&key, now) {
+        if etag_matches(request.headers().get(header::IF_NONE_MATCH), &entry.etag) {
+            return not_modified(entry.etag);
+        }
+        return cached_response(entry);
+    }
+
+    let conditional = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let content_length = response.headers().get(header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()?.parse().ok());
+    if content_length.is_some_and(|length: usize| length > MAX_BODY_SIZE) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let mut stream = body.into_data_stream();
+    let mut chunks = Vec::new();
+    let mut size = 0;
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) if size + chunk.len() <= MAX_BODY_SIZE => {
+                size += chunk.len();
+                chunks.push(chunk);
+            }
+            // Too large or failed, sent on as is without caching
+            chunk => {
+                let buffered = futures::stream::iter(chunks.into_iter().map(Ok).chain(std::iter::once(chunk)));
+                return Response::from_parts(parts, Body::from_stream(buffered.chain(stream)));
+            }
+        }
+    }
+    let body = Bytes::from(chunks.concat());
+
+    let entry = CachedResponse {
+        etag: etag_of(&body),
+        content_type: parts.headers.get(header::CONTENT_TYPE).cloned(),
+        body,
+        expires: now + ttl,
+        topics,
+    };
+    cache.insert(key, entry.clone(), now);
+
+    if etag_matches(conditional.as_ref(), &entry.etag) {
+        return not_modified(entry.etag);
+    }
+    let mut response = Response::from_parts(parts, Body::from(entry.body));
+    response.headers_mut().insert(header::ETAG, entry.etag);
+    response
+}
+
+fn cached_response(entry: CachedResponse) -> Response {
+    let mut response = Response::new(Body::from(entry.body));
+    if let Some(content_type) = entry.content_type {
+        response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    }
+    response.headers_mut().insert(header::ETAG, entry.etag);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(topics: &[&str], expires: Instant) -> CachedResponse {
+        CachedResponse {
+            body: Bytes::from_static(b"{}"),
+            content_type: None,
+            etag: etag_of(b"{}"),
+            expires,
+            topics: topics.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_expiry_and_invalidation() {
+        let cache = ResponseCache::default();
+        let now = Instant::now();
+        let later = now + Duration::from_secs(60);
+
+        cache.insert("/stats".to_string(), entry(&["jobs.stats"], later), now);
+        cache.insert("/series".to_string(), entry(&["jobs.series"], later), now);
+        cache.insert("/expired".to_string(), entry(&[], now), now);
+
+        assert!(cache.get("/stats", now).is_some());
+        assert!(cache.get("/expired", now).is_none());
+
+        cache.invalidate("jobs.stats");
+        assert!(cache.get("/stats", now).is_none());
+        assert!(cache.get("/series", now).is_some());
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = etag_of(b"body");
+        let header = HeaderValue::from_str(&format!("\"other\", {}", etag.to_str().unwrap())).unwrap();
+        assert!(etag_matches(Some(&header), &etag));
+        assert!(etag_matches(Some(&HeaderValue::from_static("*")), &etag));
+        assert!(!etag_matches(Some(&HeaderValue::from_static("\"other\"")), &etag));
+        assert!(!etag_matches(None, &etag));
+    }
+
+    #[tokio::test]
+    async fn test_large_response_passed_through() {
+        use axum::routing::get;
+        use tower::ServiceExt;
+
+        let chunks = || {
+            let chunk = Bytes::from(vec![b'x'; MAX_BODY_SIZE / 2]);
+            Body::from_stream(futures::stream::iter((0..3).map(move |_| Ok::<_, std::io::Error>(chunk.clone()))))
+        };
+        let router = CachePolicy::new(Duration::from_secs(60))
+            .apply(OpenApiRouter::new().route("/large", get(move || async move { chunks() })))
+            .split_for_parts()
+            .0
+            .layer(axum::Extension(ResponseCache::default()));
+
+        let request = Request::builder().uri("/large").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::ETAG).is_none());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body.len(), MAX_BODY_SIZE / 2 * 3);
+    }
+}
@@ -1,2 +1,2 @@
 // Re-export error types from bento-types to avoid duplication
-pub use bento_types::errors::api::{AppError, RepositoryError};
+pub use bento_types::errors::api::{AppError, ErrorBody, RepositoryError};
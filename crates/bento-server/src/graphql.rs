@@ -0,0 +1,461 @@
+//! GraphQL endpoint over the core tables, enabled by the `graphql` feature.
+//!
+//! The core schema exposes blocks, transactions and events with their relations. Apps add their
+//! own object types by merging a query object with [`CoreQuery`]:
+//!
+//! ```ignore
+//! #[derive(MergedObject, Default)]
+//! struct Query(CoreQuery, LendingQuery);
+//!
+//! let router = custom_router.merge(GraphQlModule::new(Query::default()).router());
+//! ```
+//!
+//! Resolvers read the [`AppState`] from the context with `ctx.data::<AppState>()`. Relations
+//! are resolved through the request's [`DataLoader<CoreLoader>`], so a list of blocks loads the
+//! transactions of all of them in one query.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Json, Object, ObjectType, Result, Schema};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::extract::State;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::Extension;
+use bento_types::repository::{
+    get_block_by_hash, get_blocks, get_blocks_by_hashes, get_events, get_events_by_txs, get_tx_by_hash, get_txs,
+    get_txs_by_blocks, get_txs_by_hashes,
+};
+use bento_types::{BlockModel, DbPool, EventModel, Order, TransactionModel};
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::{AppState, Pagination};
+
+/// Path the GraphQL endpoint and its GraphiQL page are served on.
+pub const GRAPHQL_PATH: &str = "/v1/graphql";
+
+/// Limits applied to every query, rejecting it before any resolver runs.
+#[derive(Debug, Clone, Copy)]
+pub struct GraphQlLimits {
+    /// Maximum nesting of selection sets, e.g. `blocks { transactions { events } }` is 3 deep
+    pub max_depth: usize,
+    /// Maximum complexity, each field counting 1 and list fields their `limit` times their children
+    pub max_complexity: usize,
+}
+
+impl Default for GraphQlLimits {
+    fn default() -> Self {
+        Self { max_depth: 8, max_complexity: 5_000 }
+    }
+}
+
+fn pagination(limit: Option<i64>, offset: Option<i64>) -> Pagination {
+    Pagination { offset: offset.unwrap_or(0), limit: limit.unwrap_or(0) }
+}
+
+/// Complexity of a paginated list field, counted like [`Pagination::get_limit`] clamps it.
+fn list_complexity(child_complexity: usize, limit: Option<i64>) -> usize {
+    let limit = pagination(limit, None).get_limit() as usize;
+    limit.saturating_mul(child_complexity).saturating_add(1)
+}
+
+pub struct Block(pub BlockModel);
+
+#[Object]
+impl Block {
+    async fn hash(&self) -> &str {
+        &self.0.hash
+    }
+
+    /// Block timestamp in milliseconds
+    async fn timestamp(&self) -> i64 {
+        self.0.timestamp.and_utc().timestamp_millis()
+    }
+
+    async fn chain_from(&self) -> i64 {
+        self.0.chain_from
+    }
+
+    async fn chain_to(&self) -> i64 {
+        self.0.chain_to
+    }
+
+    async fn height(&self) -> i64 {
+        self.0.height
+    }
+
+    async fn deps(&self) -> Vec<String> {
+        self.0.deps.iter().flatten().cloned().collect()
+    }
+
+    async fn tx_number(&self) -> i64 {
+        self.0.tx_number
+    }
+
+    async fn main_chain(&self) -> bool {
+        self.0.main_chain
+    }
+
+    async fn ghost_uncles(&self) -> Json<serde_json::Value> {
+        Json(self.0.ghost_uncles.clone())
+    }
+
+    /// Transactions of the block, ordered by hash
+    #[graphql(complexity = "list_complexity(child_complexity, limit)")]
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Transaction>> {
+        let loader = ctx.data::<DataLoader<CoreLoader>>()?;
+        let key = PageKey::new(&self.0.hash, pagination(limit, offset));
+        let txs = loader.load_one(BlockTransactions(key)).await?.unwrap_or_default();
+        Ok(txs.into_iter().map(Transaction).collect())
+    }
+}
+
+pub struct Transaction(pub TransactionModel);
+
+#[Object]
+impl Transaction {
+    async fn tx_hash(&self) -> &str {
+        &self.0.tx_hash
+    }
+
+    async fn block_hash(&self) -> Option<&str> {
+        self.0.block_hash.as_deref()
+    }
+
+    async fn script_execution_ok(&self) -> bool {
+        self.0.script_execution_ok
+    }
+
+    async fn unsigned(&self) -> Json<serde_json::Value> {
+        Json(self.0.unsigned.clone())
+    }
+
+    async fn contract_inputs(&self) -> Json<serde_json::Value> {
+        Json(self.0.contract_inputs.clone())
+    }
+
+    async fn generated_outputs(&self) -> Json<serde_json::Value> {
+        Json(self.0.generated_outputs.clone())
+    }
+
+    /// Block the transaction was included in
+    async fn block(&self, ctx: &Context<'_>) -> Result<Option<Block>> {
+        let Some(block_hash) = &self.0.block_hash else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<CoreLoader>>()?;
+        Ok(loader.load_one(BlockByHash(block_hash.clone())).await?.map(Block))
+    }
+
+    /// Events emitted by the transaction, ordered by index
+    #[graphql(complexity = "list_complexity(child_complexity, limit)")]
+    async fn events(&self, ctx: &Context<'_>, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Event>> {
+        let loader = ctx.data::<DataLoader<CoreLoader>>()?;
+        let key = PageKey::new(&self.0.tx_hash, pagination(limit, offset));
+        let events = loader.load_one(TransactionEvents(key)).await?.unwrap_or_default();
+        Ok(events.into_iter().map(Event).collect())
+    }
+}
+
+pub struct Event(pub EventModel);
+
+#[Object]
+impl Event {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn tx_id(&self) -> &str {
+        &self.0.tx_id
+    }
+
+    async fn contract_address(&self) -> &str {
+        &self.0.contract_address
+    }
+
+    async fn event_index(&self) -> i32 {
+        self.0.event_index
+    }
+
+    async fn fields(&self) -> Json<serde_json::Value> {
+        Json(self.0.fields.clone())
+    }
+
+    /// Order in which the transaction emitted the event, unset for events indexed before it was recorded
+    async fn position(&self) -> Option<i32> {
+        self.0.position
+    }
+
+    /// Transaction that emitted the event
+    async fn transaction(&self, ctx: &Context<'_>) -> Result<Option<Transaction>> {
+        let loader = ctx.data::<DataLoader<CoreLoader>>()?;
+        Ok(loader.load_one(TransactionByHash(self.0.tx_id.clone())).await?.map(Transaction))
+    }
+}
+
+/// Parent of a paginated relation with the page requested for it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PageKey {
+    pub parent: String,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl PageKey {
+    fn new(parent: &str, pagination: Pagination) -> Self {
+        Self { parent: parent.to_string(), limit: pagination.get_limit(), offset: pagination.get_offset() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockByHash(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TransactionByHash(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockTransactions(pub PageKey);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TransactionEvents(pub PageKey);
+
+/// Loads the relations of the core objects in batches, one query per relation and page. Added
+/// to each request by [`GraphQlModule`] as a [`DataLoader`], which also caches the results for
+/// the request.
+pub struct CoreLoader {
+    db: Arc<DbPool>,
+}
+
+impl CoreLoader {
+    pub fn new(db: Arc<DbPool>) -> Self {
+        Self { db }
+    }
+}
+
+/// Parents of `keys` by the page requested for them.
+fn pages<'a>(keys: impl IntoIterator<Item = &'a PageKey>) -> HashMap<(i64, i64), Vec<String>> {
+    let mut pages: HashMap<(i64, i64), Vec<String>> = HashMap::new();
+    for key in keys {
+        pages.entry((key.limit, key.offset)).or_default().push(key.parent.clone());
+    }
+    pages
+}
+
+/// `rows` grouped under the key of their parent, with an empty list for parents without rows.
+fn group_by_parent<K: Hash + Eq, T>(
+    parents: &[String],
+    rows: Vec<T>,
+    parent_of: impl Fn(&T) -> Option<&str>,
+    key: impl Fn(String) -> K,
+) -> HashMap<K, Vec<T>> {
+    let mut grouped: HashMap<String, Vec<T>> = parents.iter().map(|parent| (parent.clone(), Vec::new())).collect();
+    for row in rows {
+        if let Some(rows) = parent_of(&row).and_then(|parent| grouped.get_mut(parent)) {
+            rows.push(row);
+        }
+    }
+    grouped.into_iter().map(|(parent, rows)| (key(parent), rows)).collect()
+}
+
+impl Loader<BlockByHash> for CoreLoader {
+    type Value = BlockModel;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[BlockByHash]) -> Result<HashMap<BlockByHash, BlockModel>, Self::Error> {
+        let hashes: Vec<String> = keys.iter().map(|key| key.0.clone()).collect();
+        let blocks = get_blocks_by_hashes(self.db.clone(), &hashes).await?;
+        Ok(blocks.into_iter().map(|block| (BlockByHash(block.hash.clone()), block)).collect())
+    }
+}
+
+impl Loader<TransactionByHash> for CoreLoader {
+    type Value = TransactionModel;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[TransactionByHash],
+    ) -> Result<HashMap<TransactionByHash, TransactionModel>, Self::Error> {
+        let hashes: Vec<String> = keys.iter().map(|key| key.0.clone()).collect();
+        let txs = get_txs_by_hashes(self.db.clone(), &hashes).await?;
+        Ok(txs.into_iter().map(|tx| (TransactionByHash(tx.tx_hash.clone()), tx)).collect())
+    }
+}
+
+impl Loader<BlockTransactions> for CoreLoader {
+    type Value = Vec<TransactionModel>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[BlockTransactions],
+    ) -> Result<HashMap<BlockTransactions, Vec<TransactionModel>>, Self::Error> {
+        let mut loaded = HashMap::new();
+        for ((limit, offset), blocks) in pages(keys.iter().map(|key| &key.0)) {
+            let txs = get_txs_by_blocks(self.db.clone(), &blocks, limit, offset).await?;
+            loaded.extend(group_by_parent(
+                &blocks,
+                txs,
+                |tx| tx.block_hash.as_deref(),
+                |parent| BlockTransactions(PageKey { parent, limit, offset }),
+            ));
+        }
+        Ok(loaded)
+    }
+}
+
+impl Loader<TransactionEvents> for CoreLoader {
+    type Value = Vec<EventModel>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[TransactionEvents],
+    ) -> Result<HashMap<TransactionEvents, Vec<EventModel>>, Self::Error> {
+        let mut loaded = HashMap::new();
+        for ((limit, offset), txs) in pages(keys.iter().map(|key| &key.0)) {
+            let events = get_events_by_txs(self.db.clone(), &txs, limit, offset).await?;
+            loaded.extend(group_by_parent(
+                &txs,
+                events,
+                |event| Some(event.tx_id.as_str()),
+                |parent| TransactionEvents(PageKey { parent, limit, offset }),
+            ));
+        }
+        Ok(loaded)
+    }
+}
+
+/// Root queries over the core tables.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoreQuery;
+
+#[Object]
+impl CoreQuery {
+    /// Blocks ordered by timestamp, newest first unless `ascending` is set
+    #[graphql(complexity = "list_complexity(child_complexity, limit)")]
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        ascending: Option<bool>,
+    ) -> Result<Vec<Block>> {
+        let state = ctx.data::<AppState>()?;
+        let pagination = pagination(limit, offset);
+        let order = if ascending.unwrap_or(false) { Order::Asc } else { Order::Desc };
+        let blocks = get_blocks(state.db.clone(), pagination.get_limit(), pagination.get_offset(), Some(order)).await?;
+        Ok(blocks.into_iter().map(Block).collect())
+    }
+
+    async fn block(&self, ctx: &Context<'_>, hash: String) -> Result<Option<Block>> {
+        let state = ctx.data::<AppState>()?;
+        Ok(get_block_by_hash(state.db.clone(), &hash).await?.map(Block))
+    }
+
+    #[graphql(complexity = "list_complexity(child_complexity, limit)")]
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Transaction>> {
+        let state = ctx.data::<AppState>()?;
+        let pagination = pagination(limit, offset);
+        let txs = get_txs(state.db.clone(), pagination.get_limit(), pagination.get_offset()).await?;
+        Ok(txs.into_iter().map(Transaction).collect())
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>, hash: String) -> Result<Option<Transaction>> {
+        let state = ctx.data::<AppState>()?;
+        Ok(get_tx_by_hash(state.db.clone(), &hash).await?.map(Transaction))
+    }
+
+    #[graphql(complexity = "list_complexity(child_complexity, limit)")]
+    async fn events(&self, ctx: &Context<'_>, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Event>> {
+        let state = ctx.data::<AppState>()?;
+        let pagination = pagination(limit, offset);
+        let events = get_events(state.db.clone(), pagination.get_limit(), pagination.get_offset()).await?;
+        Ok(events.into_iter().map(Event).collect())
+    }
+}
+
+/// Serves a GraphQL schema built from `query` on [`GRAPHQL_PATH`].
+pub struct GraphQlModule<Q> {
+    query: Q,
+    limits: GraphQlLimits,
+}
+
+impl GraphQlModule<CoreQuery> {
+    /// The core schema only
+    pub fn register() -> OpenApiRouter<AppState> {
+        GraphQlModule::new(CoreQuery).router()
+    }
+}
+
+impl<Q: ObjectType + 'static> GraphQlModule<Q> {
+    pub fn new(query: Q) -> Self {
+        Self { query, limits: GraphQlLimits::default() }
+    }
+
+    pub fn limits(mut self, limits: GraphQlLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn schema(self) -> Schema<Q, EmptyMutation, EmptySubscription> {
+        Schema::build(self.query, EmptyMutation, EmptySubscription)
+            .limit_depth(self.limits.max_depth)
+            .limit_complexity(self.limits.max_complexity)
+            .finish()
+    }
+
+    /// Router to merge next to the custom `OpenApiRouter`. Queries are sent with `POST`, `GET`
+    /// serves GraphiQL.
+    pub fn router(self) -> OpenApiRouter<AppState> {
+        OpenApiRouter::new()
+            .route(GRAPHQL_PATH, get(graphiql).post(graphql_handler::<Q>))
+            .layer(Extension(self.schema()))
+    }
+}
+
+async fn graphql_handler<Q: ObjectType + 'static>(
+    State(state): State<AppState>,
+    Extension(schema): Extension<Schema<Q, EmptyMutation, EmptySubscription>>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let loader = DataLoader::new(CoreLoader::new(state.db.clone()), tokio::spawn);
+    schema.execute(request.into_inner().data(state).data(loader)).await.into()
+}
+
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint(GRAPHQL_PATH).finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_reject_deep_and_complex_queries() {
+        let schema = GraphQlModule::new(CoreQuery).limits(GraphQlLimits { max_depth: 3, max_complexity: 500 }).schema();
+
+        // Limits are checked before resolving, so no database is needed
+        let deep = "{ blocks(limit: 1) { transactions(limit: 1) { events(limit: 1) { transaction { txHash } } } } }";
+        let response = schema.execute(deep).await;
+        assert!(response.errors.iter().any(|e| e.message.contains("nested too deep")));
+
+        let complex = "{ blocks(limit: 100) { transactions { txHash } } }";
+        let response = schema.execute(complex).await;
+        assert!(response.errors.iter().any(|e| e.message.contains("too complex")));
+    }
+}
@@ -0,0 +1,88 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use bento_types::repository::{get_address_balance, get_address_transactions, get_address_utxos};
+
+use crate::error::AppError;
+use crate::handler::dto::{AddressBalanceDto, AddressQuery, AddressUtxoDto, TransactionDto};
+use crate::AppState;
+use crate::Pagination;
+use axum::response::IntoResponse;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+pub struct AddressApiModule;
+
+impl AddressApiModule {
+    pub fn register() -> OpenApiRouter<crate::AppState> {
+        OpenApiRouter::new()
+            .routes(routes!(get_address_transactions_handler))
+            .routes(routes!(get_address_balance_handler))
+            .routes(routes!(get_address_utxos_handler))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{address}/transactions",
+    tag = "Addresses",
+    params(
+        ("address" = String, Path, description = "Alephium address"),
+        AddressQuery
+    ),
+    responses(
+        (status = 200, description = "Transactions touching the address, newest first", body = Vec<TransactionDto>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_address_transactions_handler(
+    Path(address): Path<String>,
+    pagination: Query<Pagination>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let db = state.db;
+    let tx_models = get_address_transactions(db, &address, pagination.get_limit(), pagination.get_offset()).await?;
+    let txs: Vec<TransactionDto> = tx_models.into_iter().map(TransactionDto::from).collect();
+    Ok(Json(txs))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{address}/balance",
+    tag = "Addresses",
+    params(("address" = String, Path, description = "Alephium address")),
+    responses(
+        (status = 200, description = "ALPH and token balances computed from unspent outputs", body = AddressBalanceDto),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_address_balance_handler(
+    Path(address): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let db = state.db;
+    let balance = get_address_balance(db, &address).await?;
+    Ok(Json(AddressBalanceDto::new(address, balance)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{address}/utxos",
+    tag = "Addresses",
+    params(
+        ("address" = String, Path, description = "Alephium address"),
+        AddressQuery
+    ),
+    responses(
+        (status = 200, description = "Unspent outputs of the address, newest first", body = Vec<AddressUtxoDto>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_address_utxos_handler(
+    Path(address): Path<String>,
+    pagination: Query<Pagination>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let db = state.db;
+    let utxos = get_address_utxos(db, &address, pagination.get_limit(), pagination.get_offset()).await?;
+    let utxos: Vec<AddressUtxoDto> = utxos.into_iter().map(AddressUtxoDto::from).collect();
+    Ok(Json(utxos))
+}
@@ -1,6 +1,9 @@
-use axum::extract::{Query, State};
+use axum::extract::{Path, Query, State};
 use axum::Json;
-use bento_types::repository::{get_block_by_hash, get_block_by_height, get_block_transactions, get_blocks};
+use bento_types::repository::{
+    get_block_by_hash, get_block_children, get_block_transactions, get_blocks, get_blocks_by_height,
+};
+use bento_types::DEFAULT_GROUP_NUM;
 
 use crate::error::AppError;
 use crate::handler::dto::{BlockByHeightQuery, TransactionDto};
@@ -18,7 +21,9 @@ impl BlockApiModule {
             .routes(routes!(get_blocks_handler))
             .routes(routes!(get_block_by_hash_handler))
             .routes(routes!(get_block_by_height_handler))
+            .routes(routes!(get_blocks_by_height_handler))
             .routes(routes!(get_block_transactions_handler))
+            .routes(routes!(get_block_children_handler))
     }
 }
 
@@ -65,13 +70,29 @@ pub async fn get_block_by_hash_handler(
     Ok(Json(block_model))
 }
 
+/// The chain index of the query, validated, or `None` to look at every chain.
+fn chain_index(query: &BlockByHeightQuery) -> Result<Option<(i64, i64)>, AppError> {
+    match (query.from_group, query.to_group) {
+        (Some(from_group), Some(to_group)) => {
+            let groups = 0..DEFAULT_GROUP_NUM;
+            if !groups.contains(&from_group) || !groups.contains(&to_group) {
+                return Err(AppError::BadRequest(format!("Invalid chain index {from_group} -> {to_group}")));
+            }
+            Ok(Some((from_group, to_group)))
+        }
+        (None, None) => Ok(None),
+        _ => Err(AppError::BadRequest("from_group and to_group must be given together".to_string())),
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/height",
     tag = "Blocks",
     params(BlockByHeightQuery),
     responses(
-        (status = 200, description = "Block retrieved successfully", body = BlockDto),
+        (status = 200, description = "Block retrieved successfully, the main chain block of the chain index when one is given", body = BlockDto),
+        (status = 400, description = "Invalid chain index"),
         (status = 404, description = "Block not found"),
         (status = 500, description = "Internal server error")
     )
@@ -81,9 +102,10 @@ pub async fn get_block_by_height_handler(
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
     let db = state.db;
-    //TODO: Pass the from_group and to_group parameters to the repository function
-    let block_model = get_block_by_height(db, query.height).await?;
+    let chain = chain_index(&query)?;
 
+    // Main chain blocks come first, so this is the main chain block of the first chain
+    let block_model = get_blocks_by_height(db, query.height, chain).await?.into_iter().next();
     if block_model.is_none() {
         return Err(AppError::NotFound("Block not found".to_string()));
     }
@@ -91,6 +113,34 @@ pub async fn get_block_by_height_handler(
     Ok(Json(block_model))
 }
 
+#[utoipa::path(
+    get,
+    path = "/height/all",
+    tag = "Blocks",
+    params(BlockByHeightQuery),
+    responses(
+        (status = 200, description = "All blocks at the height, including uncles and forks", body = Vec<BlockDto>),
+        (status = 400, description = "Invalid chain index"),
+        (status = 404, description = "Block not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_blocks_by_height_handler(
+    Query(query): Query<BlockByHeightQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let db = state.db;
+    let chain = chain_index(&query)?;
+
+    let block_models = get_blocks_by_height(db, query.height, chain).await?;
+    if block_models.is_empty() {
+        return Err(AppError::NotFound("Block not found".to_string()));
+    }
+
+    let blocks: Vec<BlockDto> = block_models.into_iter().map(BlockDto::from).collect();
+    Ok(Json(blocks))
+}
+
 #[utoipa::path(
     get,
     path = "/transactions",
@@ -111,3 +161,121 @@ pub async fn get_block_transactions_handler(
 
     Ok(Json(transaction_models))
 }
+
+#[utoipa::path(
+    get,
+    path = "/{hash}/children",
+    tag = "Blocks",
+    params(("hash" = String, Path, description = "Hash of the parent block")),
+    responses(
+        (status = 200, description = "Blocks that reference the block in their deps", body = Vec<BlockDto>),
+        (status = 404, description = "Block not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_block_children_handler(
+    Path(hash): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let db = state.db;
+    if get_block_by_hash(db.clone(), &hash).await?.is_none() {
+        return Err(AppError::NotFound("Block not found".to_string()));
+    }
+
+    let block_models = get_block_children(db, &hash).await?;
+    let blocks: Vec<BlockDto> = block_models.into_iter().map(BlockDto::from).collect();
+    Ok(Json(blocks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use bento_types::repository::insert_blocks_to_db;
+    use bento_types::repository::test_utils::{cleanup, create_test_pool};
+    use bento_types::BlockModel;
+
+    const PARENT: &str = "test-block-handler-parent";
+    const CHILD: &str = "test-block-handler-child";
+    const HEIGHT: i64 = 987_000_000;
+
+    async fn test_state() -> AppState {
+        AppState {
+            db: create_test_pool().await,
+            node_client: std::sync::Arc::new(bento_core::Client::new(Default::default())),
+            notifications: tokio::sync::broadcast::channel(1).0,
+            cache: Default::default(),
+            health: Default::default(),
+            exports: bento_types::export::ExportTables::core(),
+        }
+    }
+
+    async fn json_body(response: axum::response::Response) -> serde_json::Value {
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    fn block(hash: &str, height: i64, deps: &[&str]) -> BlockModel {
+        BlockModel {
+            hash: hash.to_string(),
+            timestamp: chrono::Utc::now().naive_utc(),
+            chain_from: 0,
+            chain_to: 0,
+            height,
+            deps: deps.iter().map(|d| Some(d.to_string())).collect(),
+            nonce: String::new(),
+            version: String::new(),
+            dep_state_hash: String::new(),
+            txs_hash: String::new(),
+            tx_number: 0,
+            target: String::new(),
+            main_chain: true,
+            ghost_uncles: serde_json::json!([]),
+        }
+    }
+
+    fn query(from_group: Option<i64>, to_group: Option<i64>) -> BlockByHeightQuery {
+        BlockByHeightQuery { height: 1, from_group, to_group }
+    }
+
+    #[test]
+    fn test_chain_index() {
+        assert_eq!(chain_index(&query(None, None)).unwrap(), None);
+        assert_eq!(chain_index(&query(Some(0), Some(3))).unwrap(), Some((0, 3)));
+
+        assert!(matches!(chain_index(&query(Some(0), None)), Err(AppError::BadRequest(_))));
+        assert!(matches!(chain_index(&query(None, Some(1))), Err(AppError::BadRequest(_))));
+        assert!(matches!(chain_index(&query(Some(DEFAULT_GROUP_NUM), Some(0))), Err(AppError::BadRequest(_))));
+        assert!(matches!(chain_index(&query(Some(0), Some(-1))), Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_block_height_and_children_handlers() {
+        let state = test_state().await;
+        cleanup(&state.db, &[], &[PARENT, CHILD]).await;
+        let blocks = vec![block(PARENT, HEIGHT, &[]), block(CHILD, HEIGHT + 1, &[PARENT])];
+        insert_blocks_to_db(&mut state.db.get().await.unwrap(), blocks).await.unwrap();
+
+        let response =
+            get_block_children_handler(Path(PARENT.to_string()), State(state.clone())).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["hash"], CHILD);
+
+        // `/height` keeps answering a single block, `/height/all` lists them
+        let query = || Query(BlockByHeightQuery { height: HEIGHT + 1, from_group: Some(0), to_group: Some(0) });
+        let response = get_block_by_height_handler(query(), State(state.clone())).await.unwrap().into_response();
+        let body = json_body(response).await;
+        assert_eq!(body["hash"], CHILD);
+        let response = get_blocks_by_height_handler(query(), State(state.clone())).await.unwrap().into_response();
+        let body = json_body(response).await;
+        assert_eq!(body[0]["hash"], CHILD);
+
+        let missing = get_block_children_handler(Path("test-block-handler-missing".to_string()), State(state.clone()));
+        assert!(matches!(missing.await, Err(AppError::NotFound(_))));
+
+        cleanup(&state.db, &[], &[PARENT, CHILD]).await;
+    }
+}
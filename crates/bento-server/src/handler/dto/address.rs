@@ -0,0 +1,84 @@
+use bento_types::{
+    repository::{AddressBalance, AddressTokenBalance},
+    AddressOutputModel,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::Pagination;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenBalanceDto {
+    pub token_id: String,
+    /// Raw token amount, without decimals applied
+    #[schema(example = "1000000000000000000")]
+    pub amount: String,
+}
+
+impl From<AddressTokenBalance> for TokenBalanceDto {
+    fn from(token: AddressTokenBalance) -> Self {
+        Self { token_id: token.token_id, amount: token.amount.to_string() }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddressBalanceDto {
+    pub address: String,
+    /// Unspent ALPH balance in attoALPH, including locked outputs
+    #[schema(example = "1000000000000000000")]
+    pub balance: String,
+    /// Part of `balance` held by outputs that are still time-locked
+    #[schema(example = "0")]
+    pub locked_balance: String,
+    pub utxo_count: i64,
+    pub tokens: Vec<TokenBalanceDto>,
+}
+
+impl AddressBalanceDto {
+    pub fn new(address: String, balance: AddressBalance) -> Self {
+        Self {
+            address,
+            balance: balance.balance.to_string(),
+            locked_balance: balance.locked_balance.to_string(),
+            utxo_count: balance.utxo_count,
+            tokens: balance.tokens.into_iter().map(TokenBalanceDto::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddressUtxoDto {
+    pub output_key: String,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub hint: i32,
+    #[schema(example = "1000000000000000000")]
+    pub atto_alph_amount: String,
+    pub tokens: serde_json::Value,
+    pub lock_time: i64,
+    #[schema(example = "2023-01-01T00:00:00")]
+    pub timestamp: String,
+}
+
+impl From<AddressOutputModel> for AddressUtxoDto {
+    fn from(model: AddressOutputModel) -> Self {
+        Self {
+            output_key: model.output_key,
+            tx_hash: model.tx_hash,
+            block_hash: model.block_hash,
+            hint: model.hint,
+            atto_alph_amount: model.atto_alph_amount.to_string(),
+            tokens: model.tokens,
+            lock_time: model.lock_time,
+            timestamp: model.timestamp.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema, Serialize)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct AddressQuery {
+    #[serde(flatten)]
+    #[param(inline, example = json!({"offset": 0, "limit": 10}))]
+    pub pagination: Pagination,
+}
@@ -60,6 +60,10 @@ pub struct BlocksQuery {
 pub struct BlockByHeightQuery {
     /// The block height to retrieve
     pub height: i64,
+    /// Source group of the chain index, must be given together with `to_group`
+    pub from_group: Option<i64>,
+    /// Target group of the chain index, must be given together with `from_group`
+    pub to_group: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, Default, IntoParams, ToSchema, Serialize)]
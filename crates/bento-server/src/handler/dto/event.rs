@@ -11,6 +11,8 @@ pub struct EventDto {
     pub contract_address: String,
     pub event_index: i32,
     pub fields: serde_json::Value,
+    /// Order in which the transaction emitted the event, unset for events indexed before it was recorded
+    pub position: Option<i32>,
 }
 
 impl From<EventModel> for EventDto {
@@ -21,6 +23,7 @@ impl From<EventModel> for EventDto {
             contract_address: model.contract_address,
             event_index: model.event_index,
             fields: model.fields,
+            position: model.position,
         }
     }
 }
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use bento_types::export::{ExportFormat, ExportRequest};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Window and format of an export. Any other query parameter filters on the text value of the
+/// column of the same name, e.g. `&address=...`.
+#[derive(Debug, Clone, Deserialize, IntoParams, ToSchema, Serialize)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct ExportQuery {
+    /// Start of the window in milliseconds, inclusive
+    pub from: i64,
+    /// End of the window in milliseconds, exclusive
+    pub to: i64,
+    /// `csv`, `ndjson` (default) or `parquet`
+    pub format: Option<ExportFormat>,
+}
+
+impl ExportQuery {
+    /// Split the query parameters into the window, the format and the column filters.
+    pub fn parse(mut params: HashMap<String, String>) -> Result<ExportRequest, String> {
+        let mut timestamp = |name: &str| -> Result<i64, String> {
+            let value = params.remove(name).ok_or_else(|| format!("Missing {name} parameter"))?;
+            value.parse().map_err(|_| format!("Invalid {name} timestamp {value}"))
+        };
+        let from_ts = timestamp("from")?;
+        let to_ts = timestamp("to")?;
+        let format = params.remove("format").map(|f| f.parse()).transpose()?.unwrap_or_default();
+
+        Ok(ExportRequest { from_ts, to_ts, filters: params.into_iter().collect(), format })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_export_query() {
+        let request =
+            ExportQuery::parse(params(&[("from", "1000"), ("to", "2000"), ("format", "csv"), ("address", "alice")]))
+                .unwrap();
+        assert_eq!((request.from_ts, request.to_ts), (1000, 2000));
+        assert_eq!(request.format, ExportFormat::Csv);
+        assert_eq!(request.filters.get("address").map(String::as_str), Some("alice"));
+
+        assert!(ExportQuery::parse(params(&[("from", "1000")])).is_err());
+        assert!(ExportQuery::parse(params(&[("from", "1000"), ("to", "2000"), ("format", "xml")])).is_err());
+    }
+}
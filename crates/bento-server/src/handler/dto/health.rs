@@ -0,0 +1,66 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ready,
+    NotReady,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LivenessDto {
+    pub status: &'static str,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DatabaseHealthDto {
+    pub ok: bool,
+    /// Time to get a pooled connection and run a query
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NodeHealthDto {
+    /// Whether the node answered for at least one chain
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChainHealthDto {
+    pub chain_from: i64,
+    pub chain_to: i64,
+    pub ok: bool,
+    pub node_height: Option<i64>,
+    pub indexed_height: Option<i64>,
+    /// Blocks the index is behind the node, never negative
+    pub lag_blocks: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProcessorHealthDto {
+    pub processor: String,
+    pub ok: bool,
+    /// Timestamp of the latest block stored by the processor in milliseconds
+    pub last_timestamp: i64,
+    pub checkpoint_age_ms: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthThresholdsDto {
+    pub max_chain_lag: i64,
+    pub max_checkpoint_age_ms: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessDto {
+    pub status: HealthStatus,
+    pub database: DatabaseHealthDto,
+    pub node: NodeHealthDto,
+    pub chains: Vec<ChainHealthDto>,
+    pub processors: Vec<ProcessorHealthDto>,
+    pub thresholds: HealthThresholdsDto,
+}
@@ -0,0 +1,78 @@
+use bento_types::repository::{MinerStats, MinerStatsBucket, StatsInterval};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{deserialize_optional_number_from_string, Pagination};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MinerStatsDto {
+    pub miner_address: String,
+    pub blocks_mined: i64,
+    pub uncles_mined: i64,
+    /// Block and uncle rewards in attoALPH
+    #[schema(example = "1000000000000000000")]
+    pub total_reward: String,
+}
+
+impl From<MinerStats> for MinerStatsDto {
+    fn from(stats: MinerStats) -> Self {
+        Self {
+            miner_address: stats.miner_address,
+            blocks_mined: stats.blocks_mined,
+            uncles_mined: stats.uncles_mined,
+            total_reward: stats.total_reward.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MinerStatsBucketDto {
+    /// Start of the bucket
+    #[schema(example = "2023-01-01T00:00:00")]
+    pub timestamp: String,
+    pub blocks_mined: i64,
+    pub uncles_mined: i64,
+    #[schema(example = "1000000000000000000")]
+    pub total_reward: String,
+}
+
+impl From<MinerStatsBucket> for MinerStatsBucketDto {
+    fn from(bucket: MinerStatsBucket) -> Self {
+        Self {
+            timestamp: bucket.bucket.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            blocks_mined: bucket.blocks_mined,
+            uncles_mined: bucket.uncles_mined,
+            total_reward: bucket.total_reward.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema, Serialize)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct MinerStatsQuery {
+    /// Start of the window in milliseconds, defaults to 24 hours before `to`
+    #[serde(default, deserialize_with = "deserialize_optional_number_from_string")]
+    pub from: Option<i64>,
+    /// End of the window in milliseconds (exclusive), defaults to now
+    #[serde(default, deserialize_with = "deserialize_optional_number_from_string")]
+    pub to: Option<i64>,
+
+    #[serde(flatten)]
+    #[param(inline, example = json!({"offset": 0, "limit": 10}))]
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema, Serialize)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct MinerHistoryQuery {
+    /// Start of the window in milliseconds, defaults to 30 days before `to`
+    #[serde(default, deserialize_with = "deserialize_optional_number_from_string")]
+    pub from: Option<i64>,
+    /// End of the window in milliseconds (exclusive), defaults to now
+    #[serde(default, deserialize_with = "deserialize_optional_number_from_string")]
+    pub to: Option<i64>,
+    /// Bucket size of the time series
+    #[serde(default)]
+    #[param(inline)]
+    pub interval: StatsInterval,
+}
@@ -1,9 +1,21 @@
+pub mod address;
 pub mod block;
 pub mod event;
+pub mod export;
+pub mod health;
+pub mod mining;
+pub mod stream;
+pub mod token;
 pub mod transaction;
 
+pub use address::*;
 pub use block::*;
 pub use event::*;
+pub use export::*;
+pub use health::*;
+pub use mining::*;
+pub use stream::*;
+pub use token::*;
 pub use transaction::*;
 
 pub struct Paginated<T> {
@@ -0,0 +1,91 @@
+use bento_types::notifications::IndexedNotification;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams, ToSchema, Serialize)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct StreamQuery {
+    /// Receive every new block
+    #[serde(default)]
+    pub blocks: bool,
+    /// Comma separated contract addresses to receive the events of
+    pub contracts: Option<String>,
+    /// Comma separated addresses to receive the activity of
+    pub addresses: Option<String>,
+    /// Comma separated topics published by custom processors
+    pub topics: Option<String>,
+}
+
+fn split_list(list: &Option<String>) -> Vec<String> {
+    list.as_deref()
+        .map(|list| list.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Which notifications a subscriber receives. A subscriber without any filter receives all of them.
+#[derive(Debug, Clone, Default)]
+pub struct StreamFilter {
+    pub blocks: bool,
+    pub contracts: Vec<String>,
+    pub addresses: Vec<String>,
+    pub topics: Vec<String>,
+}
+
+impl From<&StreamQuery> for StreamFilter {
+    fn from(query: &StreamQuery) -> Self {
+        Self {
+            blocks: query.blocks,
+            contracts: split_list(&query.contracts),
+            addresses: split_list(&query.addresses),
+            topics: split_list(&query.topics),
+        }
+    }
+}
+
+impl StreamFilter {
+    pub fn is_empty(&self) -> bool {
+        !self.blocks && self.contracts.is_empty() && self.addresses.is_empty() && self.topics.is_empty()
+    }
+
+    pub fn matches(&self, notification: &IndexedNotification) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match notification {
+            IndexedNotification::Block { .. } => self.blocks,
+            IndexedNotification::Event { contract_address, .. } => self.contracts.contains(contract_address),
+            IndexedNotification::AddressActivity { address, .. } => self.addresses.contains(address),
+            IndexedNotification::Custom { topic, .. } => self.topics.contains(topic),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_filter() {
+        let event = IndexedNotification::Event {
+            id: "id".to_string(),
+            tx_id: "tx".to_string(),
+            contract_address: "contract2".to_string(),
+            event_index: 0,
+        };
+        let block = IndexedNotification::Block {
+            hash: "hash".to_string(),
+            chain_from: 0,
+            chain_to: 0,
+            height: 1,
+            timestamp: 0,
+        };
+
+        let filter = StreamFilter::from(&StreamQuery::default());
+        assert!(filter.matches(&event) && filter.matches(&block));
+
+        let query = StreamQuery { contracts: Some("contract1, contract2".to_string()), ..Default::default() };
+        let filter = StreamFilter::from(&query);
+        assert!(filter.matches(&event));
+        assert!(!filter.matches(&block));
+    }
+}
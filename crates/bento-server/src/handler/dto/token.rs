@@ -0,0 +1,83 @@
+use bento_types::{
+    repository::{TokenHolder, TokenStats, TokenTransfer},
+    TokenModel,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::Pagination;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenDto {
+    pub token_id: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    pub first_seen_tx_hash: String,
+    pub first_seen_block_hash: String,
+    #[schema(example = "2023-01-01T00:00:00")]
+    pub first_seen_at: String,
+    /// Sum of all unspent outputs holding the token, without decimals applied
+    #[schema(example = "1000000000000000000")]
+    pub supply: String,
+    pub holder_count: i64,
+}
+
+impl TokenDto {
+    pub fn new(model: TokenModel, stats: Option<&TokenStats>) -> Self {
+        Self {
+            token_id: model.token_id,
+            name: model.name,
+            symbol: model.symbol,
+            decimals: model.decimals,
+            first_seen_tx_hash: model.first_seen_tx_hash,
+            first_seen_block_hash: model.first_seen_block_hash,
+            first_seen_at: model.first_seen_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            supply: stats.map(|s| s.supply.to_string()).unwrap_or_else(|| "0".to_string()),
+            holder_count: stats.map(|s| s.holder_count).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenHolderDto {
+    pub address: String,
+    #[schema(example = "1000000000000000000")]
+    pub balance: String,
+}
+
+impl From<TokenHolder> for TokenHolderDto {
+    fn from(holder: TokenHolder) -> Self {
+        Self { address: holder.address, balance: holder.balance.to_string() }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenTransferDto {
+    pub tx_hash: String,
+    pub address: String,
+    /// Net balance change of `address` in this transaction, negative when sending
+    #[schema(example = "-1000000000000000000")]
+    pub amount: String,
+    #[schema(example = "2023-01-01T00:00:00")]
+    pub timestamp: String,
+}
+
+impl From<TokenTransfer> for TokenTransferDto {
+    fn from(transfer: TokenTransfer) -> Self {
+        Self {
+            tx_hash: transfer.tx_hash,
+            address: transfer.address,
+            amount: transfer.amount.to_string(),
+            timestamp: transfer.timestamp.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema, Serialize)]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct TokensQuery {
+    #[serde(flatten)]
+    #[param(inline, example = json!({"offset": 0, "limit": 10}))]
+    pub pagination: Pagination,
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use bento_types::export::{load_export_columns, ExportPlan, ExportTable};
+use futures::stream;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::auth::RoutePolicy;
+use crate::error::AppError;
+use crate::handler::dto::ExportQuery;
+use crate::AppState;
+
+pub struct ExportApiModule;
+
+impl ExportApiModule {
+    /// Exports can be large, so they need an API key.
+    pub fn register() -> OpenApiRouter<crate::AppState> {
+        OpenApiRouter::new()
+            .routes(routes!(list_exports_handler))
+            .merge(RoutePolicy::default().require_api_key().apply(OpenApiRouter::new().routes(routes!(export_handler))))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "Exports",
+    responses(
+        (status = 200, description = "Tables that can be exported", body = Vec<ExportTable>)
+    )
+)]
+pub async fn list_exports_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.exports.iter().cloned().collect::<Vec<_>>())
+}
+
+#[utoipa::path(
+    get,
+    path = "/{table}",
+    tag = "Exports",
+    params(("table" = String, Path, description = "Name of the table"), ExportQuery),
+    responses(
+        (status = 200, description = "Rows of the window, streamed in the requested format"),
+        (status = 400, description = "Invalid window, format or filter"),
+        (status = 401, description = "Missing API key"),
+        (status = 404, description = "Unknown table")
+    ),
+    security(("api_key" = []))
+)]
+pub async fn export_handler(
+    State(state): State<AppState>,
+    Path(table): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, AppError> {
+    let table = state.exports.get(&table).ok_or_else(|| AppError::NotFound(format!("Export table {table}")))?;
+    let request = ExportQuery::parse(params).map_err(AppError::BadRequest)?;
+    let (from_ts, to_ts) = (request.from_ts, request.to_ts);
+
+    let columns = load_export_columns(&state.db, table).await?;
+    let plan = ExportPlan::new(table.clone(), columns, request).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let format = plan.format();
+
+    let chunks = plan.spawn(state.db.clone());
+    let body = Body::from_stream(stream::unfold(chunks, |mut chunks| async move {
+        chunks.recv().await.map(|chunk| (chunk, chunks))
+    }));
+    let filename = format!("{}-{from_ts}-{to_ts}.{}", table.name, format.extension());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        body,
+    )
+        .into_response())
+}
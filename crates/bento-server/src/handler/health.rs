@@ -0,0 +1,308 @@
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use bento_trait::stage::BlockProvider;
+use bento_types::models::processor_status::ProcessorStatusModel;
+use bento_types::repository::{get_latest_block, get_processor_statuses};
+use bento_types::DEFAULT_GROUP_NUM;
+use futures::future::join_all;
+use std::future::Future;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handler::dto::{
+    ChainHealthDto, DatabaseHealthDto, HealthStatus, HealthThresholdsDto, LivenessDto, NodeHealthDto,
+    ProcessorHealthDto, ReadinessDto,
+};
+use crate::AppState;
+
+/// Thresholds of the readiness check.
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// Blocks a chain may be behind the node
+    pub max_chain_lag: i64,
+    /// Age of the latest block stored by a processor
+    pub max_checkpoint_age: Duration,
+    /// Time each database and node call of the check may take before it fails, keeping the probe
+    /// within the deadline of the orchestrator
+    pub check_timeout: Duration,
+    /// Processors whose checkpoints are checked, every recorded one when unset. Rows left by
+    /// renamed, removed or backfill-only processors are ignored when this is set
+    pub processors: Option<Vec<String>>,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_chain_lag: 3,
+            max_checkpoint_age: Duration::from_secs(10 * 60),
+            check_timeout: Duration::from_secs(2),
+            processors: None,
+        }
+    }
+}
+
+pub struct HealthApiModule;
+
+impl HealthApiModule {
+    pub fn register() -> OpenApiRouter<crate::AppState> {
+        OpenApiRouter::new().routes(routes!(liveness_handler)).routes(routes!(readiness_handler))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/live",
+    tag = "Health",
+    responses((status = 200, description = "The server is running", body = LivenessDto))
+)]
+pub async fn liveness_handler() -> impl IntoResponse {
+    Json(LivenessDto { status: "ok" })
+}
+
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Database, node, chains and processors are healthy", body = ReadinessDto),
+        (status = 503, description = "At least one check failed, see the body", body = ReadinessDto)
+    )
+)]
+pub async fn readiness_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let report = check_readiness(&state).await;
+    let status = if report.status == HealthStatus::Ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+/// `check`, failing when it takes longer than `timeout`
+async fn timed<T>(timeout: Duration, check: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+    tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out after {} ms", timeout.as_millis())))
+}
+
+async fn check_database(state: &AppState) -> DatabaseHealthDto {
+    use diesel_async::RunQueryDsl;
+
+    let started = Instant::now();
+    let result = timed(state.health.check_timeout, async {
+        let mut conn = state.db.get().await?;
+        diesel::sql_query("SELECT 1").execute(&mut conn).await?;
+        anyhow::Ok(())
+    })
+    .await;
+    DatabaseHealthDto {
+        ok: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+async fn check_chain(state: &AppState, chain_from: i64, chain_to: i64, max_lag: i64) -> ChainHealthDto {
+    let timeout = state.health.check_timeout;
+    let (latest, info) = tokio::join!(
+        timed(timeout, get_latest_block(&state.db, chain_from, chain_to)),
+        timed(timeout, state.node_client.get_chain_info(chain_from as u32, chain_to as u32))
+    );
+
+    let latest = latest.map(|b| b.map(|b| b.height));
+    let info = info.map(|info| info.current_height);
+    chain_health(chain_from, chain_to, latest, info, max_lag)
+}
+
+/// Compare the latest indexed height of a chain with the node tip.
+fn chain_health(
+    chain_from: i64,
+    chain_to: i64,
+    indexed: anyhow::Result<Option<i64>>,
+    node: anyhow::Result<i64>,
+    max_lag: i64,
+) -> ChainHealthDto {
+    let indexed_height = indexed.as_ref().ok().copied().flatten();
+    let node_height = node.as_ref().ok().copied();
+    // The index can briefly be ahead of a lagging node, which is not lag
+    let lag_blocks = node_height.map(|node| (node - indexed_height.unwrap_or(-1)).max(0));
+    let error = match (&indexed, &node) {
+        (Err(err), _) => Some(format!("database: {err}")),
+        (_, Err(err)) => Some(format!("node: {err}")),
+        _ => None,
+    };
+
+    ChainHealthDto {
+        chain_from,
+        chain_to,
+        ok: error.is_none() && lag_blocks.is_some_and(|lag| lag <= max_lag),
+        node_height,
+        indexed_height,
+        lag_blocks,
+        error,
+    }
+}
+
+/// Checkpoint ages of the processors checked by `config` at `now`.
+fn processor_health(config: &HealthConfig, statuses: Vec<ProcessorStatusModel>, now: i64) -> Vec<ProcessorHealthDto> {
+    let max_age_ms = config.max_checkpoint_age.as_millis() as i64;
+    statuses
+        .into_iter()
+        .filter(|s| config.processors.as_ref().is_none_or(|names| names.contains(&s.processor)))
+        .map(|s| {
+            let checkpoint_age_ms = (now - s.last_timestamp).max(0);
+            ProcessorHealthDto {
+                ok: checkpoint_age_ms <= max_age_ms,
+                processor: s.processor,
+                last_timestamp: s.last_timestamp,
+                checkpoint_age_ms,
+            }
+        })
+        .collect()
+}
+
+/// Run every readiness check. Chains are checked concurrently, and every call is bounded by
+/// [`HealthConfig::check_timeout`].
+pub async fn check_readiness(state: &AppState) -> ReadinessDto {
+    let config = &state.health;
+    let database = check_database(state).await;
+
+    let chains = (0..DEFAULT_GROUP_NUM)
+        .flat_map(|from| (0..DEFAULT_GROUP_NUM).map(move |to| (from, to)))
+        .map(|(from, to)| check_chain(state, from, to, config.max_chain_lag));
+    let chains = join_all(chains).await;
+
+    let node_errors: Vec<&str> =
+        chains.iter().filter_map(|c| c.error.as_deref().and_then(|e| e.strip_prefix("node: "))).collect();
+    let node =
+        NodeHealthDto { ok: node_errors.len() < chains.len(), error: node_errors.first().map(|e| e.to_string()) };
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let processors = match timed(config.check_timeout, get_processor_statuses(state.db.clone())).await {
+        Ok(statuses) => processor_health(config, statuses, now),
+        // Already reported by the database check
+        Err(_) => Vec::new(),
+    };
+
+    readiness(config, database, node, chains, processors)
+}
+
+/// Ready when every check passed.
+fn readiness(
+    config: &HealthConfig,
+    database: DatabaseHealthDto,
+    node: NodeHealthDto,
+    chains: Vec<ChainHealthDto>,
+    processors: Vec<ProcessorHealthDto>,
+) -> ReadinessDto {
+    let ready = database.ok && node.ok && chains.iter().all(|c| c.ok) && processors.iter().all(|p| p.ok);
+
+    ReadinessDto {
+        status: if ready { HealthStatus::Ready } else { HealthStatus::NotReady },
+        database,
+        node,
+        chains,
+        processors,
+        thresholds: HealthThresholdsDto {
+            max_chain_lag: config.max_chain_lag,
+            max_checkpoint_age_ms: config.max_checkpoint_age.as_millis() as i64,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(processor: &str, last_timestamp: i64) -> ProcessorStatusModel {
+        ProcessorStatusModel { processor: processor.to_string(), last_timestamp }
+    }
+
+    fn healthy() -> (DatabaseHealthDto, NodeHealthDto) {
+        (DatabaseHealthDto { ok: true, latency_ms: 1, error: None }, NodeHealthDto { ok: true, error: None })
+    }
+
+    #[test]
+    fn test_chain_lag_threshold() {
+        assert!(chain_health(0, 0, Ok(Some(97)), Ok(100), 3).ok);
+        let behind = chain_health(0, 0, Ok(Some(96)), Ok(100), 3);
+        assert!(!behind.ok);
+        assert_eq!(behind.lag_blocks, Some(4));
+
+        // A stale node tip is not lag
+        assert_eq!(chain_health(0, 0, Ok(Some(105)), Ok(100), 3).lag_blocks, Some(0));
+        // Nothing indexed yet, the whole chain is missing
+        assert_eq!(chain_health(0, 0, Ok(None), Ok(2), 3).lag_blocks, Some(3));
+
+        let node_down = chain_health(0, 1, Ok(Some(100)), Err(anyhow::anyhow!("refused")), 3);
+        assert!(!node_down.ok);
+        assert_eq!(node_down.lag_blocks, None);
+        assert_eq!(node_down.error.as_deref(), Some("node: refused"));
+    }
+
+    #[test]
+    fn test_processor_checkpoint_age() {
+        let config = HealthConfig { max_checkpoint_age: Duration::from_secs(60), ..Default::default() };
+        let now = 1_000_000;
+        let processors =
+            processor_health(&config, vec![status("fresh", now - 60_000), status("stale", now - 60_001)], now);
+        assert_eq!(
+            processors.iter().map(|p| (p.processor.as_str(), p.ok)).collect::<Vec<_>>(),
+            vec![("fresh", true), ("stale", false)]
+        );
+
+        // A checkpoint ahead of the clock is not negative
+        assert_eq!(processor_health(&config, vec![status("ahead", now + 5)], now)[0].checkpoint_age_ms, 0);
+    }
+
+    #[test]
+    fn test_only_configured_processors_are_checked() {
+        let config = HealthConfig { processors: Some(vec!["block".to_string()]), ..Default::default() };
+        let now = chrono::Utc::now().timestamp_millis();
+        let processors = processor_health(&config, vec![status("block", now), status("renamed", 0)], now);
+        assert_eq!(processors.len(), 1);
+        assert_eq!(processors[0].processor, "block");
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_check_fails() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            anyhow::Ok(())
+        };
+        let err = timed(Duration::from_millis(10), slow).await.unwrap_err();
+        assert_eq!(err.to_string(), "timed out after 10 ms");
+        assert_eq!(timed(Duration::from_secs(1), async { anyhow::Ok(1) }).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_readiness_status() {
+        let config = HealthConfig::default();
+        let chain = |ok| ChainHealthDto {
+            chain_from: 0,
+            chain_to: 0,
+            ok,
+            node_height: None,
+            indexed_height: None,
+            lag_blocks: None,
+            error: None,
+        };
+        let processor =
+            |ok| ProcessorHealthDto { processor: "block".to_string(), ok, last_timestamp: 0, checkpoint_age_ms: 0 };
+
+        let (database, node) = healthy();
+        assert_eq!(
+            readiness(&config, database, node, vec![chain(true)], vec![processor(true)]).status,
+            HealthStatus::Ready
+        );
+        let (database, node) = healthy();
+        assert_eq!(
+            readiness(&config, database, node, vec![chain(true), chain(false)], vec![]).status,
+            HealthStatus::NotReady
+        );
+        let (database, node) = healthy();
+        assert_eq!(readiness(&config, database, node, vec![], vec![processor(false)]).status, HealthStatus::NotReady);
+        let (mut database, node) = healthy();
+        database.ok = false;
+        assert_eq!(readiness(&config, database, node, vec![], vec![]).status, HealthStatus::NotReady);
+    }
+}
@@ -0,0 +1,80 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use bento_types::repository::{get_miner_history, get_miner_stats};
+use bento_types::utils::timestamp_millis_to_naive_datetime;
+use chrono::{Duration, NaiveDateTime};
+
+use crate::error::AppError;
+use crate::handler::dto::{MinerHistoryQuery, MinerStatsBucketDto, MinerStatsDto, MinerStatsQuery};
+use crate::AppState;
+use axum::response::IntoResponse;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+pub struct MiningApiModule;
+
+impl MiningApiModule {
+    pub fn register() -> OpenApiRouter<crate::AppState> {
+        OpenApiRouter::new().routes(routes!(get_miners_handler)).routes(routes!(get_miner_history_handler))
+    }
+}
+
+/// Resolve an optional `[from, to)` window in milliseconds, `to` defaults to now.
+fn time_window(
+    from: Option<i64>,
+    to: Option<i64>,
+    default_length: Duration,
+) -> Result<(NaiveDateTime, NaiveDateTime), AppError> {
+    let to = to.map(timestamp_millis_to_naive_datetime).unwrap_or_else(|| chrono::Utc::now().naive_utc());
+    let from = from.map(timestamp_millis_to_naive_datetime).unwrap_or(to - default_length);
+    if from >= to {
+        return Err(AppError::BadRequest("from must be before to".to_string()));
+    }
+    Ok((from, to))
+}
+
+#[utoipa::path(
+    get,
+    path = "/miners",
+    tag = "Mining",
+    params(MinerStatsQuery),
+    responses(
+        (status = 200, description = "Blocks, uncles and rewards per miner, most blocks first", body = Vec<MinerStatsDto>),
+        (status = 400, description = "Invalid time window"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_miners_handler(
+    Query(query): Query<MinerStatsQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let (from, to) = time_window(query.from, query.to, Duration::days(1))?;
+    let pagination = query.pagination;
+    let stats = get_miner_stats(state.db, from, to, pagination.get_limit(), pagination.get_offset()).await?;
+    let stats: Vec<MinerStatsDto> = stats.into_iter().map(MinerStatsDto::from).collect();
+    Ok(Json(stats))
+}
+
+#[utoipa::path(
+    get,
+    path = "/miners/{address}/history",
+    tag = "Mining",
+    params(
+        ("address" = String, Path, description = "Miner address"),
+        MinerHistoryQuery
+    ),
+    responses(
+        (status = 200, description = "Blocks, uncles and rewards of the miner over time", body = Vec<MinerStatsBucketDto>),
+        (status = 400, description = "Invalid time window"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_miner_history_handler(
+    Path(address): Path<String>,
+    Query(query): Query<MinerHistoryQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let (from, to) = time_window(query.from, query.to, Duration::days(30))?;
+    let buckets = get_miner_history(state.db, &address, query.interval, from, to).await?;
+    let buckets: Vec<MinerStatsBucketDto> = buckets.into_iter().map(MinerStatsBucketDto::from).collect();
+    Ok(Json(buckets))
+}
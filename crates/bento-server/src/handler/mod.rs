@@ -2,13 +2,25 @@ use axum::Router;
 
 use super::AppState;
 
+pub mod address;
 pub mod block;
 pub mod dto;
 pub mod event;
+pub mod export;
+pub mod health;
+pub mod mining;
+pub mod stream;
+pub mod token;
 pub mod transaction;
 
+pub use address::AddressApiModule;
 pub use block::BlockApiModule;
 pub use event::EventApiModule;
+pub use export::ExportApiModule;
+pub use health::{HealthApiModule, HealthConfig};
+pub use mining::MiningApiModule;
+pub use stream::StreamApiModule;
+pub use token::TokenApiModule;
 pub use transaction::TransactionApiModule;
 pub trait ApiModule {
     fn register() -> Router<AppState>;
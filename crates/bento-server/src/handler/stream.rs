@@ -0,0 +1,55 @@
+use std::convert::Infallible;
+
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::Stream;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handler::dto::{StreamFilter, StreamQuery};
+use crate::AppState;
+
+pub struct StreamApiModule;
+
+impl StreamApiModule {
+    pub fn register() -> OpenApiRouter<crate::AppState> {
+        OpenApiRouter::new().routes(routes!(stream_handler))
+    }
+}
+
+/// Server-sent events of newly indexed blocks, contract events, address activity and custom
+/// processor topics. Each SSE event is named after the notification type and carries it as JSON.
+/// A `lagged` event with the number of skipped notifications is sent when the client falls behind.
+///
+/// Notifications come from the Postgres `NOTIFY` of a realtime worker running with `worker.notify`
+/// (the default); without it subscribers never receive anything.
+#[utoipa::path(get, path = "/", params(StreamQuery), tag = "Stream", responses((status = OK, description = "Stream of indexed data", content_type = "text/event-stream")))]
+pub async fn stream_handler(
+    Query(query): Query<StreamQuery>,
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let filter = StreamFilter::from(&query);
+    let receiver = state.notifications.subscribe();
+
+    let stream = futures::stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(notification) if filter.matches(&notification) => {
+                    match Event::default().event(notification.kind()).json_data(&notification) {
+                        Ok(event) => event,
+                        Err(err) => {
+                            tracing::warn!(error = ?err, "Failed to serialize notification");
+                            continue;
+                        }
+                    }
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => Event::default().event("lagged").data(skipped.to_string()),
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), (receiver, filter)));
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,118 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use bento_types::repository::{get_token, get_token_holders, get_token_stats, get_token_transfers, get_tokens};
+
+use crate::error::AppError;
+use crate::handler::dto::{TokenDto, TokenHolderDto, TokenTransferDto, TokensQuery};
+use crate::AppState;
+use crate::Pagination;
+use axum::response::IntoResponse;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+pub struct TokenApiModule;
+
+impl TokenApiModule {
+    pub fn register() -> OpenApiRouter<crate::AppState> {
+        OpenApiRouter::new()
+            .routes(routes!(get_tokens_handler))
+            .routes(routes!(get_token_handler))
+            .routes(routes!(get_token_holders_handler))
+            .routes(routes!(get_token_transfers_handler))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "Tokens",
+    params(TokensQuery),
+    responses(
+        (status = 200, description = "Tokens retrieved successfully, most recently discovered first", body = Vec<TokenDto>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_tokens_handler(
+    pagination: Query<Pagination>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let token_models = get_tokens(state.db.clone(), pagination.get_limit(), pagination.get_offset()).await?;
+
+    let ids: Vec<String> = token_models.iter().map(|t| t.token_id.clone()).collect();
+    let stats = get_token_stats(state.db.clone(), &ids).await?;
+    let tokens: Vec<TokenDto> = token_models
+        .into_iter()
+        .map(|token| {
+            let token_stats = stats.iter().find(|s| s.token_id == token.token_id);
+            TokenDto::new(token, token_stats)
+        })
+        .collect();
+    Ok(Json(tokens))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "Tokens",
+    params(("id" = String, Path, description = "Token id")),
+    responses(
+        (status = 200, description = "Token retrieved successfully", body = TokenDto),
+        (status = 404, description = "Token not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_token_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(token_model) = get_token(state.db.clone(), &id).await? else {
+        return Err(AppError::NotFound(format!("Token with id {id} not found")));
+    };
+    let stats = get_token_stats(state.db.clone(), std::slice::from_ref(&id)).await?;
+    Ok(Json(TokenDto::new(token_model, stats.first())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/holders",
+    tag = "Tokens",
+    params(
+        ("id" = String, Path, description = "Token id"),
+        TokensQuery
+    ),
+    responses(
+        (status = 200, description = "Token holders, largest balance first", body = Vec<TokenHolderDto>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_token_holders_handler(
+    Path(id): Path<String>,
+    pagination: Query<Pagination>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let holders = get_token_holders(state.db, &id, pagination.get_limit(), pagination.get_offset()).await?;
+    let holders: Vec<TokenHolderDto> = holders.into_iter().map(TokenHolderDto::from).collect();
+    Ok(Json(holders))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/transfers",
+    tag = "Tokens",
+    params(
+        ("id" = String, Path, description = "Token id"),
+        TokensQuery
+    ),
+    responses(
+        (status = 200, description = "Per-transaction balance changes of the token, newest first", body = Vec<TokenTransferDto>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_token_transfers_handler(
+    Path(id): Path<String>,
+    pagination: Query<Pagination>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let transfers = get_token_transfers(state.db, &id, pagination.get_limit(), pagination.get_offset()).await?;
+    let transfers: Vec<TokenTransferDto> = transfers.into_iter().map(TokenTransferDto::from).collect();
+    Ok(Json(transfers))
+}
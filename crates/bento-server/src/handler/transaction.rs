@@ -1,6 +1,7 @@
-use axum::extract::{Query, State};
+use axum::extract::{Path, Query, State};
 use axum::Json;
-use bento_types::repository::{get_tx_by_hash, get_txs, get_txs_by_block};
+use bento_types::decoded::DecodedTransaction;
+use bento_types::repository::{get_decoded_tx, get_tx_by_hash, get_txs, get_txs_by_block};
 
 use crate::error::AppError;
 use crate::handler::dto::{TransactionBlockQuery, TransactionDto, TransactionHashQuery, TransactionsQuery};
@@ -17,6 +18,7 @@ impl TransactionApiModule {
             .routes(routes!(get_txs_handler))
             .routes(routes!(get_tx_by_hash_handler))
             .routes(routes!(get_tx_by_block_handler))
+            .routes(routes!(get_decoded_tx_handler))
     }
 }
 
@@ -84,3 +86,25 @@ pub async fn get_tx_by_block_handler(
     let tx_models = get_txs_by_block(db, &block_hash).await?;
     Ok(Json(tx_models))
 }
+
+#[utoipa::path(
+    get,
+    path = "/{hash}/decoded",
+    tag = "Transactions",
+    params(("hash" = String, Path, description = "Hash of the transaction")),
+    responses(
+        (status = 200, description = "Transaction with per-address balance changes, fee and events", body = DecodedTransaction),
+        (status = 404, description = "Transaction not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_decoded_tx_handler(
+    Path(hash): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let db = state.db;
+    match get_decoded_tx(db, &hash).await? {
+        Some(decoded) => Ok(Json(decoded)),
+        None => Err(AppError::NotFound(format!("Transaction with tx id {hash} not found"))),
+    }
+}
@@ -1,26 +1,47 @@
-use crate::error::AppError;
 use anyhow::Result;
-use axum::{extract::State, response::IntoResponse, routing::get};
-use bento_core::Client;
-use bento_trait::stage::BlockProvider;
-use bento_types::{repository::get_latest_block, ChainInfo, DbPool};
-use handler::{BlockApiModule, EventApiModule, TransactionApiModule};
+use auth::{authenticate, document_security, AuthConfig, AuthState};
+use axum::{middleware, routing::get, Extension};
+use bento_core::{spawn_listener, Client, NOTIFICATION_CAPACITY};
+use bento_types::{export::ExportTables, notifications::IndexedNotification, DbPool};
+use cache::ResponseCache;
+use handler::{
+    health::readiness_handler, AddressApiModule, BlockApiModule, EventApiModule, ExportApiModule, HealthApiModule,
+    HealthConfig, MiningApiModule, StreamApiModule, TokenApiModule, TransactionApiModule,
+};
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 use std::sync::Arc;
+use tokio::sync::broadcast;
+use tower::ServiceBuilder;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+use trace::{attach_request_id, AccessLog, RequestSpan, REQUEST_ID_HEADER};
 use utoipa::{openapi::Info, ToSchema};
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_swagger_ui::SwaggerUi;
 
+pub mod auth;
+pub mod cache;
 pub mod error;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod handler;
+pub mod trace;
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub db_client: Arc<DbPool>,
+    /// Used to listen for the notifications published by workers
+    pub database_url: String,
     pub node_client: Arc<Client>,
     pub api_host: String,
     pub api_port: u16,
+    pub auth: AuthConfig,
+    pub health: HealthConfig,
+    /// Tables served under `/v1/exports`
+    pub exports: ExportTables,
 }
 
 impl Config {
@@ -33,6 +54,13 @@ impl Config {
 pub struct AppState {
     pub db: Arc<DbPool>,
     pub node_client: Arc<Client>,
+    /// Newly indexed data, fed by the workers through Postgres notifications
+    pub notifications: broadcast::Sender<IndexedNotification>,
+    /// Responses of the routes opting in with `CachePolicy`
+    pub cache: ResponseCache,
+    /// Thresholds of the readiness check
+    pub health: HealthConfig,
+    pub exports: ExportTables,
 }
 use std::str::FromStr;
 
@@ -57,6 +85,18 @@ where
     i64::from_str(&s).map_err(serde::de::Error::custom)
 }
 
+// Custom deserializer for optional string to i64 conversion, an empty string is `None`
+pub fn deserialize_optional_number_from_string<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s.as_deref() {
+        None | Some("") => Ok(None),
+        Some(s) => i64::from_str(s).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
 impl Pagination {
     pub fn get_offset(&self) -> i64 {
         if self.offset < 0 {
@@ -82,20 +122,59 @@ impl Pagination {
 }
 
 pub async fn start(config: Config, custom_router: Option<OpenApiRouter<AppState>>) -> Result<()> {
-    let state = AppState { db: config.clone().db_client, node_client: config.clone().node_client };
-
-    let (app, mut api) = configure_api(custom_router).with_state(state).split_for_parts();
+    let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+    let listener_handle = spawn_listener(config.database_url.clone(), notifications.clone());
+    let cache = ResponseCache::default();
+    let invalidator_handle = cache.spawn_invalidator(notifications.subscribe());
+    let state = AppState {
+        db: config.clone().db_client,
+        node_client: config.clone().node_client,
+        notifications,
+        cache: cache.clone(),
+        health: config.health.clone(),
+        exports: config.exports.clone(),
+    };
+
+    let (app, mut api) = configure_api(custom_router).with_state(state.clone()).split_for_parts();
+    let (probes, probes_api) = configure_probes().with_state(state).split_for_parts();
+    api.merge(probes_api);
 
     api.info = Info::new("REST API", "v1");
     api.info.description = Some("Bento Alephium Indexer REST API".to_string());
+    document_security(&mut api);
+
+    let auth = AuthState::new(config.db_client.clone(), config.auth.clone());
     let app = app
+        .layer(Extension(cache))
+        .layer(middleware::from_fn_with_state(auth, authenticate))
+        // Probes skip authentication and rate limits, a throttled probe would restart the server
+        .merge(probes)
+        .layer(middleware::from_fn(attach_request_id))
         .layer(CorsLayer::permissive())
+        .layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(RequestSpan)
+                        .on_request(())
+                        .on_response(AccessLog)
+                        // Failed responses are already in the access log
+                        .on_failure(()),
+                )
+                .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER))
+                // Event streams are never compressed
+                .layer(CompressionLayer::new()),
+        )
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone()));
 
     let addr = config.api_endpoint();
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
-    axum::serve(listener, app).await?;
+    let result = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await;
+    listener_handle.abort();
+    invalidator_handle.abort();
+    result?;
 
     Ok(())
 }
@@ -104,21 +183,12 @@ async fn root() -> &'static str {
     "Hello Alephium Indexer API"
 }
 
-async fn health_check(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
-    let from_group = 0;
-    let to_group = 0;
-    let chain_info: ChainInfo = state.node_client.get_chain_info(from_group, to_group).await?;
-    let remote_height = chain_info.current_height;
-    let block = get_latest_block(&state.db, from_group as i64, to_group as i64).await?;
-    let block_height = block.map(|b| b.height);
-
-    block_height.map_or(Err(AppError::Internal(anyhow::anyhow!("No block found in database"))), |h| {
-        if remote_height - h > 3 {
-            Err(AppError::Internal(anyhow::anyhow!("Indexer is too far behind the node")))
-        } else {
-            Ok(())
-        }
-    })
+/// Liveness and readiness probes, served outside the authentication layer.
+pub fn configure_probes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .nest("/v1/health", HealthApiModule::register())
+        // Kept for existing probes, same as `/v1/health/ready`
+        .route("/v1/health", get(readiness_handler))
 }
 
 #[allow(clippy::let_and_return)]
@@ -127,8 +197,12 @@ pub fn configure_api(custom_router: Option<OpenApiRouter<AppState>>) -> OpenApiR
         .nest("/v1/blocks", BlockApiModule::register())
         .nest("/v1/events", EventApiModule::register())
         .nest("/v1/transactions", TransactionApiModule::register())
-        .route("/", get(root))
-        .route("/v1/health", get(health_check));
+        .nest("/v1/addresses", AddressApiModule::register())
+        .nest("/v1/tokens", TokenApiModule::register())
+        .nest("/v1/mining", MiningApiModule::register())
+        .nest("/v1/stream", StreamApiModule::register())
+        .nest("/v1/exports", ExportApiModule::register())
+        .route("/", get(root));
 
     if let Some(custom_router) = custom_router {
         router.merge(custom_router)
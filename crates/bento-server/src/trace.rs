@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::{MatchedPath, Request};
+use axum::http::{self, header, HeaderName};
+use axum::middleware::Next;
+use axum::response::Response;
+use tower_http::trace::{MakeSpan, OnResponse};
+use tracing::Span;
+
+use crate::error::ErrorBody;
+
+/// Header carrying the request id. A client may send its own, otherwise one is generated; either
+/// way it is echoed in the response.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Span of a request, carrying its method, route and request id. Status and latency are recorded
+/// once the response is ready.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &http::Request<B>) -> Span {
+        // Routes are matched before this layer runs, unmatched requests fall back to the path
+        let route = request.extensions().get::<MatchedPath>().map_or(request.uri().path(), |path| path.as_str());
+        let request_id = request.headers().get(REQUEST_ID_HEADER).and_then(|id| id.to_str().ok()).unwrap_or("");
+
+        tracing::info_span!(
+            "request",
+            method = %request.method(),
+            route,
+            request_id,
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        )
+    }
+}
+
+/// Access log line emitted when a response is ready.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AccessLog;
+
+impl<B> OnResponse<B> for AccessLog {
+    fn on_response(self, response: &http::Response<B>, latency: Duration, span: &Span) {
+        span.record("status", response.status().as_u16());
+        span.record("latency_ms", latency.as_millis() as u64);
+
+        if response.status().is_server_error() {
+            tracing::warn!("request failed");
+        } else {
+            tracing::info!("request completed");
+        }
+    }
+}
+
+/// Add the request id to the JSON body of [`crate::error::AppError`] responses, so a failed
+/// request reported by a user can be found in the logs.
+pub async fn attach_request_id(request: Request, next: Next) -> Response {
+    let request_id = request.headers().get(REQUEST_ID_HEADER).and_then(|id| id.to_str().ok()).map(str::to_string);
+    let response = next.run(request).await;
+    match request_id {
+        Some(request_id) => with_request_id(response, &request_id),
+        None => response,
+    }
+}
+
+fn with_request_id(response: Response, request_id: &str) -> Response {
+    let Some(body) = response.extensions().get::<ErrorBody>().cloned() else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let body = body.with_request_id(request_id);
+    parts.extensions.insert(body.clone());
+    Response::from_parts(parts, Body::from(body.0.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    #[tokio::test]
+    async fn test_request_id_in_error_body() {
+        let response = with_request_id(AppError::NotFound("block".to_string()).into_response(), "req-1");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["request_id"], "req-1");
+        assert_eq!(body["error"]["code"], 404);
+
+        // Other responses are left untouched
+        let response = with_request_id("ok".into_response(), "req-1");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"ok");
+    }
+}
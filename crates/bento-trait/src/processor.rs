@@ -2,7 +2,8 @@ use std::{fmt::Debug, sync::Arc};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use bento_types::{processors::ProcessorOutput, BlockAndEvents, DbPool};
+use bento_types::{processors::ProcessorOutput, BlockAndEvents, BlockRange, DbPool};
+use diesel_async::AsyncPgConnection;
 
 /// Base trait for all processors that includes both processing and storage
 #[async_trait]
@@ -16,8 +17,16 @@ pub trait ProcessorTrait: Send + Sync + Debug + 'static {
     /// Process a batch of blocks and produce output
     async fn process_blocks(&self, blocks: Vec<BlockAndEvents>) -> Result<ProcessorOutput>;
 
-    /// Store the processing output
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()>;
+    /// Store the processing output. `conn` runs inside a transaction that also holds the
+    /// webhook deliveries of the output, or the purge of the range when reindexing.
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()>;
+
+    /// Delete the rows this processor stored for blocks with a timestamp in `range`, so the
+    /// range can be reindexed. `conn` is shared by all processors being reindexed and runs
+    /// inside a single transaction.
+    async fn purge_range(&self, _conn: &mut AsyncPgConnection, _range: &BlockRange) -> Result<()> {
+        anyhow::bail!("Processor {} does not support reindexing", self.name())
+    }
 }
 
 pub type DynProcessor = Box<dyn ProcessorTrait>;
@@ -35,6 +35,16 @@ pub trait TransactionProvider {
 #[async_trait]
 pub trait ContractsProvider {
     async fn call_contract(&self, params: CallContractParams) -> Result<CallContractResult>;
+
+    /// Run several calls, returning their results in order. A failed call is a result with
+    /// `CallContractFailed`, not an error. Calls one at a time unless overridden.
+    async fn multicall_contract(&self, calls: Vec<CallContractParams>) -> Result<Vec<CallContractResult>> {
+        let mut results = Vec::with_capacity(calls.len());
+        for params in calls {
+            results.push(self.call_contract(params).await?);
+        }
+        Ok(results)
+    }
 }
 
 // Pipeline stage traits with message passing
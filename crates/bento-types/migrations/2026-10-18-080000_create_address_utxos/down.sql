@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS address_inputs;
+DROP TABLE IF EXISTS address_outputs;
@@ -0,0 +1,36 @@
+-- Your SQL goes here
+
+-- Every asset/contract output ever created, keyed by its output ref key and block. A
+-- transaction included in an uncle block and in a main chain block creates the same output
+-- keys twice, both are kept and balances count the main chain ones only.
+CREATE TABLE address_outputs (
+    output_key TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    address TEXT NOT NULL,
+    hint INTEGER NOT NULL,
+    atto_alph_amount NUMERIC NOT NULL,
+    tokens JSONB NOT NULL,
+    lock_time BIGINT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (output_key, block_hash)
+);
+
+-- Every output consumed by a transaction, keyed by the spent output ref key and block.
+-- Kept separate from `address_outputs` so that inputs and outputs can be stored
+-- in any order when blocks are processed in parallel.
+CREATE TABLE address_inputs (
+    output_key TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    address TEXT NOT NULL,
+    atto_alph_amount NUMERIC NOT NULL,
+    tokens JSONB NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (output_key, block_hash)
+);
+
+CREATE INDEX idx_address_outputs_address_timestamp ON address_outputs(address, timestamp DESC);
+CREATE INDEX idx_address_outputs_tx_hash ON address_outputs(tx_hash);
+CREATE INDEX idx_address_inputs_address_timestamp ON address_inputs(address, timestamp DESC);
+CREATE INDEX idx_address_inputs_tx_hash ON address_inputs(tx_hash);
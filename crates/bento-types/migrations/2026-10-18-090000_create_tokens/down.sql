@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS token_inputs;
+DROP TABLE IF EXISTS token_outputs;
+DROP TABLE IF EXISTS tokens;
@@ -0,0 +1,47 @@
+-- Your SQL goes here
+
+-- Tokens discovered in transaction outputs. Metadata is resolved lazily from the
+-- token contract and stays NULL until the first lookup. Failed lookups are retried with a
+-- backoff instead of blocking the tokens after them.
+CREATE TABLE tokens (
+    token_id TEXT PRIMARY KEY,
+    first_seen_tx_hash TEXT NOT NULL,
+    first_seen_block_hash TEXT NOT NULL,
+    first_seen_at TIMESTAMP NOT NULL,
+    name TEXT,
+    symbol TEXT,
+    decimals INTEGER,
+    metadata_fetched_at TIMESTAMP,
+    metadata_attempts INTEGER NOT NULL DEFAULT 0,
+    metadata_retry_at TIMESTAMP
+);
+
+-- Token amounts carried by outputs, one row per (output, token, block). Like the address
+-- index, an output of a transaction in an uncle and a main chain block is kept twice.
+CREATE TABLE token_outputs (
+    output_key TEXT NOT NULL,
+    token_id TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    address TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (output_key, token_id, block_hash)
+);
+
+-- Token amounts consumed by inputs, one row per (spent output, token, block)
+CREATE TABLE token_inputs (
+    output_key TEXT NOT NULL,
+    token_id TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    address TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (output_key, token_id, block_hash)
+);
+
+CREATE INDEX idx_tokens_first_seen_at ON tokens(first_seen_at DESC);
+CREATE INDEX idx_token_outputs_token_address ON token_outputs(token_id, address);
+CREATE INDEX idx_token_outputs_token_timestamp ON token_outputs(token_id, timestamp DESC);
+CREATE INDEX idx_token_inputs_token_timestamp ON token_inputs(token_id, timestamp DESC);
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Lookup of child blocks by dependency hash
+CREATE INDEX idx_blocks_deps ON blocks USING GIN (deps);
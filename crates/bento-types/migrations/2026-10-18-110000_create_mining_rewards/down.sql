@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS ghost_uncles;
+DROP TABLE IF EXISTS block_rewards;
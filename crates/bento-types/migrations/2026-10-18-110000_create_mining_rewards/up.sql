@@ -0,0 +1,34 @@
+-- Your SQL goes here
+
+-- Coinbase reward of every block, paid to the block miner
+CREATE TABLE block_rewards (
+    block_hash TEXT PRIMARY KEY,
+    tx_hash TEXT NOT NULL,
+    chain_from BIGINT NOT NULL,
+    chain_to BIGINT NOT NULL,
+    height BIGINT NOT NULL,
+    miner_address TEXT NOT NULL,
+    reward NUMERIC NOT NULL,
+    lock_time BIGINT NOT NULL,
+    uncle_count INTEGER NOT NULL,
+    timestamp TIMESTAMP NOT NULL
+);
+
+-- Ghost uncles referenced by a block, with the reward paid to the uncle miner
+CREATE TABLE ghost_uncles (
+    block_hash TEXT NOT NULL,
+    uncle_hash TEXT NOT NULL,
+    chain_from BIGINT NOT NULL,
+    chain_to BIGINT NOT NULL,
+    miner_address TEXT NOT NULL,
+    -- NULL when the coinbase output of the uncle miner could not be identified
+    reward NUMERIC,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (block_hash, uncle_hash)
+);
+
+CREATE INDEX idx_block_rewards_timestamp ON block_rewards(timestamp);
+CREATE INDEX idx_block_rewards_miner_timestamp ON block_rewards(miner_address, timestamp);
+CREATE INDEX idx_ghost_uncles_uncle_hash ON ghost_uncles(uncle_hash);
+CREATE INDEX idx_ghost_uncles_timestamp ON ghost_uncles(timestamp);
+CREATE INDEX idx_ghost_uncles_miner_timestamp ON ghost_uncles(miner_address, timestamp);
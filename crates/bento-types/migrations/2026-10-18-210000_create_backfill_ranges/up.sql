@@ -0,0 +1,13 @@
+-- Timestamp ranges of a backfill job, claimed one at a time by the backfill processes sharing the job
+CREATE TABLE backfill_ranges (
+    job VARCHAR(100) NOT NULL,
+    from_ts BIGINT NOT NULL,
+    to_ts BIGINT NOT NULL,
+    -- NULL until the range is claimed
+    claimed_by TEXT,
+    claimed_at TIMESTAMP,
+    completed_at TIMESTAMP,
+    PRIMARY KEY (job, from_ts, to_ts)
+);
+
+CREATE INDEX idx_backfill_ranges_pending ON backfill_ranges(job, from_ts) WHERE completed_at IS NULL;
@@ -0,0 +1,11 @@
+-- API keys of bento-server clients. Only the SHA-256 of a key is stored, the key itself is
+-- shown once when it is created.
+CREATE TABLE api_keys (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    -- NULL falls back to the server's default per-key limit
+    requests_per_minute INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    revoked_at TIMESTAMP
+);
@@ -0,0 +1,48 @@
+-- Webhook subscriptions. A notification is delivered to a subscription when it matches every
+-- filter that is set; the secret signs the payloads with HMAC-SHA256.
+CREATE TABLE webhook_subscriptions (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    url TEXT NOT NULL,
+    secret VARCHAR(100) NOT NULL,
+    -- block, event, address_activity or custom
+    notification_type VARCHAR(32),
+    contract_address TEXT,
+    event_index INTEGER,
+    address TEXT,
+    -- Topic of custom notifications
+    topic TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    disabled_at TIMESTAMP
+);
+
+-- Pending deliveries, removed once delivered. A notification enqueued again while its delivery
+-- is still pending is not duplicated; once delivered it is sent again when its blocks are stored
+-- again, e.g. after a restart, and receivers skip it by its notification_key (`x-bento-delivery`).
+CREATE TABLE webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    subscription_id BIGINT NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+    notification_key VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    UNIQUE (subscription_id, notification_key)
+);
+
+CREATE INDEX webhook_deliveries_next_attempt_at_idx ON webhook_deliveries (next_attempt_at);
+
+-- Deliveries that failed every attempt
+CREATE TABLE webhook_dead_letters (
+    id BIGSERIAL PRIMARY KEY,
+    subscription_id BIGINT NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+    notification_key VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL,
+    failed_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC')
+);
+
+CREATE INDEX webhook_dead_letters_subscription_id_idx ON webhook_dead_letters (subscription_id);
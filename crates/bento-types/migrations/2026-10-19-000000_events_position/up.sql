@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Order in which the transaction emitted the event. `event_index` identifies the kind of event
+-- within its contract, so it does not give that order. NULL for events indexed before.
+ALTER TABLE events ADD COLUMN position INTEGER;
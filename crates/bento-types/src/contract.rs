@@ -0,0 +1,265 @@
+//! Typed values of contract state and contract call returns.
+//!
+//! The node encodes fields, arguments and returns as `{ "type": "U256", "value": "1" }`. Fields
+//! are read by index with [`ContractState::imm_field`] and [`ContractState::mut_field`], or by
+//! name with the `fieldsSig` of the contract ABI through [`ContractState::field`].
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{CallContractResult, CallContractResultType, ContractState};
+
+/// A value of the node API.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "value")]
+pub enum Val {
+    Bool(bool),
+    I256(String),
+    U256(String),
+    /// Hex encoded bytes
+    ByteVec(String),
+    /// Base58 address
+    Address(String),
+}
+
+impl Val {
+    /// Decode into `T`, failing when the value has another type
+    pub fn get<T: FromVal>(&self) -> Result<T> {
+        T::from_val(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Val::Bool(_) => "Bool",
+            Val::I256(_) => "I256",
+            Val::U256(_) => "U256",
+            Val::ByteVec(_) => "ByteVec",
+            Val::Address(_) => "Address",
+        }
+    }
+}
+
+/// Types a [`Val`] decodes into.
+pub trait FromVal: Sized {
+    fn from_val(val: &Val) -> Result<Self>;
+}
+
+impl FromVal for Val {
+    fn from_val(val: &Val) -> Result<Self> {
+        Ok(val.clone())
+    }
+}
+
+impl FromVal for bool {
+    fn from_val(val: &Val) -> Result<Self> {
+        match val {
+            Val::Bool(value) => Ok(*value),
+            other => Err(anyhow!("Expected a Bool, got {}", other.type_name())),
+        }
+    }
+}
+
+impl FromVal for BigDecimal {
+    fn from_val(val: &Val) -> Result<Self> {
+        match val {
+            Val::U256(value) | Val::I256(value) => {
+                BigDecimal::from_str(value).map_err(|e| anyhow!("Invalid number {}: {}", value, e))
+            }
+            other => Err(anyhow!("Expected a U256 or I256, got {}", other.type_name())),
+        }
+    }
+}
+
+/// The bytes of a ByteVec as hex, or an address
+impl FromVal for String {
+    fn from_val(val: &Val) -> Result<Self> {
+        match val {
+            Val::ByteVec(value) | Val::Address(value) => Ok(value.clone()),
+            other => Err(anyhow!("Expected a ByteVec or Address, got {}", other.type_name())),
+        }
+    }
+}
+
+macro_rules! from_val_int {
+    ($($int:ty),*) => {$(
+        impl FromVal for $int {
+            fn from_val(val: &Val) -> Result<Self> {
+                match val {
+                    Val::U256(value) | Val::I256(value) => {
+                        value.parse().map_err(|e| anyhow!("Invalid {} {}: {}", stringify!($int), value, e))
+                    }
+                    other => Err(anyhow!("Expected a U256 or I256, got {}", other.type_name())),
+                }
+            }
+        }
+    )*};
+}
+
+from_val_int!(u32, u64, u128, i32, i64, i128);
+
+/// The `fieldsSig` of a contract ABI, as found in compiled artifacts.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldsSig {
+    pub names: Vec<String>,
+    pub types: Vec<String>,
+    pub is_mutable: Vec<bool>,
+}
+
+impl FieldsSig {
+    /// Whether the field `name` is mutable and its first slot in the immutable or mutable
+    /// fields. Fixed size arrays take one slot per element, structs are not supported.
+    fn locate(&self, name: &str) -> Result<(bool, usize, usize)> {
+        let (mut imm_index, mut mut_index) = (0, 0);
+        for ((field, typ), mutable) in self.names.iter().zip(&self.types).zip(&self.is_mutable) {
+            let slots = slots(typ)?;
+            if field == name {
+                return Ok((*mutable, if *mutable { mut_index } else { imm_index }, slots));
+            }
+            match mutable {
+                true => mut_index += slots,
+                false => imm_index += slots,
+            }
+        }
+        Err(anyhow!("No field named {}", name))
+    }
+}
+
+/// Slots taken by a field of type `typ`, e.g. 6 for `[[U256;3];2]`
+fn slots(typ: &str) -> Result<usize> {
+    if let Some(inner) = typ.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        let (element, size) = inner.rsplit_once(';').ok_or_else(|| anyhow!("Invalid array type {}", typ))?;
+        let size: usize = size.trim().parse().map_err(|_| anyhow!("Invalid array type {}", typ))?;
+        return Ok(slots(element.trim())? * size);
+    }
+    match typ {
+        "Bool" | "I256" | "U256" | "ByteVec" | "Address" => Ok(1),
+        other => bail!("Unsupported field type {}, structs are not supported", other),
+    }
+}
+
+impl ContractState {
+    /// Immutable field at `index`
+    pub fn imm_field<T: FromVal>(&self, index: usize) -> Result<T> {
+        field_at(&self.imm_fields, index, "immutable")
+    }
+
+    /// Mutable field at `index`
+    pub fn mut_field<T: FromVal>(&self, index: usize) -> Result<T> {
+        field_at(&self.mut_fields, index, "mutable")
+    }
+
+    /// Field `name` of the contract described by `abi`
+    pub fn field<T: FromVal>(&self, abi: &FieldsSig, name: &str) -> Result<T> {
+        match abi.locate(name)? {
+            (true, index, _) => self.mut_field(index),
+            (false, index, _) => self.imm_field(index),
+        }
+    }
+
+    /// Elements of the fixed size array field `name`, flattened
+    pub fn array_field<T: FromVal>(&self, abi: &FieldsSig, name: &str) -> Result<Vec<T>> {
+        let (mutable, index, slots) = abi.locate(name)?;
+        (index..index + slots).map(|i| if mutable { self.mut_field(i) } else { self.imm_field(i) }).collect()
+    }
+}
+
+fn field_at<T: FromVal>(fields: &[Val], index: usize, kind: &str) -> Result<T> {
+    let val = fields.get(index).ok_or_else(|| anyhow!("No {} field at index {}", kind, index))?;
+    val.get().map_err(|e| e.context(format!("Invalid {} field at index {}", kind, index)))
+}
+
+impl CallContractResult {
+    /// The returned values, failing when the call failed
+    pub fn values(&self) -> Result<Vec<Val>> {
+        if let CallContractResultType::CallContractFailed = self.result_type {
+            bail!("Contract call failed: {}", self.error.as_deref().unwrap_or("unknown error"));
+        }
+        self.returns
+            .iter()
+            .flatten()
+            .map(|value| serde_json::from_value(value.clone()).map_err(|e| anyhow!("Invalid return {}: {}", value, e)))
+            .collect()
+    }
+
+    /// Returned value at `index`
+    pub fn value<T: FromVal>(&self, index: usize) -> Result<T> {
+        field_at(&self.values()?, index, "returned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state() -> ContractState {
+        serde_json::from_value(json!({
+            "address": "contract",
+            "bytecode": "00",
+            "codeHash": "hash",
+            "immFields": [
+                { "type": "ByteVec", "value": "0a" },
+                { "type": "U256", "value": "1" },
+                { "type": "U256", "value": "2" },
+                { "type": "Address", "value": "1EJCtZP3" }
+            ],
+            "mutFields": [
+                { "type": "U256", "value": "1000" },
+                { "type": "Bool", "value": true }
+            ],
+            "asset": { "attoAlphAmount": "100" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fields_by_index() {
+        let state = state();
+        assert_eq!(state.imm_field::<String>(0).unwrap(), "0a");
+        assert_eq!(state.mut_field::<u64>(0).unwrap(), 1000);
+        assert_eq!(state.mut_field::<BigDecimal>(0).unwrap(), BigDecimal::from(1000));
+        assert!(state.mut_field::<bool>(1).unwrap());
+
+        assert!(state.mut_field::<bool>(0).is_err());
+        assert!(state.imm_field::<Val>(4).is_err());
+    }
+
+    #[test]
+    fn test_fields_by_abi() {
+        let abi = FieldsSig {
+            names: vec!["id".into(), "total".into(), "bounds".into(), "paused".into(), "owner".into()],
+            types: vec!["ByteVec".into(), "U256".into(), "[U256;2]".into(), "Bool".into(), "Address".into()],
+            is_mutable: vec![false, true, false, true, false],
+        };
+
+        let state = state();
+        assert_eq!(state.field::<String>(&abi, "id").unwrap(), "0a");
+        assert_eq!(state.field::<u64>(&abi, "total").unwrap(), 1000);
+        assert_eq!(state.array_field::<u64>(&abi, "bounds").unwrap(), vec![1, 2]);
+        assert!(state.field::<bool>(&abi, "paused").unwrap());
+        assert_eq!(state.field::<String>(&abi, "owner").unwrap(), "1EJCtZP3");
+        assert!(state.field::<u64>(&abi, "missing").is_err());
+
+        assert_eq!(slots("[[U256;3];2]").unwrap(), 6);
+        assert!(slots("MarketParams").is_err());
+    }
+
+    #[test]
+    fn test_call_values() {
+        let result: CallContractResult = serde_json::from_value(json!({
+            "type": "CallContractSucceeded",
+            "returns": [{ "type": "U256", "value": "42" }, { "type": "Bool", "value": false }]
+        }))
+        .unwrap();
+        assert_eq!(result.value::<u128>(0).unwrap(), 42);
+        assert!(!result.value::<bool>(1).unwrap());
+
+        let failed: CallContractResult =
+            serde_json::from_value(json!({ "type": "CallContractFailed", "error": "assertion failed" })).unwrap();
+        assert!(failed.values().unwrap_err().to_string().contains("assertion failed"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use bigdecimal::{BigDecimal, Zero};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{BlockModel, ContractInput, EventModel, Output, RichAssetInput, Token, TransactionModel, UnsignedTx};
+
+/// Net change of one token for an address.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct TokenDelta {
+    pub id: String,
+    /// Signed amount, negative when the address spent more than it received
+    #[schema(value_type = String)]
+    pub amount: BigDecimal,
+}
+
+/// Net ALPH and token change of an address in a transaction. The fee is part of the change of
+/// the addresses paying it.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct BalanceChange {
+    pub address: String,
+    /// Signed amount in atto ALPH
+    #[schema(value_type = String)]
+    pub atto_alph_amount: BigDecimal,
+    pub tokens: Vec<TokenDelta>,
+}
+
+/// A transaction with its inputs and outputs folded into per-address balance changes.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DecodedTransaction {
+    pub tx_hash: String,
+    pub block_hash: Option<String>,
+    /// Timestamp of the containing block in milliseconds
+    pub timestamp: Option<i64>,
+    /// Whether the containing block is on the main chain
+    pub main_chain: Option<bool>,
+    pub script_execution_ok: bool,
+    pub gas_amount: i32,
+    #[schema(value_type = String)]
+    pub gas_price: BigDecimal,
+    /// `gas_amount * gas_price` in atto ALPH
+    #[schema(value_type = String)]
+    pub fee: BigDecimal,
+    /// Addresses whose balances changed, sorted by address
+    pub balance_changes: Vec<BalanceChange>,
+    /// Contract events emitted by the transaction, in emission order
+    pub events: Vec<EventModel>,
+}
+
+#[derive(Default)]
+struct Balance {
+    atto_alph_amount: BigDecimal,
+    tokens: BTreeMap<String, BigDecimal>,
+}
+
+fn parse_amount(amount: &str) -> Result<BigDecimal> {
+    BigDecimal::from_str(amount).with_context(|| format!("Invalid amount {amount}"))
+}
+
+impl Balance {
+    fn add(&mut self, atto_alph_amount: &str, tokens: &[Token], sign: i32) -> Result<()> {
+        let sign = BigDecimal::from(sign);
+        self.atto_alph_amount += parse_amount(atto_alph_amount)? * &sign;
+        for token in tokens {
+            *self.tokens.entry(token.id.clone()).or_default() += parse_amount(&token.amount)? * &sign;
+        }
+        Ok(())
+    }
+}
+
+impl DecodedTransaction {
+    /// Decode a stored transaction. Spent asset and contract inputs count against their address,
+    /// fixed and generated outputs for it; addresses without any net change are left out.
+    /// `events` are expected in emission order, as returned by `get_tx_events`.
+    pub fn decode(tx: &TransactionModel, block: Option<&BlockModel>, events: Vec<EventModel>) -> Result<Self> {
+        let unsigned: UnsignedTx = serde_json::from_value(tx.unsigned.clone()).context("Invalid unsigned tx")?;
+        let contract_inputs: Vec<ContractInput> =
+            serde_json::from_value(tx.contract_inputs.clone()).context("Invalid contract inputs")?;
+        let generated_outputs: Vec<Output> =
+            serde_json::from_value(tx.generated_outputs.clone()).context("Invalid generated outputs")?;
+
+        let mut balances: BTreeMap<String, Balance> = BTreeMap::new();
+        for RichAssetInput { address, atto_alph_amount, tokens, .. } in &unsigned.inputs {
+            balances.entry(address.clone()).or_default().add(atto_alph_amount, tokens, -1)?;
+        }
+        for ContractInput { address, atto_alph_amount, tokens, .. } in &contract_inputs {
+            balances.entry(address.clone()).or_default().add(atto_alph_amount, tokens, -1)?;
+        }
+        for output in &unsigned.fixed_outputs {
+            balances.entry(output.address.clone()).or_default().add(&output.atto_alph_amount, &output.tokens, 1)?;
+        }
+        for output in &generated_outputs {
+            balances.entry(output.address.clone()).or_default().add(&output.atto_alph_amount, &output.tokens, 1)?;
+        }
+
+        let balance_changes = balances
+            .into_iter()
+            .map(|(address, balance)| BalanceChange {
+                address,
+                atto_alph_amount: balance.atto_alph_amount,
+                tokens: balance
+                    .tokens
+                    .into_iter()
+                    .filter(|(_, amount)| !amount.is_zero())
+                    .map(|(id, amount)| TokenDelta { id, amount })
+                    .collect(),
+            })
+            .filter(|change| !change.atto_alph_amount.is_zero() || !change.tokens.is_empty())
+            .collect();
+
+        let gas_price = parse_amount(&unsigned.gas_price)?;
+        let fee = &gas_price * BigDecimal::from(unsigned.gas_amount);
+
+        Ok(Self {
+            tx_hash: tx.tx_hash.clone(),
+            block_hash: tx.block_hash.clone(),
+            timestamp: block.map(|b| b.timestamp.and_utc().timestamp_millis()),
+            main_chain: block.map(|b| b.main_chain),
+            script_execution_ok: tx.script_execution_ok,
+            gas_amount: unsigned.gas_amount,
+            gas_price,
+            fee,
+            balance_changes,
+            events,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn token(id: &str, amount: &str) -> serde_json::Value {
+        json!({ "id": id, "amount": amount })
+    }
+
+    #[test]
+    fn test_decode_balance_changes_and_fee() {
+        let unsigned = json!({
+            "txId": "tx1",
+            "version": 0,
+            "networkId": 0,
+            "scriptOpt": null,
+            "gasAmount": 20000,
+            "gasPrice": "100000000000",
+            "inputs": [{
+                "hint": 0, "key": "in1", "unlockScript": "",
+                "attoAlphAmount": "10000000000000000000", "address": "alice",
+                "tokens": [token("usdt", "500")]
+            }],
+            "fixedOutputs": [
+                {
+                    "hint": 0, "key": "out1", "attoAlphAmount": "3000000000000000000", "address": "bob",
+                    "tokens": [token("usdt", "200")], "lockTime": 0, "message": ""
+                },
+                {
+                    "hint": 0, "key": "out2", "attoAlphAmount": "6998000000000000000", "address": "alice",
+                    "tokens": [token("usdt", "300")], "lockTime": 0, "message": ""
+                }
+            ]
+        });
+        let tx = TransactionModel {
+            tx_hash: "tx1".to_string(),
+            unsigned,
+            script_execution_ok: true,
+            contract_inputs: json!([]),
+            generated_outputs: json!([]),
+            input_signatures: vec![],
+            script_signatures: vec![],
+            block_hash: Some("block1".to_string()),
+        };
+
+        let decoded = DecodedTransaction::decode(&tx, None, vec![]).unwrap();
+        assert_eq!(decoded.fee, BigDecimal::from_str("2000000000000000").unwrap());
+        assert_eq!(decoded.timestamp, None);
+        assert_eq!(decoded.balance_changes.len(), 2);
+
+        let alice = &decoded.balance_changes[0];
+        assert_eq!(alice.address, "alice");
+        assert_eq!(alice.atto_alph_amount, BigDecimal::from_str("-3002000000000000000").unwrap());
+        assert_eq!(alice.tokens, vec![TokenDelta { id: "usdt".to_string(), amount: BigDecimal::from(-200) }]);
+
+        let bob = &decoded.balance_changes[1];
+        assert_eq!(bob.atto_alph_amount, BigDecimal::from_str("3000000000000000000").unwrap());
+        assert_eq!(bob.tokens, vec![TokenDelta { id: "usdt".to_string(), amount: BigDecimal::from(200) }]);
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -31,6 +31,9 @@ pub enum AppError {
 
     // Bad request errors
     BadRequest(String),
+
+    // Rate limit errors, with the seconds to wait before retrying
+    TooManyRequests(u64),
 }
 
 // Implement Display for AppError
@@ -44,6 +47,7 @@ impl fmt::Display for AppError {
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
             AppError::Forbidden(msg) => write!(f, "{}", msg),
             AppError::BadRequest(msg) => write!(f, "{}", msg),
+            AppError::TooManyRequests(retry_after) => write!(f, "Too many requests, retry in {}s", retry_after),
         }
     }
 }
@@ -64,21 +68,56 @@ impl IntoResponse for AppError {
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, format!("Unauthorized: {}", msg)),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, format!("Forbidden: {}", msg)),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, format!("Bad request: {}", msg)),
+            AppError::TooManyRequests(retry_after) => {
+                let message = format!("Too many requests, retry in {}s", retry_after);
+                let mut response = error_response(StatusCode::TOO_MANY_REQUESTS, message);
+                response.headers_mut().insert(header::RETRY_AFTER, retry_after.into());
+                return response;
+            }
         };
 
-        // Create a JSON response with error details
-        let body = Json(json!({
+        error_response(status, error_message)
+    }
+}
+
+/// JSON body of an [`AppError`] response, also kept in the response extensions so a server
+/// layer can add request details such as the request id to it.
+#[derive(Debug, Clone)]
+pub struct ErrorBody(pub serde_json::Value);
+
+impl ErrorBody {
+    pub fn new(status: StatusCode, error_message: String) -> Self {
+        Self(json!({
             "success": false,
             "error": {
                 "message": error_message,
                 "code": status.as_u16()
             }
-        }));
+        }))
+    }
 
-        (status, body).into_response()
+    /// Add `request_id` to the `error` object.
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        if let Some(error) = self.0.get_mut("error").and_then(|e| e.as_object_mut()) {
+            error.insert("request_id".to_string(), request_id.into());
+        }
+        self
     }
 }
 
+impl IntoResponse for ErrorBody {
+    fn into_response(self) -> Response {
+        let mut response = Json(self.0.clone()).into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+// Create a JSON response with error details
+fn error_response(status: StatusCode, error_message: String) -> Response {
+    (status, ErrorBody::new(status, error_message)).into_response()
+}
+
 // Implement the conversion from anyhow::Error to AppError
 impl From<anyhow::Error> for AppError {
     fn from(error: anyhow::Error) -> Self {
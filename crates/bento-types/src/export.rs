@@ -0,0 +1,742 @@
+//! Bulk export of registered tables as CSV, NDJSON or Parquet.
+//!
+//! Rows of a time window are read in pages of [`EXPORT_BATCH_SIZE`] rows ordered by time and key,
+//! each page continuing after the last row of the previous one. A page is a single query, so a
+//! slow download holds neither a pooled connection nor a snapshot between pages:
+//!
+//! ```ignore
+//! let tables = ExportTables::core().table(ExportTable::new("lending_events", "block_time"));
+//! let table = tables.get("lending_events").unwrap();
+//! let columns = load_export_columns(&db, table).await?;
+//! let mut chunks = ExportPlan::new(table.clone(), columns, request)?.spawn(db);
+//! while let Some(chunk) = chunks.recv().await {
+//!     output.write_all(&chunk?).await?;
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, NaiveDateTime};
+use diesel::sql_types::{Text, Timestamp};
+use diesel::QueryableByName;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use utoipa::ToSchema;
+
+use crate::DbPool;
+
+/// Rows fetched and encoded at once.
+pub const EXPORT_BATCH_SIZE: usize = 10_000;
+
+/// Encoded batches waiting to be written before the export pauses.
+const CHANNEL_CAPACITY: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    #[default]
+    Ndjson,
+    /// Requires the `parquet` feature
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            "parquet" => Ok(ExportFormat::Parquet),
+            other => Err(format!("Unknown export format {other}, expected csv, ndjson or parquet")),
+        }
+    }
+}
+
+/// A table that can be exported.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ExportTable {
+    pub name: String,
+    /// SQL expression over the table giving the time of a row, a `timestamp` or `date`
+    pub time_column: String,
+    /// Column unique per row, ordering the rows of the same time. `id` unless set.
+    pub key_column: String,
+    /// Joins bringing in the tables read by `time_column`, e.g. the block of a transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join: Option<String>,
+}
+
+impl ExportTable {
+    pub fn new(name: impl Into<String>, time_column: impl Into<String>) -> Self {
+        Self { name: name.into(), time_column: time_column.into(), key_column: "id".to_string(), join: None }
+    }
+
+    pub fn with_key(mut self, key_column: impl Into<String>) -> Self {
+        self.key_column = key_column.into();
+        self
+    }
+
+    /// Time the rows with the columns of other tables, joined once per page rather than looked
+    /// up per row. `time_column` should then be qualified, and indexed on the joined table.
+    pub fn with_join(mut self, join: impl Into<String>) -> Self {
+        self.join = Some(join.into());
+        self
+    }
+}
+
+/// Tables that can be exported, by name.
+#[derive(Debug, Clone, Default)]
+pub struct ExportTables {
+    tables: BTreeMap<String, ExportTable>,
+}
+
+impl ExportTables {
+    /// The core blocks, transactions and events tables, the latter two timed by their block
+    /// through the `blocks.timestamp` index
+    pub fn core() -> Self {
+        Self::default()
+            .table(ExportTable::new("blocks", "timestamp").with_key("hash"))
+            .table(
+                ExportTable::new("transactions", "b.timestamp")
+                    .with_key("tx_hash")
+                    .with_join("JOIN blocks b ON b.hash = transactions.block_hash"),
+            )
+            .table(
+                ExportTable::new("events", "b.timestamp").with_join(
+                    "JOIN transactions t ON t.tx_hash = events.tx_id JOIN blocks b ON b.hash = t.block_hash",
+                ),
+            )
+    }
+
+    pub fn table(mut self, table: ExportTable) -> Self {
+        self.tables.insert(table.name.clone(), table);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ExportTable> {
+        self.tables.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ExportTable> {
+        self.tables.values()
+    }
+}
+
+/// Column of an exported table, as reported by `information_schema.columns`.
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct ExportColumn {
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = Text)]
+    pub data_type: String,
+}
+
+impl ExportColumn {
+    pub fn new(name: &str, data_type: &str) -> Self {
+        Self { name: name.to_string(), data_type: data_type.to_string() }
+    }
+
+    /// Whether the values keep their type in every format. Numerics are exported as strings to
+    /// keep their precision, and other types as text in Parquet.
+    fn is_primitive(&self) -> bool {
+        matches!(
+            self.data_type.as_str(),
+            "bigint"
+                | "integer"
+                | "smallint"
+                | "boolean"
+                | "double precision"
+                | "real"
+                | "timestamp without time zone"
+                | "timestamp with time zone"
+                | "date"
+                | "text"
+                | "character varying"
+                | "character"
+        )
+    }
+
+    /// The column of `table`, qualified as the time and key expressions of the query use the
+    /// same names.
+    fn select_expr(&self, table: &str, format: ExportFormat) -> String {
+        let cast = self.data_type == "numeric" || (format == ExportFormat::Parquet && !self.is_primitive());
+        let name = quote_ident(&self.name);
+        let column = format!("{}.{name}", quote_ident(table));
+        if cast {
+            format!("{column}::text AS {name}")
+        } else {
+            format!("{column} AS {name}")
+        }
+    }
+}
+
+/// Columns of `table` in table order.
+pub async fn load_export_columns(db: &DbPool, table: &ExportTable) -> Result<Vec<ExportColumn>> {
+    let mut conn = db.get().await?;
+    let columns: Vec<ExportColumn> = diesel::sql_query(
+        r#"
+        SELECT column_name::text AS name, data_type::text AS data_type
+        FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = $1
+        ORDER BY ordinal_position
+        "#,
+    )
+    .bind::<Text, _>(&table.name)
+    .load(&mut conn)
+    .await?;
+
+    if columns.is_empty() {
+        bail!("Table {} does not exist", table.name);
+    }
+    Ok(columns)
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Rows to export.
+#[derive(Debug, Clone, Default)]
+pub struct ExportRequest {
+    /// Start of the window in milliseconds, inclusive
+    pub from_ts: i64,
+    /// End of the window in milliseconds, exclusive
+    pub to_ts: i64,
+    /// Exact matches on the text value of columns
+    pub filters: BTreeMap<String, String>,
+    pub format: ExportFormat,
+}
+
+fn to_timestamp(ts: i64) -> Result<NaiveDateTime> {
+    DateTime::from_timestamp_millis(ts).map(|t| t.naive_utc()).ok_or_else(|| anyhow!("Invalid timestamp {ts}"))
+}
+
+/// A validated export, run with [`ExportPlan::spawn`].
+#[derive(Debug, Clone)]
+pub struct ExportPlan {
+    table: ExportTable,
+    columns: Vec<ExportColumn>,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    filters: BTreeMap<String, String>,
+    format: ExportFormat,
+    page_size: usize,
+}
+
+#[derive(QueryableByName)]
+struct ExportRow {
+    #[diesel(sql_type = Text)]
+    json: String,
+    #[diesel(sql_type = Timestamp)]
+    cursor_time: NaiveDateTime,
+    #[diesel(sql_type = Text)]
+    cursor_key: String,
+}
+
+impl ExportPlan {
+    /// Check the window and the filtered columns of `request` against the `columns` of `table`.
+    pub fn new(table: ExportTable, columns: Vec<ExportColumn>, request: ExportRequest) -> Result<Self> {
+        if request.from_ts >= request.to_ts {
+            bail!("The start of the window must be before its end");
+        }
+        if let Some(name) = request.filters.keys().find(|name| !columns.iter().any(|c| &c.name == *name)) {
+            bail!("Unknown column {name} in table {}", table.name);
+        }
+        if !columns.iter().any(|c| c.name == table.key_column) {
+            bail!("Unknown key column {} in table {}", table.key_column, table.name);
+        }
+        if cfg!(not(feature = "parquet")) && request.format == ExportFormat::Parquet {
+            bail!("Parquet exports require the parquet feature");
+        }
+        Ok(Self {
+            table,
+            columns,
+            from: to_timestamp(request.from_ts)?,
+            to: to_timestamp(request.to_ts)?,
+            filters: request.filters,
+            format: request.format,
+            page_size: EXPORT_BATCH_SIZE,
+        })
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    /// Query selecting a page of rows as JSON objects with their time and key, binding the window,
+    /// the filter values and, after the first page, the time and key of the last exported row.
+    fn sql(&self, after_cursor: bool) -> String {
+        let columns: Vec<String> = self.columns.iter().map(|c| c.select_expr(&self.table.name, self.format)).collect();
+        let table = quote_ident(&self.table.name);
+        let time = &self.table.time_column;
+        let key = format!("{table}.{}", quote_ident(&self.table.key_column));
+        let mut conditions = vec![format!("{time} >= $1"), format!("{time} < $2")];
+        for (i, name) in self.filters.keys().enumerate() {
+            conditions.push(format!("{table}.{}::text = ${}", quote_ident(name), i + 3));
+        }
+        if after_cursor {
+            let n = self.filters.len() + 3;
+            let key_type =
+                self.columns.iter().find(|c| c.name == self.table.key_column).map_or("text", |c| &c.data_type);
+            // The plain bound lets an index on the time start at the cursor
+            conditions.push(format!("{time} >= ${n}"));
+            conditions.push(format!("({time}, {key}) > (${n}, ${}::{key_type})", n + 1));
+        }
+        let from = match &self.table.join {
+            Some(join) => format!("{table} {join}"),
+            None => table,
+        };
+
+        format!(
+            "SELECT (SELECT row_to_json(r) FROM (SELECT {}) r)::text AS json, {time}::timestamp AS cursor_time, \
+             {key}::text AS cursor_key FROM {from} WHERE {} ORDER BY {time}, {key} LIMIT {}",
+            columns.join(", "),
+            conditions.join(" AND "),
+            self.page_size,
+        )
+    }
+
+    /// Run the export in a task sending the encoded chunks. The export stops when the receiver
+    /// is dropped; a failure is sent as the last item.
+    pub fn spawn(self, db: Arc<DbPool>) -> mpsc::Receiver<Result<Vec<u8>>> {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            if let Err(err) = self.run(&db, &sender).await {
+                if !sender.is_closed() {
+                    tracing::error!(table = %self.table.name, error = ?err, "Export failed");
+                    let _ = sender.send(Err(err)).await;
+                }
+            }
+        });
+        receiver
+    }
+
+    async fn run(&self, db: &DbPool, sender: &mpsc::Sender<Result<Vec<u8>>>) -> Result<()> {
+        let mut encoder = Encoder::new(self.format, &self.columns)?;
+        let mut cursor: Option<(NaiveDateTime, String)> = None;
+
+        loop {
+            let rows = self.load_page(db, cursor.as_ref()).await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            cursor = Some((last.cursor_time, last.cursor_key.clone()));
+            let full_page = rows.len() == self.page_size;
+            let rows: Vec<String> = rows.into_iter().map(|r| r.json).collect();
+            send(sender, encoder.encode(&rows)?).await?;
+            if !full_page {
+                break;
+            }
+        }
+
+        send(sender, encoder.finish()?).await
+    }
+
+    /// The rows after `cursor`, on a connection released before they are sent.
+    async fn load_page(&self, db: &DbPool, cursor: Option<&(NaiveDateTime, String)>) -> Result<Vec<ExportRow>> {
+        let mut query = diesel::sql_query(self.sql(cursor.is_some()))
+            .into_boxed::<diesel::pg::Pg>()
+            .bind::<Timestamp, _>(self.from)
+            .bind::<Timestamp, _>(self.to);
+        for value in self.filters.values() {
+            query = query.bind::<Text, _>(value.clone());
+        }
+        if let Some((time, key)) = cursor {
+            query = query.bind::<Timestamp, _>(*time).bind::<Text, _>(key.clone());
+        }
+
+        let mut conn = db.get().await?;
+        Ok(query.load(&mut conn).await?)
+    }
+}
+
+async fn send(sender: &mpsc::Sender<Result<Vec<u8>>>, chunk: Vec<u8>) -> Result<()> {
+    if chunk.is_empty() {
+        return Ok(());
+    }
+    sender.send(Ok(chunk)).await.map_err(|_| anyhow!("Export cancelled"))
+}
+
+/// Encodes rows selected as JSON objects.
+enum Encoder {
+    Csv {
+        columns: Vec<String>,
+        header_written: bool,
+    },
+    Ndjson,
+    #[cfg(feature = "parquet")]
+    Parquet(Box<parquet_encoder::ParquetEncoder>),
+}
+
+impl Encoder {
+    fn new(format: ExportFormat, columns: &[ExportColumn]) -> Result<Self> {
+        match format {
+            ExportFormat::Csv => {
+                Ok(Encoder::Csv { columns: columns.iter().map(|c| c.name.clone()).collect(), header_written: false })
+            }
+            ExportFormat::Ndjson => Ok(Encoder::Ndjson),
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => Ok(Encoder::Parquet(Box::new(parquet_encoder::ParquetEncoder::new(columns)?))),
+            #[cfg(not(feature = "parquet"))]
+            ExportFormat::Parquet => bail!("Parquet exports require the parquet feature"),
+        }
+    }
+
+    fn encode(&mut self, rows: &[String]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        match self {
+            Encoder::Csv { columns, header_written } => {
+                if !*header_written {
+                    write_csv_header(&mut out, columns);
+                    *header_written = true;
+                }
+                for row in rows {
+                    let values: serde_json::Map<String, serde_json::Value> =
+                        serde_json::from_str(row).context("Invalid exported row")?;
+                    let fields: Vec<String> = columns.iter().map(|c| csv_field(values.get(c))).collect();
+                    out.extend_from_slice(fields.join(",").as_bytes());
+                    out.push(b'\n');
+                }
+            }
+            Encoder::Ndjson => {
+                for row in rows {
+                    out.extend_from_slice(row.as_bytes());
+                    out.push(b'\n');
+                }
+            }
+            #[cfg(feature = "parquet")]
+            Encoder::Parquet(encoder) => out = encoder.encode(rows)?,
+        }
+        Ok(out)
+    }
+
+    fn finish(self) -> Result<Vec<u8>> {
+        match self {
+            Encoder::Csv { columns, header_written: false } => {
+                let mut out = Vec::new();
+                write_csv_header(&mut out, &columns);
+                Ok(out)
+            }
+            Encoder::Csv { .. } | Encoder::Ndjson => Ok(Vec::new()),
+            #[cfg(feature = "parquet")]
+            Encoder::Parquet(encoder) => encoder.finish(),
+        }
+    }
+}
+
+fn write_csv_header(out: &mut Vec<u8>, columns: &[String]) {
+    let fields: Vec<String> = columns.iter().map(|c| csv_escape(c)).collect();
+    out.extend_from_slice(fields.join(",").as_bytes());
+    out.push(b'\n');
+}
+
+/// CSV field of a JSON value: strings as is, nulls empty, objects and arrays as JSON.
+fn csv_field(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(s)) => csv_escape(s),
+        Some(other) => csv_escape(&other.to_string()),
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_encoder {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Result;
+    use arrow_json::reader::{Decoder, ReaderBuilder};
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+
+    use super::{ExportColumn, EXPORT_BATCH_SIZE};
+
+    /// Bytes written by the Parquet writer, taken after every row group.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn take(&self) -> Vec<u8> {
+            std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn data_type(column: &ExportColumn) -> DataType {
+        match column.data_type.as_str() {
+            "bigint" => DataType::Int64,
+            "integer" => DataType::Int32,
+            "smallint" => DataType::Int16,
+            "boolean" => DataType::Boolean,
+            "double precision" => DataType::Float64,
+            "real" => DataType::Float32,
+            "timestamp without time zone" => DataType::Timestamp(TimeUnit::Microsecond, None),
+            "timestamp with time zone" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            "date" => DataType::Date32,
+            _ => DataType::Utf8,
+        }
+    }
+
+    /// Writes one row group per encoded batch.
+    pub(super) struct ParquetEncoder {
+        decoder: Decoder,
+        writer: ArrowWriter<SharedBuffer>,
+        buffer: SharedBuffer,
+    }
+
+    impl ParquetEncoder {
+        pub(super) fn new(columns: &[ExportColumn]) -> Result<Self> {
+            let fields: Vec<Field> = columns.iter().map(|c| Field::new(&c.name, data_type(c), true)).collect();
+            let schema = Arc::new(Schema::new(fields));
+            let decoder = ReaderBuilder::new(schema.clone()).with_batch_size(EXPORT_BATCH_SIZE).build_decoder()?;
+            let buffer = SharedBuffer::default();
+            let writer = ArrowWriter::try_new(buffer.clone(), schema, None)?;
+            Ok(Self { decoder, writer, buffer })
+        }
+
+        fn write_decoded(&mut self) -> Result<()> {
+            if let Some(batch) = self.decoder.flush()? {
+                self.writer.write(&batch)?;
+            }
+            Ok(())
+        }
+
+        pub(super) fn encode(&mut self, rows: &[String]) -> Result<Vec<u8>> {
+            for row in rows {
+                let mut bytes = row.as_bytes();
+                while !bytes.is_empty() {
+                    let read = self.decoder.decode(bytes)?;
+                    if read == 0 {
+                        // The decoder holds a full batch
+                        self.write_decoded()?;
+                    }
+                    bytes = &bytes[read..];
+                }
+            }
+            self.write_decoded()?;
+            self.writer.flush()?;
+            Ok(self.buffer.take())
+        }
+
+        pub(super) fn finish(mut self) -> Result<Vec<u8>> {
+            self.write_decoded()?;
+            self.writer.close()?;
+            Ok(self.buffer.take())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(format: ExportFormat, filters: &[(&str, &str)]) -> Result<ExportPlan> {
+        let columns = vec![
+            ExportColumn::new("id", "bigint"),
+            ExportColumn::new("address", "text"),
+            ExportColumn::new("amount", "numeric"),
+            ExportColumn::new("fields", "jsonb"),
+        ];
+        let request = ExportRequest {
+            from_ts: 0,
+            to_ts: 1_000,
+            filters: filters.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            format,
+        };
+        ExportPlan::new(ExportTable::new("lending_events", "block_time"), columns, request)
+    }
+
+    #[test]
+    fn test_plan_sql() {
+        let export = plan(ExportFormat::Ndjson, &[("address", "alice")]).unwrap();
+        assert_eq!(
+            export.sql(false),
+            "SELECT (SELECT row_to_json(r) FROM (SELECT \"lending_events\".\"id\" AS \"id\", \"lending_events\".\"address\" \
+             AS \"address\", \"lending_events\".\"amount\"::text AS \"amount\", \"lending_events\".\"fields\" AS \
+             \"fields\") r)::text AS json, block_time::timestamp AS cursor_time, \"lending_events\".\"id\"::text AS \
+             cursor_key FROM \"lending_events\" WHERE block_time >= $1 AND block_time < $2 AND \
+             \"lending_events\".\"address\"::text = $3 ORDER BY block_time, \"lending_events\".\"id\" LIMIT 10000"
+        );
+
+        // Later pages continue after the last exported row
+        assert!(export
+            .sql(true)
+            .contains("AND block_time >= $4 AND (block_time, \"lending_events\".\"id\") > ($4, $5::bigint) ORDER BY"));
+
+        // Parquet columns hold primitives or text
+        #[cfg(feature = "parquet")]
+        assert!(plan(ExportFormat::Parquet, &[])
+            .unwrap()
+            .sql(false)
+            .contains("\"lending_events\".\"fields\"::text AS \"fields\""));
+        #[cfg(not(feature = "parquet"))]
+        assert!(plan(ExportFormat::Parquet, &[]).is_err());
+
+        assert!(plan(ExportFormat::Csv, &[("unknown", "x")]).is_err());
+    }
+
+    #[test]
+    fn test_joined_table_sql() {
+        let table = ExportTables::core().get("transactions").unwrap().clone();
+        let columns = vec![ExportColumn::new("tx_hash", "text"), ExportColumn::new("block_hash", "text")];
+        let request = ExportRequest { from_ts: 0, to_ts: 1_000, ..Default::default() };
+        let sql = ExportPlan::new(table, columns, request).unwrap().sql(true);
+
+        // Blocks are joined once instead of looked up per row
+        assert!(sql.contains(
+            "FROM \"transactions\" JOIN blocks b ON b.hash = transactions.block_hash WHERE b.timestamp >= $1 AND \
+             b.timestamp < $2 AND b.timestamp >= $3 AND (b.timestamp, \"transactions\".\"tx_hash\") > ($3, $4::text) \
+             ORDER BY b.timestamp, \"transactions\".\"tx_hash\""
+        ));
+        assert!(!sql.contains("(SELECT b.timestamp"));
+    }
+
+    #[test]
+    fn test_csv_encoding() {
+        let columns = vec![ExportColumn::new("id", "bigint"), ExportColumn::new("memo", "text")];
+        let mut encoder = Encoder::new(ExportFormat::Csv, &columns).unwrap();
+        let rows =
+            vec![r#"{"id": 1, "memo": "a, \"quoted\" memo"}"#.to_string(), r#"{"id": 2, "memo": null}"#.to_string()];
+
+        let out = String::from_utf8(encoder.encode(&rows).unwrap()).unwrap();
+        assert_eq!(out, "id,memo\n1,\"a, \"\"quoted\"\" memo\"\n2,\n");
+        assert!(encoder.finish().unwrap().is_empty());
+
+        // An empty export still has its header
+        let encoder = Encoder::new(ExportFormat::Csv, &columns).unwrap();
+        assert_eq!(encoder.finish().unwrap(), b"id,memo\n");
+    }
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_export_pages() {
+        use crate::repository::test_utils::{cleanup, create_test_pool, insert_test_block};
+
+        let blocks = ["test-export-a", "test-export-b", "test-export-c"];
+        let db = create_test_pool().await;
+        cleanup(&db, &[], &blocks).await;
+        for hash in blocks {
+            insert_test_block(&db, hash, true).await;
+        }
+        // The same time for all, so pages continue on the key
+        let time = DateTime::from_timestamp_millis(1_000).unwrap().naive_utc();
+        diesel::sql_query("UPDATE blocks SET timestamp = $1 WHERE hash LIKE 'test-export-%'")
+            .bind::<Timestamp, _>(time)
+            .execute(&mut db.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let tables = ExportTables::core();
+        let table = tables.get("blocks").unwrap();
+        let columns = load_export_columns(&db, table).await.unwrap();
+        let request = ExportRequest { from_ts: 1_000, to_ts: 1_001, ..Default::default() };
+        let mut export = ExportPlan::new(table.clone(), columns, request).unwrap();
+        export.page_size = 2;
+
+        let mut chunks = export.spawn(db.clone());
+        let mut out = Vec::new();
+        while let Some(chunk) = chunks.recv().await {
+            out.extend(chunk.unwrap());
+        }
+        let hashes: Vec<String> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["hash"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(hashes, blocks);
+
+        cleanup(&db, &[], &blocks).await;
+    }
+
+    /// Runs `table` over the test window in pages of one row and returns the `key` of each row
+    async fn export_keys(db: &Arc<DbPool>, table: &str, key: &str) -> Vec<String> {
+        let tables = ExportTables::core();
+        let table = tables.get(table).unwrap();
+        let columns = load_export_columns(db, table).await.unwrap();
+        let request = ExportRequest { from_ts: 1_000, to_ts: 1_001, ..Default::default() };
+        let mut export = ExportPlan::new(table.clone(), columns, request).unwrap();
+        export.page_size = 1;
+
+        let mut chunks = export.spawn(db.clone());
+        let mut out = Vec::new();
+        while let Some(chunk) = chunks.recv().await {
+            out.extend(chunk.unwrap());
+        }
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()[key].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_export_joined_tables() {
+        use crate::repository::test_utils::{cleanup, create_test_pool, insert_test_block};
+
+        let block = "test-export-joined";
+        let db = create_test_pool().await;
+        let mut conn = db.get().await.unwrap();
+        diesel::sql_query("DELETE FROM events WHERE id LIKE 'test-export-%'").execute(&mut conn).await.unwrap();
+        cleanup(&db, &["transactions"], &[block]).await;
+        insert_test_block(&db, block, true).await;
+        for statement in [
+            "UPDATE blocks SET timestamp = to_timestamp(1)::timestamp WHERE hash = 'test-export-joined'",
+            "INSERT INTO transactions (tx_hash, unsigned, script_execution_ok, contract_inputs, generated_outputs, \
+             input_signatures, script_signatures, main_chain, block_hash) VALUES \
+             ('test-export-tx-a', '{}', true, '[]', '[]', '{}', '{}', true, 'test-export-joined'), \
+             ('test-export-tx-b', '{}', true, '[]', '[]', '{}', '{}', true, 'test-export-joined')",
+            "INSERT INTO events (id, tx_id, contract_address, event_index, fields) VALUES \
+             ('test-export-event', 'test-export-tx-b', 'contract', 0, '[]')",
+        ] {
+            diesel::sql_query(statement).execute(&mut conn).await.unwrap();
+        }
+
+        // Paged on the time of their block
+        assert_eq!(export_keys(&db, "transactions", "tx_hash").await, vec!["test-export-tx-a", "test-export-tx-b"]);
+        assert_eq!(export_keys(&db, "events", "id").await, vec!["test-export-event"]);
+
+        diesel::sql_query("DELETE FROM events WHERE id LIKE 'test-export-%'").execute(&mut conn).await.unwrap();
+        cleanup(&db, &["transactions"], &[block]).await;
+    }
+}
@@ -1,10 +1,15 @@
 pub mod config;
+pub mod contract;
+pub mod decoded;
 pub mod errors;
+pub mod export;
 pub mod models;
 pub mod network;
+pub mod notifications;
 pub mod processors;
 pub mod repository;
 pub mod schema;
+pub mod tx_builder;
 pub mod utils;
 
 use std::fmt::Debug;
@@ -32,6 +37,11 @@ pub type GroupIndex = i64;
 pub trait CustomProcessorOutput: Send + Sync + Debug + 'static {
     fn as_any(&self) -> &dyn std::any::Any;
     fn clone_box(&self) -> Box<dyn CustomProcessorOutput>;
+
+    /// Notifications published to stream subscribers once the output is stored
+    fn notifications(&self) -> Vec<notifications::IndexedNotification> {
+        Vec::new()
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -285,6 +295,16 @@ pub struct BlockRange {
     pub to_ts: u64,
 }
 
+impl BlockRange {
+    /// Inclusive bounds of the range, for filtering stored rows by block timestamp
+    pub fn as_datetimes(&self) -> (chrono::NaiveDateTime, chrono::NaiveDateTime) {
+        (
+            utils::timestamp_millis_to_naive_datetime(self.from_ts as i64),
+            utils::timestamp_millis_to_naive_datetime(self.to_ts as i64),
+        )
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct BlockBatch {
     pub blocks: Vec<BlockAndEvents>,
@@ -321,6 +341,18 @@ pub enum CallContractResultType {
     CallContractFailed,
 }
 
+/// Body of `POST /contracts/multicall-contract`
+#[derive(Serialize, Debug)]
+pub struct MultipleCallContract<'a> {
+    pub calls: &'a [CallContractParams],
+}
+
+/// Response of `POST /contracts/multicall-contract`, one result per call in order
+#[derive(Deserialize, Debug)]
+pub struct MultipleCallContractResult {
+    pub results: Vec<CallContractResult>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CallContractResult {
@@ -340,6 +372,12 @@ pub struct CallContractResult {
 #[serde(rename_all = "camelCase")]
 pub struct ContractState {
     pub address: String,
+    /// Immutable fields, see [`contract`] to decode them
+    #[serde(default)]
+    pub imm_fields: Vec<contract::Val>,
+    /// Mutable fields, see [`contract`] to decode them
+    #[serde(default)]
+    pub mut_fields: Vec<contract::Val>,
     pub asset: ContractAsset,
 }
 
@@ -357,6 +395,22 @@ pub struct ContractToken {
     pub amount: String,
 }
 
+/// Status of a transaction as reported by the node's `transactions/status`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum TxStatus {
+    #[serde(rename_all = "camelCase")]
+    Confirmed {
+        block_hash: String,
+        tx_index: i32,
+        chain_confirmations: i32,
+        from_group_confirmations: i32,
+        to_group_confirmations: i32,
+    },
+    MemPooled,
+    TxNotFound,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -516,4 +570,199 @@ mod tests {
         let field = &event.fields[2];
         assert_eq!(field.field_type, EventFieldType::ByteVec);
     }
+
+    #[test]
+    fn test_convert_bwe_to_event_models() {
+        let event = |tx_id: &str, event_index: i32| json!({ "txId": tx_id, "contractAddress": "contract", "eventIndex": event_index, "fields": [] });
+        let json_data = json!({
+            "block": {
+                "hash": "blockhash123",
+                "parent": "parent_hash",
+                "mainChain": true,
+                "timestamp": 1672531200000u64,
+                "chainFrom": 0,
+                "chainTo": 0,
+                "height": 1000,
+                "deps": [],
+                "transactions": [],
+                "nonce": "nonce_value",
+                "version": 1,
+                "depStateHash": "dep_hash",
+                "txsHash": "txs_hash",
+                "target": "target_value",
+                "ghostUncles": []
+            },
+            "events": [event("tx1", 3), event("tx2", 0), event("tx1", 1)]
+        });
+
+        let bwe: BlockAndEvents = serde_json::from_value(json_data).unwrap();
+        let events = convert_bwe_to_event_models(vec![bwe]);
+
+        // Positions count the events of each transaction in the order the node lists them
+        let positions: Vec<_> = events.iter().map(|e| (e.tx_id.as_str(), e.event_index, e.position)).collect();
+        assert_eq!(positions, vec![("tx1", 3, Some(0)), ("tx2", 0, Some(0)), ("tx1", 1, Some(1))]);
+    }
+
+    #[test]
+    fn test_convert_bwe_to_address_models() {
+        let json_data = json!({
+            "block": {
+                "hash": "blockhash123",
+                "parent": "parent_hash",
+                "mainChain": true,
+                "timestamp": 1672531200000u64,
+                "chainFrom": 0,
+                "chainTo": 0,
+                "height": 1000,
+                "deps": [],
+                "transactions": [{
+                    "unsigned": {
+                        "txId": "tx123",
+                        "version": 0,
+                        "networkId": 1,
+                        "gasAmount": 20000,
+                        "gasPrice": "100000000000",
+                        "inputs": [{
+                            "hint": 1,
+                            "key": "spent_key",
+                            "unlockScript": "00",
+                            "attoAlphAmount": "3000",
+                            "address": "sender",
+                            "tokens": []
+                        }],
+                        "fixedOutputs": [{
+                            "hint": 2,
+                            "key": "paid_key",
+                            "attoAlphAmount": "2000",
+                            "address": "receiver",
+                            "tokens": [{ "id": "token1", "amount": "5" }],
+                            "lockTime": 1672531300000u64,
+                            "message": ""
+                        }]
+                    },
+                    "scriptExecutionOk": true,
+                    "contractInputs": [],
+                    "generatedOutputs": [{
+                        "type": "AssetOutput",
+                        "hint": 3,
+                        "key": "generated_key",
+                        "attoAlphAmount": "1000",
+                        "address": "sender",
+                        "tokens": []
+                    }],
+                    "inputSignatures": [],
+                    "scriptSignatures": []
+                }],
+                "nonce": "nonce_value",
+                "version": 1,
+                "depStateHash": "dep_hash",
+                "txsHash": "txs_hash",
+                "target": "target_value",
+                "ghostUncles": []
+            },
+            "events": []
+        });
+
+        let bwe: BlockAndEvents = serde_json::from_value(json_data).unwrap();
+        let (outputs, inputs) = convert_bwe_to_address_models(&[bwe]).unwrap();
+
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].output_key, "paid_key");
+        assert_eq!(outputs[0].address, "receiver");
+        assert_eq!(outputs[0].lock_time, 1672531300000);
+        assert_eq!(outputs[0].tokens, json!([{ "id": "token1", "amount": "5" }]));
+        assert_eq!(outputs[1].output_key, "generated_key");
+        assert_eq!(outputs[1].lock_time, 0);
+
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].output_key, "spent_key");
+        assert_eq!(inputs[0].address, "sender");
+        assert_eq!(inputs[0].tx_hash, "tx123");
+    }
+
+    #[test]
+    fn test_convert_bwe_to_mining_models() {
+        let output = |key: &str, address: &str, amount: &str| {
+            json!({
+                "hint": 0,
+                "key": key,
+                "attoAlphAmount": amount,
+                "address": address,
+                "tokens": [],
+                "lockTime": 1672531800000u64,
+                "message": ""
+            })
+        };
+        let json_data = json!({
+            "block": {
+                "hash": "blockhash123",
+                "parent": "parent_hash",
+                "mainChain": true,
+                "timestamp": 1672531200000u64,
+                "chainFrom": 1,
+                "chainTo": 2,
+                "height": 1000,
+                "deps": [],
+                "transactions": [{
+                    "unsigned": {
+                        "txId": "coinbase_tx",
+                        "version": 0,
+                        "networkId": 1,
+                        "gasAmount": 20000,
+                        "gasPrice": "100000000000",
+                        "inputs": [],
+                        "fixedOutputs": [
+                            output("miner_key", "miner", "300"),
+                            output("uncle_key", "uncle_miner", "100")
+                        ]
+                    },
+                    "scriptExecutionOk": true,
+                    "contractInputs": [],
+                    "generatedOutputs": [],
+                    "inputSignatures": [],
+                    "scriptSignatures": []
+                }],
+                "nonce": "nonce_value",
+                "version": 1,
+                "depStateHash": "dep_hash",
+                "txsHash": "txs_hash",
+                "target": "target_value",
+                "ghostUncles": [{ "blockHash": "unclehash1", "miner": "uncle_miner" }]
+            },
+            "events": []
+        });
+
+        let bwe: BlockAndEvents = serde_json::from_value(json_data).unwrap();
+        let (rewards, uncles) = convert_bwe_to_mining_models(&[bwe]).unwrap();
+
+        assert_eq!(rewards.len(), 1);
+        assert_eq!(rewards[0].tx_hash, "coinbase_tx");
+        assert_eq!(rewards[0].miner_address, "miner");
+        assert_eq!(rewards[0].reward.to_string(), "300");
+        assert_eq!(rewards[0].uncle_count, 1);
+
+        assert_eq!(uncles.len(), 1);
+        assert_eq!(uncles[0].uncle_hash, "unclehash1");
+        assert_eq!(uncles[0].miner_address, "uncle_miner");
+        assert_eq!(uncles[0].reward.as_ref().map(|r| r.to_string()), Some("100".to_string()));
+    }
+
+    #[test]
+    fn test_tx_status_deser() {
+        let status: TxStatus = serde_json::from_value(json!({
+            "type": "Confirmed",
+            "blockHash": "00000000000006f8c2bcaac93c5a23df8fba7119ba139d80a49d0303bbf84850",
+            "txIndex": 1,
+            "chainConfirmations": 3,
+            "fromGroupConfirmations": 2,
+            "toGroupConfirmations": 2
+        }))
+        .unwrap();
+        assert!(matches!(status, TxStatus::Confirmed { chain_confirmations: 3, tx_index: 1, .. }));
+
+        let status: TxStatus = serde_json::from_value(json!({ "type": "MemPooled" })).unwrap();
+        assert_eq!(status, TxStatus::MemPooled);
+        let status: TxStatus = serde_json::from_value(json!({ "type": "TxNotFound" })).unwrap();
+        assert_eq!(status, TxStatus::TxNotFound);
+    }
 }
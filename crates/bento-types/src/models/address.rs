@@ -0,0 +1,37 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// An output (fixed or contract-generated) paid to an address, once per block including it.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::address_outputs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(output_key, block_hash))]
+pub struct AddressOutputModel {
+    pub output_key: String,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub address: String,
+    pub hint: i32,
+    pub atto_alph_amount: BigDecimal,
+    pub tokens: serde_json::Value,
+    pub lock_time: i64,
+    pub timestamp: NaiveDateTime,
+}
+
+/// An output consumed by a transaction, either an asset input or a contract input, once per
+/// block including the transaction.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::address_inputs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(output_key, block_hash))]
+pub struct AddressInputModel {
+    pub output_key: String,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub address: String,
+    pub atto_alph_amount: BigDecimal,
+    pub tokens: serde_json::Value,
+    pub timestamp: NaiveDateTime,
+}
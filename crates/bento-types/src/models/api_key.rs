@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// An API key of a bento-server client. Only the hash of the key is stored.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::api_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiKeyModel {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip)]
+    pub key_hash: String,
+    /// Requests per minute allowed to the key, the server default when unset
+    pub requests_per_minute: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = crate::schema::api_keys)]
+pub struct NewApiKeyModel {
+    pub name: String,
+    pub key_hash: String,
+    pub requests_per_minute: Option<i32>,
+}
@@ -0,0 +1,24 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// A timestamp range of a shared backfill job.
+#[derive(Queryable, QueryableByName, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::backfill_ranges)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(job, from_ts, to_ts))]
+pub struct BackfillRangeModel {
+    pub job: String,
+    pub from_ts: i64,
+    pub to_ts: i64,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<NaiveDateTime>,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+impl BackfillRangeModel {
+    /// An unclaimed range of `job`
+    pub fn new(job: &str, from_ts: i64, to_ts: i64) -> Self {
+        Self { job: job.to_string(), from_ts, to_ts, claimed_by: None, claimed_at: None, completed_at: None }
+    }
+}
@@ -13,4 +13,7 @@ pub struct EventModel {
     pub contract_address: String,
     pub event_index: i32,
     pub fields: serde_json::Value,
+    /// Order in which the transaction emitted the event, unset for events indexed before it was
+    /// recorded. `event_index` is the kind of event within its contract, not this order.
+    pub position: Option<i32>,
 }
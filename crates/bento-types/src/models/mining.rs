@@ -0,0 +1,37 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// The coinbase reward of a block, paid to its miner.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::block_rewards)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(block_hash))]
+pub struct BlockRewardModel {
+    pub block_hash: String,
+    pub tx_hash: String,
+    pub chain_from: i64,
+    pub chain_to: i64,
+    pub height: i64,
+    pub miner_address: String,
+    pub reward: BigDecimal,
+    pub lock_time: i64,
+    pub uncle_count: i32,
+    pub timestamp: NaiveDateTime,
+}
+
+/// A ghost uncle referenced by a block.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::ghost_uncles)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(block_hash, uncle_hash))]
+pub struct GhostUncleModel {
+    pub block_hash: String,
+    pub uncle_hash: String,
+    pub chain_from: i64,
+    pub chain_to: i64,
+    pub miner_address: String,
+    pub reward: Option<BigDecimal>,
+    pub timestamp: NaiveDateTime,
+}
@@ -1,13 +1,32 @@
+use std::{collections::HashMap, str::FromStr};
+
+use bigdecimal::BigDecimal;
+
 use crate::BlockAndEvents;
 
+pub mod address;
+pub mod api_key;
+pub mod backfill;
 pub mod block;
 pub mod event;
+pub mod mining;
 pub mod processor_status;
+pub mod token;
 pub mod transaction;
+pub mod webhook;
 
+pub use address::{AddressInputModel, AddressOutputModel};
+pub use api_key::{ApiKeyModel, NewApiKeyModel};
+pub use backfill::BackfillRangeModel;
 pub use block::BlockModel;
 pub use event::EventModel;
+pub use mining::{BlockRewardModel, GhostUncleModel};
+pub use token::{TokenInputModel, TokenModel, TokenOutputModel};
 pub use transaction::TransactionModel;
+pub use webhook::{
+    NewWebhookDeliveryModel, NewWebhookSubscriptionModel, WebhookDeadLetterModel, WebhookDeliveryModel,
+    WebhookSubscriptionModel,
+};
 
 pub fn convert_bwe_to_block_models(blocks: Vec<BlockAndEvents>) -> Vec<BlockModel> {
     let mut models = Vec::new();
@@ -37,14 +56,19 @@ pub fn convert_bwe_to_event_models(blocks: Vec<BlockAndEvents>) -> Vec<EventMode
     let mut models = Vec::new();
 
     for be in blocks {
+        // The node lists the events of a block in emission order
+        let mut positions: HashMap<String, i32> = HashMap::new();
         for e in be.events {
+            let position = positions.entry(e.tx_id.clone()).or_default();
             models.push(EventModel {
                 id: uuid::Uuid::new_v4().to_string(),
                 tx_id: e.tx_id,
                 contract_address: e.contract_address,
                 event_index: e.event_index,
                 fields: serde_json::to_value(e.fields).unwrap_or_default(), // TODO: need error handling here for retry?
+                position: Some(*position),
             });
+            *position += 1;
         }
     }
     models
@@ -71,3 +95,211 @@ pub fn convert_bwe_to_tx_models(blocks: Vec<BlockAndEvents>) -> Vec<TransactionM
         })
         .collect::<Vec<_>>()
 }
+
+/// Flatten the inputs and outputs of every transaction into per-address rows.
+///
+/// Fixed and generated outputs become `AddressOutputModel`s, asset and contract inputs
+/// become `AddressInputModel`s. An output is unspent as long as no input references its key.
+/// Fails on an amount that is not a decimal or tokens that cannot be serialized, rather than
+/// recording them as 0 or empty.
+pub fn convert_bwe_to_address_models(
+    blocks: &[BlockAndEvents],
+) -> anyhow::Result<(Vec<AddressOutputModel>, Vec<AddressInputModel>)> {
+    let mut outputs = Vec::new();
+    let mut inputs = Vec::new();
+
+    for be in blocks {
+        let block = &be.block;
+        let timestamp = crate::utils::timestamp_millis_to_naive_datetime(block.timestamp);
+
+        for tx in &block.transactions {
+            let tx_hash = &tx.unsigned.tx_id;
+
+            for output in &tx.unsigned.fixed_outputs {
+                outputs.push(AddressOutputModel {
+                    output_key: output.key.clone(),
+                    tx_hash: tx_hash.clone(),
+                    block_hash: block.hash.clone(),
+                    address: output.address.clone(),
+                    hint: output.hint,
+                    atto_alph_amount: parse_amount(&output.atto_alph_amount, &output.key)?,
+                    tokens: tokens_json(&output.tokens, &output.key)?,
+                    lock_time: output.lock_time,
+                    timestamp,
+                });
+            }
+
+            for output in &tx.generated_outputs {
+                outputs.push(AddressOutputModel {
+                    output_key: output.key.clone(),
+                    tx_hash: tx_hash.clone(),
+                    block_hash: block.hash.clone(),
+                    address: output.address.clone(),
+                    hint: output.hint,
+                    atto_alph_amount: parse_amount(&output.atto_alph_amount, &output.key)?,
+                    tokens: tokens_json(&output.tokens, &output.key)?,
+                    lock_time: 0,
+                    timestamp,
+                });
+            }
+
+            for input in &tx.unsigned.inputs {
+                inputs.push(AddressInputModel {
+                    output_key: input.key.clone(),
+                    tx_hash: tx_hash.clone(),
+                    block_hash: block.hash.clone(),
+                    address: input.address.clone(),
+                    atto_alph_amount: parse_amount(&input.atto_alph_amount, &input.key)?,
+                    tokens: tokens_json(&input.tokens, &input.key)?,
+                    timestamp,
+                });
+            }
+
+            for input in &tx.contract_inputs {
+                inputs.push(AddressInputModel {
+                    output_key: input.key.clone(),
+                    tx_hash: tx_hash.clone(),
+                    block_hash: block.hash.clone(),
+                    address: input.address.clone(),
+                    atto_alph_amount: parse_amount(&input.atto_alph_amount, &input.key)?,
+                    tokens: tokens_json(&input.tokens, &input.key)?,
+                    timestamp,
+                });
+            }
+        }
+    }
+
+    Ok((outputs, inputs))
+}
+
+fn parse_amount(amount: &str, output_key: &str) -> anyhow::Result<BigDecimal> {
+    BigDecimal::from_str(amount)
+        .map_err(|e| anyhow::anyhow!("Invalid amount {} of output {}: {}", amount, output_key, e))
+}
+
+fn tokens_json(tokens: &[crate::Token], output_key: &str) -> anyhow::Result<serde_json::Value> {
+    serde_json::to_value(tokens)
+        .map_err(|e| anyhow::anyhow!("Failed to serialize the tokens of output {}: {}", output_key, e))
+}
+
+/// Explode token amounts of every output and input into per-token rows, and collect
+/// the earliest occurrence of each token id in the batch.
+pub fn convert_bwe_to_token_models(
+    blocks: &[BlockAndEvents],
+) -> anyhow::Result<(Vec<TokenModel>, Vec<TokenOutputModel>, Vec<TokenInputModel>)> {
+    let mut tokens: HashMap<String, TokenModel> = HashMap::new();
+    let mut outputs = Vec::new();
+    let mut inputs = Vec::new();
+
+    for be in blocks {
+        let block = &be.block;
+        let timestamp = crate::utils::timestamp_millis_to_naive_datetime(block.timestamp);
+
+        for tx in &block.transactions {
+            let tx_hash = &tx.unsigned.tx_id;
+
+            let fixed = tx.unsigned.fixed_outputs.iter().map(|o| (&o.key, &o.address, &o.tokens));
+            let generated = tx.generated_outputs.iter().map(|o| (&o.key, &o.address, &o.tokens));
+            for (key, address, output_tokens) in fixed.chain(generated) {
+                for token in output_tokens {
+                    let seen = tokens.entry(token.id.clone()).or_insert_with(|| TokenModel {
+                        token_id: token.id.clone(),
+                        first_seen_tx_hash: tx_hash.clone(),
+                        first_seen_block_hash: block.hash.clone(),
+                        first_seen_at: timestamp,
+                        name: None,
+                        symbol: None,
+                        decimals: None,
+                        metadata_fetched_at: None,
+                    });
+                    if timestamp < seen.first_seen_at {
+                        seen.first_seen_tx_hash = tx_hash.clone();
+                        seen.first_seen_block_hash = block.hash.clone();
+                        seen.first_seen_at = timestamp;
+                    }
+
+                    outputs.push(TokenOutputModel {
+                        output_key: key.clone(),
+                        token_id: token.id.clone(),
+                        tx_hash: tx_hash.clone(),
+                        block_hash: block.hash.clone(),
+                        address: address.clone(),
+                        amount: parse_amount(&token.amount, key)?,
+                        timestamp,
+                    });
+                }
+            }
+
+            let asset_inputs = tx.unsigned.inputs.iter().map(|i| (&i.key, &i.address, &i.tokens));
+            let contract_inputs = tx.contract_inputs.iter().map(|i| (&i.key, &i.address, &i.tokens));
+            for (key, address, input_tokens) in asset_inputs.chain(contract_inputs) {
+                for token in input_tokens {
+                    inputs.push(TokenInputModel {
+                        output_key: key.clone(),
+                        token_id: token.id.clone(),
+                        tx_hash: tx_hash.clone(),
+                        block_hash: block.hash.clone(),
+                        address: address.clone(),
+                        amount: parse_amount(&token.amount, key)?,
+                        timestamp,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok((tokens.into_values().collect(), outputs, inputs))
+}
+
+/// Extract the coinbase reward and the ghost uncles of every block.
+///
+/// The coinbase is the last transaction of a block. Its first output pays the block miner,
+/// the following outputs pay the uncle miners in the order the uncles are listed.
+pub fn convert_bwe_to_mining_models(
+    blocks: &[BlockAndEvents],
+) -> anyhow::Result<(Vec<BlockRewardModel>, Vec<GhostUncleModel>)> {
+    let mut rewards = Vec::new();
+    let mut uncles = Vec::new();
+
+    for be in blocks {
+        let block = &be.block;
+        let timestamp = crate::utils::timestamp_millis_to_naive_datetime(block.timestamp);
+        let coinbase =
+            block.transactions.last().filter(|tx| tx.unsigned.inputs.is_empty() && tx.contract_inputs.is_empty());
+        let coinbase_outputs = coinbase.map(|tx| tx.unsigned.fixed_outputs.as_slice()).unwrap_or_default();
+
+        if let (Some(tx), Some(miner_output)) = (coinbase, coinbase_outputs.first()) {
+            rewards.push(BlockRewardModel {
+                block_hash: block.hash.clone(),
+                tx_hash: tx.unsigned.tx_id.clone(),
+                chain_from: block.chain_from,
+                chain_to: block.chain_to,
+                height: block.height,
+                miner_address: miner_output.address.clone(),
+                reward: parse_amount(&miner_output.atto_alph_amount, &miner_output.key)?,
+                lock_time: miner_output.lock_time,
+                uncle_count: block.ghost_uncles.len() as i32,
+                timestamp,
+            });
+        }
+
+        for (index, uncle) in block.ghost_uncles.iter().enumerate() {
+            let reward = coinbase_outputs
+                .get(index + 1)
+                .filter(|output| output.address == uncle.miner)
+                .map(|output| parse_amount(&output.atto_alph_amount, &output.key))
+                .transpose()?;
+            uncles.push(GhostUncleModel {
+                block_hash: block.hash.clone(),
+                uncle_hash: uncle.block_hash.clone(),
+                chain_from: block.chain_from,
+                chain_to: block.chain_to,
+                miner_address: uncle.miner.clone(),
+                reward,
+                timestamp,
+            });
+        }
+    }
+
+    Ok((rewards, uncles))
+}
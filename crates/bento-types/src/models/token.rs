@@ -0,0 +1,50 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// A token id seen in at least one output, together with its on-chain metadata once resolved.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(token_id))]
+pub struct TokenModel {
+    pub token_id: String,
+    pub first_seen_tx_hash: String,
+    pub first_seen_block_hash: String,
+    pub first_seen_at: NaiveDateTime,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    pub metadata_fetched_at: Option<NaiveDateTime>,
+}
+
+/// The amount of a single token carried by an output.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::token_outputs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(output_key, token_id, block_hash))]
+pub struct TokenOutputModel {
+    pub output_key: String,
+    pub token_id: String,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub address: String,
+    pub amount: BigDecimal,
+    pub timestamp: NaiveDateTime,
+}
+
+/// The amount of a single token consumed by an input.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::token_inputs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(output_key, token_id, block_hash))]
+pub struct TokenInputModel {
+    pub output_key: String,
+    pub token_id: String,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub address: String,
+    pub amount: BigDecimal,
+    pub timestamp: NaiveDateTime,
+}
@@ -0,0 +1,110 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::notifications::IndexedNotification;
+
+/// A webhook subscription. Notifications matching every filter that is set are delivered to
+/// `url`, signed with `secret`.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::webhook_subscriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookSubscriptionModel {
+    pub id: i64,
+    pub name: String,
+    pub url: String,
+    #[serde(skip)]
+    pub secret: String,
+    /// Kind of notification, see [`IndexedNotification::kind`]
+    pub notification_type: Option<String>,
+    /// Contract emitting the events
+    pub contract_address: Option<String>,
+    pub event_index: Option<i32>,
+    /// Address whose activity is delivered
+    pub address: Option<String>,
+    /// Topic of custom notifications
+    pub topic: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub disabled_at: Option<NaiveDateTime>,
+}
+
+impl WebhookSubscriptionModel {
+    /// Whether `notification` passes every filter that is set. A filter on a field the
+    /// notification does not have rejects it. The address of a custom notification is the
+    /// `address` field of its data, if any.
+    pub fn matches(&self, notification: &IndexedNotification) -> bool {
+        fn check(filter: &Option<String>, value: Option<&str>) -> bool {
+            filter.as_deref().is_none_or(|filter| value == Some(filter))
+        }
+
+        let (contract_address, event_index, address, topic) = match notification {
+            IndexedNotification::Event { contract_address, event_index, .. } => {
+                (Some(contract_address.as_str()), Some(*event_index), None, None)
+            }
+            IndexedNotification::AddressActivity { address, .. } => (None, None, Some(address.as_str()), None),
+            IndexedNotification::Custom { topic, data } => {
+                (None, None, data.get("address").and_then(|a| a.as_str()), Some(topic.as_str()))
+            }
+            IndexedNotification::Block { .. } => (None, None, None, None),
+        };
+
+        check(&self.notification_type, Some(notification.kind()))
+            && check(&self.contract_address, contract_address)
+            && self.event_index.is_none_or(|index| event_index == Some(index))
+            && check(&self.address, address)
+            && check(&self.topic, topic)
+    }
+}
+
+#[derive(Insertable, Debug, Clone, Default)]
+#[diesel(table_name = crate::schema::webhook_subscriptions)]
+pub struct NewWebhookSubscriptionModel {
+    pub name: String,
+    pub url: String,
+    pub secret: String,
+    pub notification_type: Option<String>,
+    pub contract_address: Option<String>,
+    pub event_index: Option<i32>,
+    pub address: Option<String>,
+    pub topic: Option<String>,
+}
+
+/// A pending delivery of a notification to a subscription.
+#[derive(Queryable, Selectable, Debug, Clone, PartialEq)]
+#[diesel(table_name = crate::schema::webhook_deliveries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookDeliveryModel {
+    pub id: i64,
+    pub subscription_id: i64,
+    /// Hash of the notification, identifying it across retried batches
+    pub notification_key: String,
+    /// The notification as JSON
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = crate::schema::webhook_deliveries)]
+pub struct NewWebhookDeliveryModel {
+    pub subscription_id: i64,
+    pub notification_key: String,
+    pub payload: serde_json::Value,
+}
+
+/// A delivery that failed every attempt.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::webhook_dead_letters)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookDeadLetterModel {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub notification_key: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub failed_at: NaiveDateTime,
+}
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::processors::ProcessorOutput;
+
+/// Postgres channel the worker notifies on after storing processor output.
+pub const INDEXED_NOTIFY_CHANNEL: &str = "bento_indexed";
+
+/// A piece of newly indexed data, published after a processor stored its output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndexedNotification {
+    Block {
+        hash: String,
+        chain_from: i64,
+        chain_to: i64,
+        height: i64,
+        /// Block timestamp in milliseconds
+        timestamp: i64,
+    },
+    Event {
+        id: String,
+        tx_id: String,
+        contract_address: String,
+        event_index: i32,
+    },
+    /// An address received or spent an output in a transaction
+    AddressActivity {
+        address: String,
+        tx_hash: String,
+        block_hash: String,
+        /// Block timestamp in milliseconds
+        timestamp: i64,
+    },
+    /// Published by custom processors under a topic of their choice
+    Custom {
+        topic: String,
+        data: serde_json::Value,
+    },
+}
+
+impl IndexedNotification {
+    /// Name of the variant, used as the SSE event name.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IndexedNotification::Block { .. } => "block",
+            IndexedNotification::Event { .. } => "event",
+            IndexedNotification::AddressActivity { .. } => "address_activity",
+            IndexedNotification::Custom { .. } => "custom",
+        }
+    }
+
+    /// The notifications of a processor output. Address activity is reported once per address
+    /// and transaction.
+    pub fn from_output(output: &ProcessorOutput) -> Vec<Self> {
+        match output {
+            ProcessorOutput::Block(blocks) => blocks
+                .iter()
+                .map(|b| IndexedNotification::Block {
+                    hash: b.hash.clone(),
+                    chain_from: b.chain_from,
+                    chain_to: b.chain_to,
+                    height: b.height,
+                    timestamp: b.timestamp.and_utc().timestamp_millis(),
+                })
+                .collect(),
+            ProcessorOutput::Event(events) => events
+                .iter()
+                .map(|e| IndexedNotification::Event {
+                    id: e.id.clone(),
+                    tx_id: e.tx_id.clone(),
+                    contract_address: e.contract_address.clone(),
+                    event_index: e.event_index,
+                })
+                .collect(),
+            ProcessorOutput::Address(outputs, inputs) => {
+                let mut seen = HashSet::new();
+                outputs
+                    .iter()
+                    .map(|o| (&o.address, &o.tx_hash, &o.block_hash, o.timestamp))
+                    .chain(inputs.iter().map(|i| (&i.address, &i.tx_hash, &i.block_hash, i.timestamp)))
+                    .filter(|(address, tx_hash, _, _)| seen.insert((*address, *tx_hash)))
+                    .map(|(address, tx_hash, block_hash, timestamp)| IndexedNotification::AddressActivity {
+                        address: address.clone(),
+                        tx_hash: tx_hash.clone(),
+                        block_hash: block_hash.clone(),
+                        timestamp: timestamp.and_utc().timestamp_millis(),
+                    })
+                    .collect()
+            }
+            ProcessorOutput::Custom(custom) => custom.notifications(),
+            ProcessorOutput::Tx(_) | ProcessorOutput::Token(..) | ProcessorOutput::Mining(..) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::address::{AddressInputModel, AddressOutputModel};
+
+    #[test]
+    fn test_address_activity_deduplicated() {
+        let timestamp = chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap().naive_utc();
+        let output = AddressOutputModel {
+            output_key: "key1".to_string(),
+            tx_hash: "tx1".to_string(),
+            block_hash: "block1".to_string(),
+            address: "alice".to_string(),
+            hint: 0,
+            atto_alph_amount: Default::default(),
+            tokens: serde_json::json!([]),
+            lock_time: 0,
+            timestamp,
+        };
+        let input = AddressInputModel {
+            output_key: "key0".to_string(),
+            tx_hash: "tx1".to_string(),
+            block_hash: "block1".to_string(),
+            address: "alice".to_string(),
+            atto_alph_amount: Default::default(),
+            tokens: serde_json::json!([]),
+            timestamp,
+        };
+        let mut second = output.clone();
+        second.address = "bob".to_string();
+
+        let notifications =
+            IndexedNotification::from_output(&ProcessorOutput::Address(vec![output, second], vec![input]));
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0].kind(), "address_activity");
+
+        let json = serde_json::to_value(&notifications[1]).unwrap();
+        assert_eq!(json["type"], "address_activity");
+        assert_eq!(json["address"], "bob");
+        assert_eq!(json["timestamp"], 1_700_000_000_000i64);
+    }
+}
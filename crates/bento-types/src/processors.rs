@@ -8,5 +8,12 @@ pub enum ProcessorOutput {
     Block(Vec<crate::models::block::BlockModel>),
     Event(Vec<crate::models::event::EventModel>),
     Tx(Vec<crate::models::transaction::TransactionModel>),
+    Address(Vec<crate::models::address::AddressOutputModel>, Vec<crate::models::address::AddressInputModel>),
+    Token(
+        Vec<crate::models::token::TokenModel>,
+        Vec<crate::models::token::TokenOutputModel>,
+        Vec<crate::models::token::TokenInputModel>,
+    ),
+    Mining(Vec<crate::models::mining::BlockRewardModel>, Vec<crate::models::mining::GhostUncleModel>),
     Custom(Arc<dyn CustomProcessorOutput>),
 }
@@ -0,0 +1,309 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Numeric, Text};
+use diesel::{insert_into, QueryableByName};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+
+use crate::models::{
+    address::{AddressInputModel, AddressOutputModel},
+    transaction::TransactionModel,
+};
+use crate::{BlockRange, DbPool};
+
+/// Rows per insert statement, keeps us well below the Postgres bind parameter limit.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Default)]
+pub struct AddressBalance {
+    pub balance: BigDecimal,
+    pub locked_balance: BigDecimal,
+    pub utxo_count: i64,
+    pub tokens: Vec<AddressTokenBalance>,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct AddressTokenBalance {
+    #[diesel(sql_type = Text)]
+    pub token_id: String,
+    #[diesel(sql_type = Numeric)]
+    pub amount: BigDecimal,
+}
+
+#[derive(QueryableByName)]
+struct AlphBalanceRow {
+    #[diesel(sql_type = Numeric)]
+    balance: BigDecimal,
+    #[diesel(sql_type = Numeric)]
+    locked_balance: BigDecimal,
+    #[diesel(sql_type = BigInt)]
+    utxo_count: i64,
+}
+
+#[derive(QueryableByName)]
+struct AddressTxRow {
+    #[diesel(sql_type = Text)]
+    tx_hash: String,
+}
+
+/// Insert address outputs and inputs into the database in a single transaction.
+pub async fn insert_address_models(
+    conn: &mut diesel_async::AsyncPgConnection,
+    outputs: Vec<AddressOutputModel>,
+    inputs: Vec<AddressInputModel>,
+) -> Result<()> {
+    if outputs.is_empty() && inputs.is_empty() {
+        return Ok(());
+    }
+    let (outputs_len, inputs_len) = (outputs.len(), inputs.len());
+
+    conn.transaction(|conn| {
+        async move {
+            for chunk in outputs.chunks(INSERT_CHUNK_SIZE) {
+                insert_into(crate::schema::address_outputs::table)
+                    .values(chunk)
+                    .on_conflict((
+                        crate::schema::address_outputs::output_key,
+                        crate::schema::address_outputs::block_hash,
+                    ))
+                    .do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            for chunk in inputs.chunks(INSERT_CHUNK_SIZE) {
+                insert_into(crate::schema::address_inputs::table)
+                    .values(chunk)
+                    .on_conflict((crate::schema::address_inputs::output_key, crate::schema::address_inputs::block_hash))
+                    .do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    tracing::info!("Inserted {} address outputs and {} address inputs", outputs_len, inputs_len);
+    Ok(())
+}
+
+/// List the unspent outputs of an address, newest first.
+///
+/// Only outputs of main chain blocks count, and an output is spent by an input of a main
+/// chain block.
+pub async fn get_address_utxos(
+    db: Arc<DbPool>,
+    address_value: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<AddressOutputModel>> {
+    use crate::schema::{address_inputs, address_outputs, blocks};
+
+    let (input_blocks, output_blocks) = diesel::alias!(blocks as input_blocks, blocks as output_blocks);
+    let mut conn = db.get().await?;
+    let utxos = address_outputs::table
+        .filter(address_outputs::address.eq(address_value))
+        .filter(diesel::dsl::exists(
+            output_blocks
+                .filter(output_blocks.field(blocks::hash).eq(address_outputs::block_hash))
+                .filter(output_blocks.field(blocks::main_chain).eq(true)),
+        ))
+        .filter(diesel::dsl::not(diesel::dsl::exists(
+            address_inputs::table.filter(address_inputs::output_key.eq(address_outputs::output_key)).filter(
+                diesel::dsl::exists(
+                    input_blocks
+                        .filter(input_blocks.field(blocks::hash).eq(address_inputs::block_hash))
+                        .filter(input_blocks.field(blocks::main_chain).eq(true)),
+                ),
+            ),
+        )))
+        .order(address_outputs::timestamp.desc())
+        .limit(limit)
+        .offset(offset)
+        .select(AddressOutputModel::as_select())
+        .load(&mut conn)
+        .await?;
+
+    Ok(utxos)
+}
+
+/// Compute the ALPH and token balances of an address from its unspent outputs.
+///
+/// Only outputs of main chain blocks count, and an output is spent by an input of a main
+/// chain block. Outputs whose `lock_time` is still in the future are counted in
+/// `locked_balance` as well.
+pub async fn get_address_balance(db: Arc<DbPool>, address_value: &str) -> Result<AddressBalance> {
+    let mut conn = db.get().await?;
+
+    let alph: AlphBalanceRow = diesel::sql_query(
+        r#"
+        SELECT
+            COALESCE(SUM(o.atto_alph_amount), 0) AS balance,
+            COALESCE(SUM(o.atto_alph_amount) FILTER (
+                WHERE o.lock_time > (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+            ), 0) AS locked_balance,
+            COUNT(*) AS utxo_count
+        FROM address_outputs o
+        JOIN blocks b ON b.hash = o.block_hash AND b.main_chain
+        WHERE o.address = $1
+          AND NOT EXISTS (
+              SELECT 1 FROM address_inputs i
+              JOIN blocks ib ON ib.hash = i.block_hash AND ib.main_chain
+              WHERE i.output_key = o.output_key
+          )
+        "#,
+    )
+    .bind::<Text, _>(address_value)
+    .get_result(&mut conn)
+    .await?;
+
+    let tokens: Vec<AddressTokenBalance> = diesel::sql_query(
+        r#"
+        SELECT t->>'id' AS token_id, SUM((t->>'amount')::NUMERIC) AS amount
+        FROM address_outputs o
+        JOIN blocks b ON b.hash = o.block_hash AND b.main_chain
+        CROSS JOIN jsonb_array_elements(o.tokens) t
+        WHERE o.address = $1
+          AND NOT EXISTS (
+              SELECT 1 FROM address_inputs i
+              JOIN blocks ib ON ib.hash = i.block_hash AND ib.main_chain
+              WHERE i.output_key = o.output_key
+          )
+        GROUP BY t->>'id'
+        ORDER BY t->>'id'
+        "#,
+    )
+    .bind::<Text, _>(address_value)
+    .load(&mut conn)
+    .await?;
+
+    Ok(AddressBalance {
+        balance: alph.balance,
+        locked_balance: alph.locked_balance,
+        utxo_count: alph.utxo_count,
+        tokens,
+    })
+}
+
+/// List the main chain transactions that spent from or paid to an address, newest first.
+pub async fn get_address_transactions(
+    db: Arc<DbPool>,
+    address_value: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<TransactionModel>> {
+    use crate::schema::transactions::dsl::*;
+
+    let mut conn = db.get().await?;
+    let rows: Vec<AddressTxRow> = diesel::sql_query(
+        r#"
+        SELECT tx_hash FROM (
+            SELECT tx_hash, MAX(timestamp) AS timestamp FROM (
+                SELECT o.tx_hash, o.timestamp FROM address_outputs o
+                JOIN blocks b ON b.hash = o.block_hash AND b.main_chain
+                WHERE o.address = $1
+                UNION ALL
+                SELECT i.tx_hash, i.timestamp FROM address_inputs i
+                JOIN blocks b ON b.hash = i.block_hash AND b.main_chain
+                WHERE i.address = $1
+            ) touched
+            GROUP BY tx_hash
+        ) address_txs
+        ORDER BY timestamp DESC, tx_hash
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind::<Text, _>(address_value)
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .load(&mut conn)
+    .await?;
+
+    let hashes: Vec<String> = rows.into_iter().map(|r| r.tx_hash).collect();
+    let mut tx_models: Vec<TransactionModel> =
+        transactions.filter(tx_hash.eq_any(&hashes)).select(TransactionModel::as_select()).load(&mut conn).await?;
+
+    // Keep the ordering of the address history
+    tx_models.sort_by_key(|tx| hashes.iter().position(|h| *h == tx.tx_hash));
+    Ok(tx_models)
+}
+
+/// Delete the address outputs and inputs of blocks with a timestamp in `range`.
+pub async fn delete_address_models_in_range(
+    conn: &mut diesel_async::AsyncPgConnection,
+    range: &BlockRange,
+) -> Result<usize> {
+    use crate::schema::{address_inputs, address_outputs};
+
+    let (from, to) = range.as_datetimes();
+    let outputs = diesel::delete(address_outputs::table.filter(address_outputs::timestamp.between(from, to)))
+        .execute(conn)
+        .await?;
+    let inputs =
+        diesel::delete(address_inputs::table.filter(address_inputs::timestamp.between(from, to))).execute(conn).await?;
+    Ok(outputs + inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils::{create_test_pool, FixtureInput, FixtureOutput, MainUncleFixture};
+
+    const ADDRESS: &str = "test-address-balance";
+    const TABLES: &[&str] = &["address_outputs", "address_inputs"];
+
+    fn output(o: FixtureOutput, lock_time: i64) -> AddressOutputModel {
+        AddressOutputModel {
+            output_key: o.key.to_string(),
+            tx_hash: o.tx_hash,
+            block_hash: o.block_hash,
+            address: ADDRESS.to_string(),
+            hint: 0,
+            atto_alph_amount: BigDecimal::from(o.amount),
+            tokens: serde_json::json!([]),
+            lock_time,
+            timestamp: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    fn input(i: FixtureInput) -> AddressInputModel {
+        AddressInputModel {
+            output_key: i.key.to_string(),
+            tx_hash: i.tx_hash,
+            block_hash: i.block_hash,
+            address: ADDRESS.to_string(),
+            atto_alph_amount: BigDecimal::from(i.amount),
+            tokens: serde_json::json!([]),
+            timestamp: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_get_address_balance() {
+        let db = create_test_pool().await;
+        let fixture = MainUncleFixture::new(ADDRESS);
+        fixture.setup(&db, TABLES).await;
+
+        let far_future = chrono::Utc::now().timestamp_millis() + 86_400_000;
+        let mut outputs: Vec<_> = fixture.outputs().into_iter().map(|o| output(o, 0)).collect();
+        outputs.push(output(FixtureOutput::new("locked", &fixture.main, 300), far_future));
+        let inputs = fixture.inputs().into_iter().map(input).collect();
+        insert_address_models(&mut db.get().await.unwrap(), outputs, inputs).await.unwrap();
+
+        let balance = get_address_balance(db.clone(), ADDRESS).await.unwrap();
+        assert_eq!(balance.balance, BigDecimal::from(900));
+        assert_eq!(balance.locked_balance, BigDecimal::from(300));
+        assert_eq!(balance.utxo_count, 3);
+
+        let mut utxos: Vec<String> =
+            get_address_utxos(db.clone(), ADDRESS, 10, 0).await.unwrap().into_iter().map(|o| o.output_key).collect();
+        utxos.sort();
+        assert_eq!(utxos, vec!["locked", "spent-in-uncle", "unspent"]);
+
+        fixture.cleanup(&db, TABLES).await;
+    }
+}
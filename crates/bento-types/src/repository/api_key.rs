@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use sha2::{Digest, Sha256};
+
+use crate::models::api_key::{ApiKeyModel, NewApiKeyModel};
+use crate::schema::api_keys;
+use crate::DbPool;
+
+/// Prefix of generated keys, making them recognizable in logs and secret scanners.
+pub const API_KEY_PREFIX: &str = "bento_";
+
+/// Generate a new random API key.
+pub fn generate_api_key() -> String {
+    format!("{}{}{}", API_KEY_PREFIX, uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+/// Hex encoded SHA-256 of a key, as stored in `api_keys.key_hash`.
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Create an API key named `name` and return it with the plain key, which is not stored.
+pub async fn create_api_key(
+    db: Arc<DbPool>,
+    name: &str,
+    requests_per_minute: Option<i32>,
+) -> Result<(ApiKeyModel, String)> {
+    let key = generate_api_key();
+    let new_key = NewApiKeyModel { name: name.to_string(), key_hash: hash_api_key(&key), requests_per_minute };
+
+    let mut conn = db.get().await?;
+    let model = insert_into(api_keys::table)
+        .values(&new_key)
+        .returning(ApiKeyModel::as_returning())
+        .get_result(&mut conn)
+        .await?;
+    Ok((model, key))
+}
+
+/// Get the unrevoked API key matching `key`
+pub async fn get_active_api_key(db: &Arc<DbPool>, key: &str) -> Result<Option<ApiKeyModel>> {
+    let mut conn = db.get().await?;
+    let model = api_keys::table
+        .filter(api_keys::key_hash.eq(hash_api_key(key)))
+        .filter(api_keys::revoked_at.is_null())
+        .select(ApiKeyModel::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?;
+    Ok(model)
+}
+
+/// List API keys, revoked ones included, ordered by name
+pub async fn list_api_keys(db: Arc<DbPool>) -> Result<Vec<ApiKeyModel>> {
+    let mut conn = db.get().await?;
+    let models = api_keys::table.order(api_keys::name.asc()).select(ApiKeyModel::as_select()).load(&mut conn).await?;
+    Ok(models)
+}
+
+/// Revoke the API key named `name`
+pub async fn revoke_api_key(db: Arc<DbPool>, name: &str) -> Result<()> {
+    let mut conn = db.get().await?;
+    let updated = diesel::update(api_keys::table)
+        .filter(api_keys::name.eq(name))
+        .filter(api_keys::revoked_at.is_null())
+        .set(api_keys::revoked_at.eq(chrono::Utc::now().naive_utc()))
+        .execute(&mut conn)
+        .await?;
+    if updated == 0 {
+        return Err(anyhow!("No active API key named {name}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_are_unique_and_hashed() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + 64);
+        assert_ne!(key, generate_api_key());
+
+        let hash = hash_api_key(&key);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key(&key));
+    }
+}
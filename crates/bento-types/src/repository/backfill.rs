@@ -0,0 +1,185 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel_async::RunQueryDsl;
+
+use crate::models::backfill::BackfillRangeModel;
+use crate::schema::backfill_ranges;
+use crate::DbPool;
+
+/// Rows per insert statement, keeps us well below the Postgres bind parameter limit.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Register the ranges of a backfill job. Ranges that already exist keep their claim and
+/// completion state, so restarting a job resumes it.
+pub async fn insert_backfill_ranges(db: Arc<DbPool>, ranges: Vec<BackfillRangeModel>) -> Result<()> {
+    let mut conn = db.get().await?;
+    for chunk in ranges.chunks(INSERT_CHUNK_SIZE) {
+        insert_into(backfill_ranges::table).values(chunk).on_conflict_do_nothing().execute(&mut conn).await?;
+    }
+    Ok(())
+}
+
+/// Claim the earliest range of `job` that is neither completed nor claimed by a live worker.
+///
+/// Claims older than `stale_after` are considered abandoned and can be taken over.
+/// `SKIP LOCKED` lets concurrent workers claim different ranges without waiting on each other.
+pub async fn claim_backfill_range(
+    db: Arc<DbPool>,
+    job: &str,
+    worker_id: &str,
+    stale_after: Duration,
+) -> Result<Option<BackfillRangeModel>> {
+    let mut conn = db.get().await?;
+    let range = diesel::sql_query(
+        r#"
+        UPDATE backfill_ranges
+        SET claimed_by = $2, claimed_at = NOW() AT TIME ZONE 'UTC'
+        WHERE (job, from_ts, to_ts) = (
+            SELECT job, from_ts, to_ts
+            FROM backfill_ranges
+            WHERE job = $1
+              AND completed_at IS NULL
+              AND (claimed_at IS NULL OR claimed_at < NOW() AT TIME ZONE 'UTC' - make_interval(secs => $3::DOUBLE PRECISION))
+            ORDER BY from_ts
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+    )
+    .bind::<Text, _>(job)
+    .bind::<Text, _>(worker_id)
+    .bind::<BigInt, _>(stale_after.as_secs() as i64)
+    .get_result::<BackfillRangeModel>(&mut conn)
+    .await
+    .optional()?;
+    Ok(range)
+}
+
+/// Extend the claim of `worker_id` on a range it is still processing, so it is not considered
+/// abandoned. Returns `false` if the claim was taken over or the range completed meanwhile.
+pub async fn renew_backfill_claim(db: Arc<DbPool>, range: &BackfillRangeModel, worker_id: &str) -> Result<bool> {
+    let mut conn = db.get().await?;
+    let updated = diesel::update(
+        backfill_ranges::table
+            .find((&range.job, range.from_ts, range.to_ts))
+            .filter(backfill_ranges::claimed_by.eq(worker_id))
+            .filter(backfill_ranges::completed_at.is_null()),
+    )
+    .set(backfill_ranges::claimed_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(&mut conn)
+    .await?;
+    Ok(updated > 0)
+}
+
+/// Mark a range claimed by `worker_id` as completed. Returns `false` if another worker took the
+/// claim over, in which case that worker completes it.
+pub async fn complete_backfill_range(db: Arc<DbPool>, range: &BackfillRangeModel, worker_id: &str) -> Result<bool> {
+    let mut conn = db.get().await?;
+    let updated = diesel::update(
+        backfill_ranges::table
+            .find((&range.job, range.from_ts, range.to_ts))
+            .filter(backfill_ranges::claimed_by.eq(worker_id)),
+    )
+    .set(backfill_ranges::completed_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(&mut conn)
+    .await?;
+    Ok(updated > 0)
+}
+
+/// Give a range back so another worker can claim it right away.
+pub async fn release_backfill_range(db: Arc<DbPool>, range: &BackfillRangeModel, worker_id: &str) -> Result<()> {
+    let mut conn = db.get().await?;
+    diesel::update(
+        backfill_ranges::table
+            .find((&range.job, range.from_ts, range.to_ts))
+            .filter(backfill_ranges::claimed_by.eq(worker_id)),
+    )
+    .set((
+        backfill_ranges::claimed_by.eq(None::<String>),
+        backfill_ranges::claimed_at.eq(None::<chrono::NaiveDateTime>),
+    ))
+    .execute(&mut conn)
+    .await?;
+    Ok(())
+}
+
+/// Number of ranges of `job` that are not completed yet.
+pub async fn count_pending_backfill_ranges(db: Arc<DbPool>, job: &str) -> Result<i64> {
+    let mut conn = db.get().await?;
+    let count = backfill_ranges::table
+        .filter(backfill_ranges::job.eq(job))
+        .filter(backfill_ranges::completed_at.is_null())
+        .count()
+        .get_result(&mut conn)
+        .await?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils::create_test_pool;
+
+    async fn setup(db: &Arc<DbPool>, job: &str, count: i64) {
+        let mut conn = db.get().await.unwrap();
+        diesel::delete(backfill_ranges::table.filter(backfill_ranges::job.eq(job))).execute(&mut conn).await.unwrap();
+        let ranges = (0..count).map(|i| BackfillRangeModel::new(job, i * 10, (i + 1) * 10)).collect();
+        insert_backfill_ranges(db.clone(), ranges).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_claim_renew_and_complete() {
+        const JOB: &str = "test-backfill-claims";
+        let db = create_test_pool().await;
+        setup(&db, JOB, 2).await;
+        let timeout = Duration::from_secs(600);
+
+        let first = claim_backfill_range(db.clone(), JOB, "a", timeout).await.unwrap().unwrap();
+        let second = claim_backfill_range(db.clone(), JOB, "b", timeout).await.unwrap().unwrap();
+        assert_eq!((first.from_ts, second.from_ts), (0, 10));
+        assert!(claim_backfill_range(db.clone(), JOB, "c", timeout).await.unwrap().is_none());
+
+        // Only the claiming worker can renew or complete a range
+        assert!(!renew_backfill_claim(db.clone(), &first, "b").await.unwrap());
+        assert!(!complete_backfill_range(db.clone(), &first, "b").await.unwrap());
+        assert!(renew_backfill_claim(db.clone(), &first, "a").await.unwrap());
+        assert!(complete_backfill_range(db.clone(), &first, "a").await.unwrap());
+        assert!(!renew_backfill_claim(db.clone(), &first, "a").await.unwrap());
+        assert_eq!(count_pending_backfill_ranges(db.clone(), JOB).await.unwrap(), 1);
+
+        // A stale claim is taken over, and its former holder can no longer complete it
+        let taken = claim_backfill_range(db.clone(), JOB, "c", Duration::ZERO).await.unwrap().unwrap();
+        assert_eq!(taken.from_ts, second.from_ts);
+        assert!(!renew_backfill_claim(db.clone(), &second, "b").await.unwrap());
+        assert!(!complete_backfill_range(db.clone(), &second, "b").await.unwrap());
+        assert!(complete_backfill_range(db.clone(), &taken, "c").await.unwrap());
+        assert_eq!(count_pending_backfill_ranges(db.clone(), JOB).await.unwrap(), 0);
+
+        setup(&db, JOB, 0).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_concurrent_claims_skip_locked_ranges() {
+        const JOB: &str = "test-backfill-concurrent";
+        let db = create_test_pool().await;
+        setup(&db, JOB, 4).await;
+
+        let claims = futures_util::future::join_all((0..4).map(|i| {
+            let db = db.clone();
+            async move { claim_backfill_range(db, JOB, &format!("worker-{i}"), Duration::from_secs(600)).await }
+        }))
+        .await;
+        let mut from: Vec<i64> = claims.into_iter().map(|claim| claim.unwrap().unwrap().from_ts).collect();
+        from.sort_unstable();
+        assert_eq!(from, vec![0, 10, 20, 30]);
+
+        setup(&db, JOB, 0).await;
+    }
+}
@@ -3,8 +3,8 @@ use std::sync::Arc;
 use chrono::NaiveDateTime;
 use diesel::{insert_into, query_dsl::methods::FilterDsl, SelectableHelper};
 
-use crate::Order;
 use crate::{models::block::BlockModel, DbPool};
+use crate::{BlockRange, Order};
 use anyhow::Result;
 use diesel::ExpressionMethods;
 
@@ -14,16 +14,18 @@ use diesel_async::RunQueryDsl;
 
 /// Insert blocks into the database
 #[allow(clippy::get_first)]
-pub async fn insert_blocks_to_db(db: Arc<DbPool>, block_models: Vec<BlockModel>) -> Result<()> {
+pub async fn insert_blocks_to_db(
+    conn: &mut diesel_async::AsyncPgConnection,
+    block_models: Vec<BlockModel>,
+) -> Result<()> {
     if block_models.is_empty() {
         return Ok(());
     }
-    let mut conn = db.get().await?;
     insert_into(crate::schema::blocks::table)
         .values(&block_models)
         .on_conflict(crate::schema::blocks::hash)
         .do_nothing()
-        .execute(&mut conn)
+        .execute(conn)
         .await?;
     tracing::info!(
         "Inserted {} blocks from timestamp {} to timestamp {}",
@@ -43,6 +45,15 @@ pub async fn get_block_by_hash(db: Arc<DbPool>, block_hash: &str) -> Result<Opti
     Ok(block)
 }
 
+/// Get the blocks of several hashes at once, unknown hashes are skipped
+pub async fn get_blocks_by_hashes(db: Arc<DbPool>, block_hashes: &[String]) -> Result<Vec<BlockModel>> {
+    use crate::schema::blocks::dsl::*;
+
+    let mut conn = db.get().await?;
+    let models = blocks.filter(hash.eq_any(block_hashes)).select(BlockModel::as_select()).load(&mut conn).await?;
+    Ok(models)
+}
+
 /** Fetch bloch-hashes belonging to the input chain-index at a height, ignoring/filtering-out one
  * block-hash.
  *
@@ -106,14 +117,44 @@ pub async fn get_blocks(db: Arc<DbPool>, limit: i64, offset: i64, order: Option<
     }
 }
 
-pub async fn get_block_by_height(db: Arc<DbPool>, height_value: i64) -> Result<Option<BlockModel>> {
+/// Get all blocks at a height, including uncles and forks, main chain blocks first.
+///
+/// When `chain` is `Some((from_group, to_group))` only blocks of that chain index are returned.
+pub async fn get_blocks_by_height(
+    db: Arc<DbPool>,
+    height_value: i64,
+    chain: Option<(i64, i64)>,
+) -> Result<Vec<BlockModel>> {
     use crate::schema::blocks::dsl::*;
 
     let mut conn = db.get().await?;
-    let block_model =
-        blocks.filter(height.eq(height_value)).select(BlockModel::as_select()).first(&mut conn).await.ok();
+    let mut query = diesel::QueryDsl::into_boxed(blocks.filter(height.eq(height_value)));
+    if let Some((from_group, to_group)) = chain {
+        query = query.filter(chain_from.eq(from_group)).filter(chain_to.eq(to_group));
+    }
+    let block_models = query
+        .order((chain_from.asc(), chain_to.asc(), main_chain.desc(), hash.asc()))
+        .select(BlockModel::as_select())
+        .load(&mut conn)
+        .await?;
 
-    Ok(block_model)
+    Ok(block_models)
+}
+
+/// Get the blocks that reference `block_hash` in their `deps`, i.e. its children in the BlockFlow DAG.
+pub async fn get_block_children(db: Arc<DbPool>, block_hash: &str) -> Result<Vec<BlockModel>> {
+    use crate::schema::blocks::dsl::*;
+    use diesel::PgArrayExpressionMethods;
+
+    let mut conn = db.get().await?;
+    let block_models = blocks
+        .filter(deps.contains(vec![Some(block_hash.to_string())]))
+        .order((chain_from.asc(), chain_to.asc(), main_chain.desc(), hash.asc()))
+        .select(BlockModel::as_select())
+        .load(&mut conn)
+        .await?;
+
+    Ok(block_models)
 }
 
 pub async fn exists_block(db: Arc<DbPool>, block_hash_value: &str) -> Result<bool> {
@@ -159,3 +200,74 @@ pub async fn get_latest_block(db: &Arc<DbPool>, from_group: i64, to_group: i64)
 
     Ok(block_model)
 }
+
+/// Delete the blocks with a timestamp in `range`, returning the number of rows removed.
+pub async fn delete_blocks_in_range(conn: &mut diesel_async::AsyncPgConnection, range: &BlockRange) -> Result<usize> {
+    use crate::schema::blocks::dsl::*;
+
+    let (from, to) = range.as_datetimes();
+    let deleted = diesel::delete(blocks.filter(timestamp.between(from, to))).execute(conn).await?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils::{cleanup, create_test_pool};
+
+    const HEIGHT: i64 = 987_654_321;
+    const PARENT: &str = "test-block-height-parent";
+    const MAIN: &str = "test-block-height-main";
+    const UNCLE: &str = "test-block-height-uncle";
+    const OTHER_CHAIN: &str = "test-block-height-other-chain";
+
+    fn block(hash: &str, chain_from: i64, height: i64, main_chain: bool, deps: &[&str]) -> BlockModel {
+        BlockModel {
+            hash: hash.to_string(),
+            timestamp: chrono::Utc::now().naive_utc(),
+            chain_from,
+            chain_to: 0,
+            height,
+            deps: deps.iter().map(|d| Some(d.to_string())).collect(),
+            nonce: String::new(),
+            version: String::new(),
+            dep_state_hash: String::new(),
+            txs_hash: String::new(),
+            tx_number: 0,
+            target: String::new(),
+            main_chain,
+            ghost_uncles: serde_json::json!([]),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_blocks_by_height_and_children() {
+        let db = create_test_pool().await;
+        let hashes = [PARENT, MAIN, UNCLE, OTHER_CHAIN];
+        cleanup(&db, &[], &hashes).await;
+        let blocks = vec![
+            block(PARENT, 0, HEIGHT - 1, true, &[]),
+            block(UNCLE, 0, HEIGHT, false, &[PARENT]),
+            block(MAIN, 0, HEIGHT, true, &[PARENT]),
+            block(OTHER_CHAIN, 1, HEIGHT, true, &[]),
+        ];
+        insert_blocks_to_db(&mut db.get().await.unwrap(), blocks).await.unwrap();
+
+        let hashes_of = |blocks: Vec<BlockModel>| blocks.into_iter().map(|b| b.hash).collect::<Vec<_>>();
+
+        // Every chain, main chain blocks first within a chain
+        let all = get_blocks_by_height(db.clone(), HEIGHT, None).await.unwrap();
+        assert_eq!(hashes_of(all), vec![MAIN, UNCLE, OTHER_CHAIN]);
+
+        let chain = get_blocks_by_height(db.clone(), HEIGHT, Some((1, 0))).await.unwrap();
+        assert_eq!(hashes_of(chain), vec![OTHER_CHAIN]);
+        assert!(get_blocks_by_height(db.clone(), HEIGHT, Some((2, 0))).await.unwrap().is_empty());
+
+        let children = get_block_children(db.clone(), PARENT).await.unwrap();
+        assert_eq!(hashes_of(children), vec![MAIN, UNCLE]);
+        assert!(get_block_children(db.clone(), MAIN).await.unwrap().is_empty());
+
+        cleanup(&db, &[], &hashes).await;
+    }
+}
@@ -1,37 +1,18 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::{models::event::EventModel, DbPool};
+use crate::{models::event::EventModel, BlockRange, DbPool};
 use anyhow::Result;
 use diesel::insert_into;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
 /// Insert events into the database.
-pub async fn insert_events_to_db(db: Arc<DbPool>, events: Vec<EventModel>) -> Result<()> {
-    // Log the start with event count
-    tracing::debug!("Starting DB insertion process for {} events", events.len());
-
-    // 1. Acquire connection with timeout
-    tracing::debug!("Attempting to acquire DB connection");
-    let conn_future = db.get();
-    let mut conn = match tokio::time::timeout(Duration::from_secs(5), conn_future).await {
-        Ok(result) => {
-            let conn = result?;
-            tracing::info!("Successfully acquired DB connection");
-            conn
-        }
-        Err(_) => {
-            tracing::error!("Timed out waiting for database connection");
-            return Err(anyhow::anyhow!("Timed out waiting for database connection"));
-        }
-    };
-
-    // Now try the full batch
+pub async fn insert_events_to_db(conn: &mut diesel_async::AsyncPgConnection, events: Vec<EventModel>) -> Result<()> {
     tracing::debug!("Executing full insert query for {} events", events.len());
     match tokio::time::timeout(
         Duration::from_secs(30), // Increased timeout for larger batches
-        insert_into(crate::schema::events::table).values(&events).on_conflict_do_nothing().execute(&mut conn),
+        insert_into(crate::schema::events::table).values(&events).on_conflict_do_nothing().execute(conn),
     )
     .await
     {
@@ -104,3 +85,107 @@ pub async fn get_events_by_tx(
 
     Ok(event_models)
 }
+
+/// Get the events of several transactions at once, in emission order within each transaction,
+/// with `limit` and `offset` applied to each transaction.
+pub async fn get_events_by_txs(db: Arc<DbPool>, tx_ids: &[String], limit: i64, offset: i64) -> Result<Vec<EventModel>> {
+    use diesel::sql_types::{Array, BigInt, Text};
+
+    let mut conn = db.get().await?;
+    let event_models = diesel::sql_query(
+        r#"
+        SELECT e.* FROM unnest($1::text[]) AS t(id)
+        CROSS JOIN LATERAL (
+            SELECT * FROM events WHERE tx_id = t.id ORDER BY position, event_index, id LIMIT $2 OFFSET $3
+        ) e
+        "#,
+    )
+    .bind::<Array<Text>, _>(tx_ids)
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .load(&mut conn)
+    .await?;
+    Ok(event_models)
+}
+
+/// Get every event emitted by a transaction, in emission order. Events indexed before the order
+/// was recorded come last, by index.
+pub async fn get_tx_events(db: Arc<DbPool>, tx_id_value: &str) -> Result<Vec<EventModel>> {
+    use crate::schema::events::dsl::*;
+
+    let mut conn = db.get().await?;
+
+    let event_models: Vec<EventModel> = events
+        .filter(tx_id.eq(tx_id_value))
+        .order((position.asc(), event_index.asc(), id.asc()))
+        .select(EventModel::as_select())
+        .load(&mut conn)
+        .await?;
+
+    Ok(event_models)
+}
+
+/// Delete the events emitted by transactions of blocks with a timestamp in `range`.
+/// Events only reference their transaction, so this relies on the transactions and blocks
+/// of the range still being stored.
+pub async fn delete_events_in_range(conn: &mut diesel_async::AsyncPgConnection, range: &BlockRange) -> Result<usize> {
+    let (from, to) = range.as_datetimes();
+    let deleted = diesel::sql_query(
+        r#"
+        DELETE FROM events
+        WHERE tx_id IN (
+            SELECT t.tx_hash
+            FROM transactions t
+            JOIN blocks b ON b.hash = t.block_hash
+            WHERE b.timestamp BETWEEN $1 AND $2
+        )
+        "#,
+    )
+    .bind::<diesel::sql_types::Timestamp, _>(from)
+    .bind::<diesel::sql_types::Timestamp, _>(to)
+    .execute(conn)
+    .await?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils::create_test_pool;
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_tx_events_in_emission_order() {
+        let db = create_test_pool().await;
+        let event = |id: &str, event_index: i32, position: Option<i32>| EventModel {
+            id: id.to_string(),
+            tx_id: "test-emission-tx".to_string(),
+            contract_address: "contract".to_string(),
+            event_index,
+            fields: serde_json::json!([]),
+            position,
+        };
+        let mut conn = db.get().await.unwrap();
+        let delete =
+            diesel::delete(crate::schema::events::table).filter(crate::schema::events::tx_id.eq("test-emission-tx"));
+        delete.clone().execute(&mut conn).await.unwrap();
+        insert_events_to_db(
+            &mut conn,
+            vec![
+                event("test-emission-legacy", 0, None),
+                event("test-emission-second", 2, Some(1)),
+                event("test-emission-first", 5, Some(0)),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let ids = |events: Vec<EventModel>| events.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        let expected = vec!["test-emission-first", "test-emission-second", "test-emission-legacy"];
+        assert_eq!(ids(get_tx_events(db.clone(), "test-emission-tx").await.unwrap()), expected);
+        let txs = vec!["test-emission-tx".to_string()];
+        assert_eq!(ids(get_events_by_txs(db.clone(), &txs, 10, 0).await.unwrap()), expected);
+
+        delete.execute(&mut conn).await.unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use diesel::sql_types::BigInt;
+use diesel_async::RunQueryDsl;
+use serde::Serialize;
+
+use crate::DbPool;
+
+/// A run of consecutive heights missing on one chain, bounded by the stored blocks around it.
+#[derive(Debug, Clone, PartialEq, Eq, diesel::QueryableByName, Serialize)]
+pub struct HeightGap {
+    #[diesel(sql_type = BigInt)]
+    pub chain_from: i64,
+    #[diesel(sql_type = BigInt)]
+    pub chain_to: i64,
+    /// First missing height
+    #[diesel(sql_type = BigInt)]
+    pub from_height: i64,
+    /// Last missing height
+    #[diesel(sql_type = BigInt)]
+    pub to_height: i64,
+    /// Timestamp in milliseconds of the stored block before the gap
+    #[diesel(sql_type = BigInt)]
+    pub from_ts: i64,
+    /// Timestamp in milliseconds of the stored block after the gap
+    #[diesel(sql_type = BigInt)]
+    pub to_ts: i64,
+}
+
+impl HeightGap {
+    pub fn missing_blocks(&self) -> i64 {
+        self.to_height - self.from_height + 1
+    }
+}
+
+/// Missing heights of one chain, summarised as the number of holes and blocks missing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainGapSummary {
+    pub chain_from: i64,
+    pub chain_to: i64,
+    pub gap_count: i64,
+    pub missing_blocks: i64,
+}
+
+/// A period in which no block is stored for one chain.
+#[derive(Debug, Clone, PartialEq, Eq, diesel::QueryableByName, Serialize)]
+pub struct TimestampGap {
+    #[diesel(sql_type = BigInt)]
+    pub chain_from: i64,
+    #[diesel(sql_type = BigInt)]
+    pub chain_to: i64,
+    /// Timestamp in milliseconds of the last block before the gap
+    #[diesel(sql_type = BigInt)]
+    pub from_ts: i64,
+    /// Timestamp in milliseconds of the first block after the gap
+    #[diesel(sql_type = BigInt)]
+    pub to_ts: i64,
+}
+
+impl TimestampGap {
+    pub fn duration_ms(&self) -> i64 {
+        self.to_ts - self.from_ts
+    }
+}
+
+/// Height holes per chain, considering blocks at or above `min_height` with a timestamp at or
+/// after `since_ts` (milliseconds). Ordered by chain and height.
+pub async fn get_height_gaps(db: &Arc<DbPool>, min_height: i64, since_ts: i64) -> Result<Vec<HeightGap>> {
+    let mut conn = db.get().await?;
+    let gaps = diesel::sql_query(
+        r#"
+        SELECT chain_from,
+               chain_to,
+               prev_height + 1 AS from_height,
+               height - 1 AS to_height,
+               (EXTRACT(EPOCH FROM prev_timestamp) * 1000)::BIGINT AS from_ts,
+               (EXTRACT(EPOCH FROM timestamp) * 1000)::BIGINT AS to_ts
+        FROM (
+            SELECT chain_from,
+                   chain_to,
+                   height,
+                   timestamp,
+                   LAG(height) OVER (PARTITION BY chain_from, chain_to ORDER BY height) AS prev_height,
+                   LAG(timestamp) OVER (PARTITION BY chain_from, chain_to ORDER BY height) AS prev_timestamp
+            FROM (
+                SELECT chain_from, chain_to, height, MIN(timestamp) AS timestamp
+                FROM blocks
+                WHERE height >= $1 AND timestamp >= to_timestamp($2 / 1000.0) AT TIME ZONE 'UTC'
+                GROUP BY chain_from, chain_to, height
+            ) heights
+        ) gaps
+        WHERE height - prev_height > 1
+        ORDER BY chain_from, chain_to, from_height
+        "#,
+    )
+    .bind::<BigInt, _>(min_height)
+    .bind::<BigInt, _>(since_ts)
+    .load(&mut conn)
+    .await?;
+    Ok(gaps)
+}
+
+/// Per-chain summary of the height gaps between the lowest and highest stored block.
+/// Chains without gaps are omitted.
+pub async fn get_block_gap_summary(db: &Arc<DbPool>) -> Result<Vec<ChainGapSummary>> {
+    Ok(summarize_height_gaps(&get_height_gaps(db, 0, 0).await?))
+}
+
+/// Group height gaps ordered by chain, as returned by [`get_height_gaps`], into one summary per chain.
+pub fn summarize_height_gaps(gaps: &[HeightGap]) -> Vec<ChainGapSummary> {
+    let mut summary: Vec<ChainGapSummary> = Vec::new();
+    for gap in gaps {
+        match summary.last_mut() {
+            Some(chain) if (chain.chain_from, chain.chain_to) == (gap.chain_from, gap.chain_to) => {
+                chain.gap_count += 1;
+                chain.missing_blocks += gap.missing_blocks();
+            }
+            _ => summary.push(ChainGapSummary {
+                chain_from: gap.chain_from,
+                chain_to: gap.chain_to,
+                gap_count: 1,
+                missing_blocks: gap.missing_blocks(),
+            }),
+        }
+    }
+    summary
+}
+
+/// Periods longer than `min_gap_ms` without any stored block on a chain, considering blocks
+/// with a timestamp at or after `since_ts` (milliseconds). Ordered by chain and timestamp.
+pub async fn get_timestamp_gaps(db: &Arc<DbPool>, min_gap_ms: i64, since_ts: i64) -> Result<Vec<TimestampGap>> {
+    let mut conn = db.get().await?;
+    let gaps = diesel::sql_query(
+        r#"
+        SELECT chain_from, chain_to, from_ts, to_ts
+        FROM (
+            SELECT chain_from,
+                   chain_to,
+                   (EXTRACT(EPOCH FROM LAG(timestamp) OVER w) * 1000)::BIGINT AS from_ts,
+                   (EXTRACT(EPOCH FROM timestamp) * 1000)::BIGINT AS to_ts
+            FROM blocks
+            WHERE timestamp >= to_timestamp($2 / 1000.0) AT TIME ZONE 'UTC'
+            WINDOW w AS (PARTITION BY chain_from, chain_to ORDER BY timestamp)
+        ) gaps
+        WHERE to_ts - from_ts > $1
+        ORDER BY chain_from, chain_to, from_ts
+        "#,
+    )
+    .bind::<BigInt, _>(min_gap_ms)
+    .bind::<BigInt, _>(since_ts)
+    .load(&mut conn)
+    .await?;
+    Ok(gaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gap(chain_from: i64, chain_to: i64, from_height: i64, to_height: i64) -> HeightGap {
+        HeightGap { chain_from, chain_to, from_height, to_height, from_ts: 0, to_ts: 0 }
+    }
+
+    #[test]
+    fn test_summarize_height_gaps() {
+        let gaps = [gap(0, 0, 5, 5), gap(0, 0, 10, 14), gap(0, 1, 3, 4)];
+        assert_eq!(
+            summarize_height_gaps(&gaps),
+            vec![
+                ChainGapSummary { chain_from: 0, chain_to: 0, gap_count: 2, missing_blocks: 6 },
+                ChainGapSummary { chain_from: 0, chain_to: 1, gap_count: 1, missing_blocks: 2 },
+            ]
+        );
+        assert!(summarize_height_gaps(&[]).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::sql_types::{BigInt, Numeric, Text, Timestamp};
+use diesel::{insert_into, QueryableByName};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::mining::{BlockRewardModel, GhostUncleModel};
+use crate::{BlockRange, DbPool};
+
+/// Rows per insert statement, keeps us well below the Postgres bind parameter limit.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Bucket size of the mining statistics time series.
+#[derive(Deserialize, Debug, Clone, Copy, Default, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsInterval {
+    Hour,
+    #[default]
+    Day,
+    Week,
+}
+
+impl StatsInterval {
+    /// The matching `date_trunc` field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatsInterval::Hour => "hour",
+            StatsInterval::Day => "day",
+            StatsInterval::Week => "week",
+        }
+    }
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MinerStats {
+    #[diesel(sql_type = Text)]
+    pub miner_address: String,
+    #[diesel(sql_type = BigInt)]
+    pub blocks_mined: i64,
+    #[diesel(sql_type = BigInt)]
+    pub uncles_mined: i64,
+    /// Block and uncle rewards in attoALPH
+    #[diesel(sql_type = Numeric)]
+    pub total_reward: BigDecimal,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MinerStatsBucket {
+    #[diesel(sql_type = Timestamp)]
+    pub bucket: NaiveDateTime,
+    #[diesel(sql_type = BigInt)]
+    pub blocks_mined: i64,
+    #[diesel(sql_type = BigInt)]
+    pub uncles_mined: i64,
+    #[diesel(sql_type = Numeric)]
+    pub total_reward: BigDecimal,
+}
+
+// Block and uncle rewards of main chain blocks in [$1, $2), one row per reward.
+// Blocks not stored by the block processor are assumed to be on the main chain.
+const MINING_REWARDS_SQL: &str = r#"
+    SELECT r.miner_address, r.timestamp, 1 AS blocks, 0 AS uncles, r.reward
+    FROM block_rewards r
+    LEFT JOIN blocks b ON b.hash = r.block_hash
+    WHERE r.timestamp >= $1 AND r.timestamp < $2 AND COALESCE(b.main_chain, TRUE)
+    UNION ALL
+    SELECT u.miner_address, u.timestamp, 0 AS blocks, 1 AS uncles, COALESCE(u.reward, 0) AS reward
+    FROM ghost_uncles u
+    LEFT JOIN blocks b ON b.hash = u.block_hash
+    WHERE u.timestamp >= $1 AND u.timestamp < $2 AND COALESCE(b.main_chain, TRUE)
+"#;
+
+/// Insert block rewards and ghost uncles into the database in a single transaction.
+pub async fn insert_mining_models(
+    conn: &mut diesel_async::AsyncPgConnection,
+    rewards: Vec<BlockRewardModel>,
+    uncles: Vec<GhostUncleModel>,
+) -> Result<()> {
+    if rewards.is_empty() && uncles.is_empty() {
+        return Ok(());
+    }
+    let (rewards_len, uncles_len) = (rewards.len(), uncles.len());
+
+    conn.transaction(|conn| {
+        async move {
+            for chunk in rewards.chunks(INSERT_CHUNK_SIZE) {
+                insert_into(crate::schema::block_rewards::table)
+                    .values(chunk)
+                    .on_conflict(crate::schema::block_rewards::block_hash)
+                    .do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            for chunk in uncles.chunks(INSERT_CHUNK_SIZE) {
+                insert_into(crate::schema::ghost_uncles::table)
+                    .values(chunk)
+                    .on_conflict((crate::schema::ghost_uncles::block_hash, crate::schema::ghost_uncles::uncle_hash))
+                    .do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    tracing::info!("Inserted {} block rewards and {} ghost uncles", rewards_len, uncles_len);
+    Ok(())
+}
+
+/// Per-miner block count, uncle count and rewards between `from` and `to`, most blocks first.
+pub async fn get_miner_stats(
+    db: Arc<DbPool>,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<MinerStats>> {
+    let mut conn = db.get().await?;
+    let query = format!(
+        r#"
+        SELECT miner_address,
+               SUM(blocks)::BIGINT AS blocks_mined,
+               SUM(uncles)::BIGINT AS uncles_mined,
+               SUM(reward) AS total_reward
+        FROM ({MINING_REWARDS_SQL}) rewards
+        GROUP BY miner_address
+        ORDER BY blocks_mined DESC, miner_address
+        LIMIT $3 OFFSET $4
+        "#
+    );
+    let stats = diesel::sql_query(query)
+        .bind::<Timestamp, _>(from)
+        .bind::<Timestamp, _>(to)
+        .bind::<BigInt, _>(limit)
+        .bind::<BigInt, _>(offset)
+        .load(&mut conn)
+        .await?;
+    Ok(stats)
+}
+
+/// Time series of a miner's block count, uncle count and rewards between `from` and `to`.
+pub async fn get_miner_history(
+    db: Arc<DbPool>,
+    miner: &str,
+    interval: StatsInterval,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<MinerStatsBucket>> {
+    let mut conn = db.get().await?;
+    let query = format!(
+        r#"
+        SELECT date_trunc($4, timestamp) AS bucket,
+               SUM(blocks)::BIGINT AS blocks_mined,
+               SUM(uncles)::BIGINT AS uncles_mined,
+               SUM(reward) AS total_reward
+        FROM ({MINING_REWARDS_SQL}) rewards
+        WHERE miner_address = $3
+        GROUP BY bucket
+        ORDER BY bucket
+        "#
+    );
+    let buckets = diesel::sql_query(query)
+        .bind::<Timestamp, _>(from)
+        .bind::<Timestamp, _>(to)
+        .bind::<Text, _>(miner)
+        .bind::<Text, _>(interval.as_str())
+        .load(&mut conn)
+        .await?;
+    Ok(buckets)
+}
+
+/// Delete the block rewards and ghost uncles of blocks with a timestamp in `range`.
+pub async fn delete_mining_models_in_range(
+    conn: &mut diesel_async::AsyncPgConnection,
+    range: &BlockRange,
+) -> Result<usize> {
+    use crate::schema::{block_rewards, ghost_uncles};
+    use diesel::{ExpressionMethods, QueryDsl};
+
+    let (from, to) = range.as_datetimes();
+    let rewards =
+        diesel::delete(block_rewards::table.filter(block_rewards::timestamp.between(from, to))).execute(conn).await?;
+    let uncles =
+        diesel::delete(ghost_uncles::table.filter(ghost_uncles::timestamp.between(from, to))).execute(conn).await?;
+    Ok(rewards + uncles)
+}
@@ -1,11 +1,29 @@
+pub mod address;
+pub mod api_key;
+pub mod backfill;
 pub mod block;
 pub mod event;
+pub mod gap;
+pub mod mining;
+pub mod processor_status;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod token;
 pub mod transaction;
+pub mod webhook;
 use std::sync::Arc;
 
+pub use address::*;
+pub use api_key::*;
+pub use backfill::*;
 pub use block::*;
 pub use event::*;
+pub use gap::*;
+pub use mining::*;
+pub use processor_status::*;
+pub use token::*;
 pub use transaction::*;
+pub use webhook::*;
 
 use crate::{
     errors::api::RepositoryError,
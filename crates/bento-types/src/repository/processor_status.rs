@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use diesel_async::RunQueryDsl;
+
+use crate::models::processor_status::ProcessorStatusModel;
+use crate::schema::processor_status;
+use crate::DbPool;
+
+/// Record that `processor` has stored blocks up to `last_timestamp` (milliseconds).
+/// The checkpoint never moves backwards, so backfilling old ranges leaves it untouched.
+pub async fn upsert_processor_status(db: Arc<DbPool>, processor: &str, last_timestamp: i64) -> Result<()> {
+    let mut conn = db.get().await?;
+    insert_into(processor_status::table)
+        .values(ProcessorStatusModel { processor: processor.to_string(), last_timestamp })
+        .on_conflict(processor_status::processor)
+        .do_update()
+        .set(
+            processor_status::last_timestamp
+                .eq(diesel::dsl::sql::<BigInt>("GREATEST(processor_status.last_timestamp, excluded.last_timestamp)")),
+        )
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Checkpoints of all processors, ordered by name.
+pub async fn get_processor_statuses(db: Arc<DbPool>) -> Result<Vec<ProcessorStatusModel>> {
+    let mut conn = db.get().await?;
+    let statuses = processor_status::table
+        .select(ProcessorStatusModel::as_select())
+        .order(processor_status::processor.asc())
+        .load(&mut conn)
+        .await?;
+    Ok(statuses)
+}
@@ -0,0 +1,133 @@
+//! Fixtures of the repository tests. They run against the database of the `POSTGRES_*`
+//! environment variables and are ignored unless `--include-ignored` is passed.
+
+use std::sync::Arc;
+
+use diesel::sql_types::{Bool, Text};
+use diesel_async::pooled_connection::{bb8::Pool, AsyncDieselConnectionManager};
+use diesel_async::RunQueryDsl;
+
+use crate::DbPool;
+
+pub async fn create_test_pool() -> Arc<DbPool> {
+    let config = AsyncDieselConnectionManager::new(test_database_url());
+    Arc::new(Pool::builder().max_size(4).build(config).await.expect("Failed to create test DB pool"))
+}
+
+/// URL of the test database, built from the `POSTGRES_*` environment variables
+pub fn test_database_url() -> String {
+    let var = |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.to_string());
+    format!(
+        "postgresql://{}:{}@{}:{}/{}",
+        var("POSTGRES_USER", "postgres"),
+        var("POSTGRES_PASSWORD", "postgres"),
+        var("POSTGRES_HOST", "localhost"),
+        var("POSTGRES_PORT", "5432"),
+        var("POSTGRES_DB", "bento_alephium"),
+    )
+}
+
+/// Insert an empty block at height 1 of chain 0 -> 0
+pub async fn insert_test_block(db: &DbPool, hash: &str, main_chain: bool) {
+    let mut conn = db.get().await.unwrap();
+    diesel::sql_query(
+        r#"
+        INSERT INTO blocks (hash, timestamp, chain_from, chain_to, height, nonce, version, dep_state_hash,
+                            txs_hash, tx_number, target, main_chain)
+        VALUES ($1, NOW(), 0, 0, 1, '', '', '', '', 1, '', $2)
+        "#,
+    )
+    .bind::<Text, _>(hash)
+    .bind::<Bool, _>(main_chain)
+    .execute(&mut conn)
+    .await
+    .unwrap();
+}
+
+/// Delete the rows of `tables` stored for `blocks`, then the blocks themselves
+pub async fn cleanup(db: &DbPool, tables: &[&str], blocks: &[&str]) {
+    let mut conn = db.get().await.unwrap();
+    let blocks: Vec<String> = blocks.iter().map(|b| b.to_string()).collect();
+    let statements = tables
+        .iter()
+        .map(|table| format!("DELETE FROM {table} WHERE block_hash = ANY($1)"))
+        .chain(std::iter::once("DELETE FROM blocks WHERE hash = ANY($1)".to_string()));
+    for statement in statements {
+        diesel::sql_query(statement)
+            .bind::<diesel::sql_types::Array<Text>, _>(&blocks)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+}
+
+/// A main chain block and an uncle at the same height, with outputs and inputs covering how
+/// the uncle must be ignored. Each test maps them to the models of its own tables.
+pub struct MainUncleFixture {
+    pub main: String,
+    pub uncle: String,
+}
+
+/// An output created by `tx_hash` in `block_hash`
+pub struct FixtureOutput {
+    pub key: &'static str,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub amount: i64,
+}
+
+/// An input of `tx_hash` in `block_hash` spending the output `key`
+pub struct FixtureInput {
+    pub key: &'static str,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub amount: i64,
+}
+
+impl FixtureOutput {
+    pub fn new(key: &'static str, block_hash: &str, amount: i64) -> Self {
+        Self { key, tx_hash: format!("tx-{key}"), block_hash: block_hash.to_string(), amount }
+    }
+}
+
+impl FixtureInput {
+    fn new(key: &'static str, block_hash: &str, amount: i64) -> Self {
+        Self { key, tx_hash: format!("spending-tx-{key}"), block_hash: block_hash.to_string(), amount }
+    }
+}
+
+impl MainUncleFixture {
+    pub fn new(prefix: &str) -> Self {
+        Self { main: format!("{prefix}-main"), uncle: format!("{prefix}-uncle") }
+    }
+
+    /// Remove what a previous run left in `tables` and insert both blocks
+    pub async fn setup(&self, db: &DbPool, tables: &[&str]) {
+        self.cleanup(db, tables).await;
+        insert_test_block(db, &self.main, true).await;
+        insert_test_block(db, &self.uncle, false).await;
+    }
+
+    pub async fn cleanup(&self, db: &DbPool, tables: &[&str]) {
+        cleanup(db, tables, &[&self.main, &self.uncle]).await;
+    }
+
+    /// Outputs "unspent" (100) and "spent" (200) on the main chain, "uncle-only" (400) in the
+    /// uncle, and "spent-in-uncle" (500) on the main chain. Only "unspent" and "spent-in-uncle"
+    /// remain unspent on the main chain.
+    pub fn outputs(&self) -> Vec<FixtureOutput> {
+        vec![
+            FixtureOutput::new("unspent", &self.main, 100),
+            FixtureOutput::new("spent", &self.main, 200),
+            FixtureOutput::new("uncle-only", &self.uncle, 400),
+            FixtureOutput::new("spent-in-uncle", &self.main, 500),
+            // The same transaction in an uncle block, counted once
+            FixtureOutput::new("unspent", &self.uncle, 100),
+        ]
+    }
+
+    /// Spends "spent" on the main chain and "spent-in-uncle" only in the uncle
+    pub fn inputs(&self) -> Vec<FixtureInput> {
+        vec![FixtureInput::new("spent", &self.main, 200), FixtureInput::new("spent-in-uncle", &self.uncle, 500)]
+    }
+}
@@ -0,0 +1,365 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Numeric, Text, Timestamp};
+use diesel::upsert::excluded;
+use diesel::{insert_into, QueryableByName};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+
+use crate::models::token::{TokenInputModel, TokenModel, TokenOutputModel};
+use crate::{BlockRange, DbPool};
+
+/// Rows per insert statement, keeps us well below the Postgres bind parameter limit.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct TokenStats {
+    #[diesel(sql_type = Text)]
+    pub token_id: String,
+    /// Sum of all unspent outputs holding the token
+    #[diesel(sql_type = Numeric)]
+    pub supply: BigDecimal,
+    #[diesel(sql_type = BigInt)]
+    pub holder_count: i64,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct TokenHolder {
+    #[diesel(sql_type = Text)]
+    pub address: String,
+    #[diesel(sql_type = Numeric)]
+    pub balance: BigDecimal,
+}
+
+/// Net change of an address' token balance caused by a transaction.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct TokenTransfer {
+    #[diesel(sql_type = Text)]
+    pub tx_hash: String,
+    #[diesel(sql_type = Text)]
+    pub address: String,
+    /// Positive when the address received tokens, negative when it sent them
+    #[diesel(sql_type = Numeric)]
+    pub amount: BigDecimal,
+    #[diesel(sql_type = Timestamp)]
+    pub timestamp: NaiveDateTime,
+}
+
+/// Insert discovered tokens and their output/input rows in a single transaction.
+///
+/// Tokens that already exist keep their metadata; the first-seen fields are only moved
+/// backwards in time, so batches can be stored in any order.
+pub async fn insert_token_models(
+    conn: &mut diesel_async::AsyncPgConnection,
+    new_tokens: Vec<TokenModel>,
+    outputs: Vec<TokenOutputModel>,
+    inputs: Vec<TokenInputModel>,
+) -> Result<()> {
+    use crate::schema::{token_inputs, token_outputs, tokens};
+
+    if new_tokens.is_empty() && outputs.is_empty() && inputs.is_empty() {
+        return Ok(());
+    }
+    let (tokens_len, outputs_len, inputs_len) = (new_tokens.len(), outputs.len(), inputs.len());
+
+    conn.transaction(|conn| {
+        async move {
+            for chunk in new_tokens.chunks(INSERT_CHUNK_SIZE) {
+                let upsert = insert_into(tokens::table).values(chunk).on_conflict(tokens::token_id).do_update().set((
+                    tokens::first_seen_tx_hash.eq(excluded(tokens::first_seen_tx_hash)),
+                    tokens::first_seen_block_hash.eq(excluded(tokens::first_seen_block_hash)),
+                    tokens::first_seen_at.eq(excluded(tokens::first_seen_at)),
+                ));
+                // ON CONFLICT ... DO UPDATE ... WHERE, only move first-seen backwards
+                diesel::query_dsl::methods::FilterDsl::filter(
+                    upsert,
+                    tokens::first_seen_at.gt(excluded(tokens::first_seen_at)),
+                )
+                .execute(conn)
+                .await?;
+            }
+            for chunk in outputs.chunks(INSERT_CHUNK_SIZE) {
+                insert_into(token_outputs::table)
+                    .values(chunk)
+                    .on_conflict((token_outputs::output_key, token_outputs::token_id, token_outputs::block_hash))
+                    .do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            for chunk in inputs.chunks(INSERT_CHUNK_SIZE) {
+                insert_into(token_inputs::table)
+                    .values(chunk)
+                    .on_conflict((token_inputs::output_key, token_inputs::token_id, token_inputs::block_hash))
+                    .do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    tracing::info!("Inserted {} tokens, {} token outputs and {} token inputs", tokens_len, outputs_len, inputs_len);
+    Ok(())
+}
+
+/// List known tokens, most recently discovered first.
+pub async fn get_tokens(db: Arc<DbPool>, limit: i64, offset: i64) -> Result<Vec<TokenModel>> {
+    use crate::schema::tokens::dsl::*;
+
+    let mut conn = db.get().await?;
+    let token_models = tokens
+        .order((first_seen_at.desc(), token_id))
+        .limit(limit)
+        .offset(offset)
+        .select(TokenModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(token_models)
+}
+
+pub async fn get_token(db: Arc<DbPool>, id: &str) -> Result<Option<TokenModel>> {
+    use crate::schema::tokens::dsl::*;
+
+    let mut conn = db.get().await?;
+    let token_model =
+        tokens.filter(token_id.eq(id)).select(TokenModel::as_select()).first(&mut conn).await.optional()?;
+    Ok(token_model)
+}
+
+/// Ids of the tokens whose metadata was never looked up and whose retry delay, if a previous
+/// lookup failed, has passed. Oldest first.
+pub async fn get_tokens_without_metadata(db: Arc<DbPool>, limit: i64) -> Result<Vec<String>> {
+    use crate::schema::tokens::dsl::*;
+
+    let mut conn = db.get().await?;
+    let ids = tokens
+        .filter(metadata_fetched_at.is_null())
+        .filter(metadata_retry_at.is_null().or(metadata_retry_at.le(chrono::Utc::now().naive_utc())))
+        .order((first_seen_at, token_id))
+        .limit(limit)
+        .select(token_id)
+        .load(&mut conn)
+        .await?;
+    Ok(ids)
+}
+
+/// Store the metadata read from the token contract. Fields the contract did not
+/// provide stay `None`, `metadata_fetched_at` is set so the lookup is not retried.
+pub async fn update_token_metadata(
+    db: Arc<DbPool>,
+    id: &str,
+    token_name: Option<String>,
+    token_symbol: Option<String>,
+    token_decimals: Option<i32>,
+) -> Result<()> {
+    use crate::schema::tokens::dsl::*;
+
+    let mut conn = db.get().await?;
+    diesel::update(tokens.filter(token_id.eq(id)))
+        .set((
+            name.eq(token_name),
+            symbol.eq(token_symbol),
+            decimals.eq(token_decimals),
+            metadata_fetched_at.eq(chrono::Utc::now().naive_utc()),
+            metadata_retry_at.eq(None::<NaiveDateTime>),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Record a failed metadata lookup. The token is retried after a delay that doubles with
+/// every attempt, from one minute up to a day.
+pub async fn record_token_metadata_failure(db: Arc<DbPool>, id: &str) -> Result<()> {
+    let mut conn = db.get().await?;
+    diesel::sql_query(
+        r#"
+        UPDATE tokens
+        SET metadata_attempts = metadata_attempts + 1,
+            metadata_retry_at = NOW() AT TIME ZONE 'UTC'
+                + LEAST(POWER(2, LEAST(metadata_attempts, 11)), 1440) * INTERVAL '1 minute'
+        WHERE token_id = $1
+        "#,
+    )
+    .bind::<Text, _>(id)
+    .execute(&mut conn)
+    .await?;
+    Ok(())
+}
+
+/// Circulating supply and holder count of the given tokens, computed from unspent main chain outputs.
+pub async fn get_token_stats(db: Arc<DbPool>, ids: &[String]) -> Result<Vec<TokenStats>> {
+    let mut conn = db.get().await?;
+    let stats = diesel::sql_query(
+        r#"
+        SELECT o.token_id, SUM(o.amount) AS supply, COUNT(DISTINCT o.address) AS holder_count
+        FROM token_outputs o
+        JOIN blocks b ON b.hash = o.block_hash AND b.main_chain
+        WHERE o.token_id = ANY($1)
+          AND NOT EXISTS (
+            SELECT 1 FROM token_inputs i
+            JOIN blocks ib ON ib.hash = i.block_hash AND ib.main_chain
+            WHERE i.output_key = o.output_key AND i.token_id = o.token_id
+          )
+        GROUP BY o.token_id
+        "#,
+    )
+    .bind::<Array<Text>, _>(ids)
+    .load(&mut conn)
+    .await?;
+    Ok(stats)
+}
+
+/// Addresses holding a token, largest balance first.
+pub async fn get_token_holders(db: Arc<DbPool>, id: &str, limit: i64, offset: i64) -> Result<Vec<TokenHolder>> {
+    let mut conn = db.get().await?;
+    let holders = diesel::sql_query(
+        r#"
+        SELECT o.address, SUM(o.amount) AS balance
+        FROM token_outputs o
+        JOIN blocks b ON b.hash = o.block_hash AND b.main_chain
+        WHERE o.token_id = $1
+          AND NOT EXISTS (
+            SELECT 1 FROM token_inputs i
+            JOIN blocks ib ON ib.hash = i.block_hash AND ib.main_chain
+            WHERE i.output_key = o.output_key AND i.token_id = o.token_id
+          )
+        GROUP BY o.address
+        ORDER BY balance DESC, o.address
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind::<Text, _>(id)
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .load(&mut conn)
+    .await?;
+    Ok(holders)
+}
+
+/// Per-transaction balance changes of a token on the main chain, newest first.
+pub async fn get_token_transfers(db: Arc<DbPool>, id: &str, limit: i64, offset: i64) -> Result<Vec<TokenTransfer>> {
+    let mut conn = db.get().await?;
+    let transfers = diesel::sql_query(
+        r#"
+        SELECT tx_hash, address, SUM(amount) AS amount, MAX(timestamp) AS timestamp
+        FROM (
+            SELECT o.tx_hash, o.address, o.amount, o.timestamp FROM token_outputs o
+            JOIN blocks b ON b.hash = o.block_hash AND b.main_chain
+            WHERE o.token_id = $1
+            UNION ALL
+            SELECT i.tx_hash, i.address, -i.amount, i.timestamp FROM token_inputs i
+            JOIN blocks b ON b.hash = i.block_hash AND b.main_chain
+            WHERE i.token_id = $1
+        ) movements
+        GROUP BY tx_hash, address
+        HAVING SUM(amount) <> 0
+        ORDER BY MAX(timestamp) DESC, tx_hash, address
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind::<Text, _>(id)
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .load(&mut conn)
+    .await?;
+    Ok(transfers)
+}
+
+/// Delete the token outputs and inputs of blocks with a timestamp in `range`.
+/// Discovered tokens are kept, they are registered again with `ON CONFLICT DO NOTHING`.
+pub async fn delete_token_models_in_range(
+    conn: &mut diesel_async::AsyncPgConnection,
+    range: &BlockRange,
+) -> Result<usize> {
+    use crate::schema::{token_inputs, token_outputs};
+
+    let (from, to) = range.as_datetimes();
+    let outputs =
+        diesel::delete(token_outputs::table.filter(token_outputs::timestamp.between(from, to))).execute(conn).await?;
+    let inputs =
+        diesel::delete(token_inputs::table.filter(token_inputs::timestamp.between(from, to))).execute(conn).await?;
+    Ok(outputs + inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils::{create_test_pool, FixtureInput, FixtureOutput, MainUncleFixture};
+
+    const TOKEN: &str = "test-token-stats";
+    const TABLES: &[&str] = &["token_outputs", "token_inputs"];
+
+    /// Holder of each fixture output
+    fn owner(key: &str) -> String {
+        match key {
+            "uncle-only" => "bob",
+            "spent-in-uncle" => "carol",
+            _ => "alice",
+        }
+        .to_string()
+    }
+
+    fn output(o: FixtureOutput) -> TokenOutputModel {
+        TokenOutputModel {
+            output_key: o.key.to_string(),
+            token_id: TOKEN.to_string(),
+            tx_hash: o.tx_hash,
+            block_hash: o.block_hash,
+            address: owner(o.key),
+            amount: BigDecimal::from(o.amount),
+            timestamp: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    fn input(i: FixtureInput) -> TokenInputModel {
+        TokenInputModel {
+            output_key: i.key.to_string(),
+            token_id: TOKEN.to_string(),
+            tx_hash: i.tx_hash,
+            block_hash: i.block_hash,
+            address: owner(i.key),
+            amount: BigDecimal::from(i.amount),
+            timestamp: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_token_stats_main_chain() {
+        let db = create_test_pool().await;
+        let fixture = MainUncleFixture::new(TOKEN);
+        fixture.setup(&db, TABLES).await;
+
+        let outputs = fixture.outputs().into_iter().map(output).collect();
+        let inputs = fixture.inputs().into_iter().map(input).collect();
+        insert_token_models(&mut db.get().await.unwrap(), vec![], outputs, inputs).await.unwrap();
+
+        let stats = get_token_stats(db.clone(), &[TOKEN.to_string()]).await.unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].supply, BigDecimal::from(600));
+        assert_eq!(stats[0].holder_count, 2);
+
+        let holders: Vec<(String, BigDecimal)> = get_token_holders(db.clone(), TOKEN, 10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|h| (h.address, h.balance))
+            .collect();
+        assert_eq!(
+            holders,
+            vec![("carol".to_string(), BigDecimal::from(500)), ("alice".to_string(), BigDecimal::from(100))]
+        );
+
+        let transfers = get_token_transfers(db.clone(), TOKEN, 10, 0).await.unwrap();
+        assert_eq!(transfers.len(), 4);
+        assert!(transfers.iter().all(|t| t.address != "bob" && t.tx_hash != "spending-tx-spent-in-uncle"));
+
+        fixture.cleanup(&db, TABLES).await;
+    }
+}
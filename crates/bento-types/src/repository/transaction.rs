@@ -2,20 +2,19 @@ use std::sync::Arc;
 
 use diesel::insert_into;
 
-use crate::{models::transaction::TransactionModel, DbPool};
+use crate::{decoded::DecodedTransaction, models::transaction::TransactionModel, BlockRange, DbPool};
 use anyhow::Result;
 use diesel_async::RunQueryDsl;
 
 use diesel::prelude::*;
 
 /// Insert txs into the database
-pub async fn insert_txs_to_db(db: Arc<DbPool>, txs: Vec<TransactionModel>) -> Result<()> {
-    let mut conn = db.get().await?;
+pub async fn insert_txs_to_db(conn: &mut diesel_async::AsyncPgConnection, txs: Vec<TransactionModel>) -> Result<()> {
     insert_into(crate::schema::transactions::table)
         .values(&txs)
         .on_conflict(crate::schema::transactions::tx_hash)
         .do_nothing()
-        .execute(&mut conn)
+        .execute(conn)
         .await?;
 
     tracing::info!("Inserted {} txs", txs.len());
@@ -49,6 +48,21 @@ pub async fn get_tx_by_hash(db: Arc<DbPool>, tx_hash_value: &str) -> Result<Opti
     Ok(tx)
 }
 
+/// Get a transaction decoded into balance changes, with its block and events
+pub async fn get_decoded_tx(db: Arc<DbPool>, tx_hash_value: &str) -> Result<Option<DecodedTransaction>> {
+    let Some(tx) = get_tx_by_hash(db.clone(), tx_hash_value).await? else {
+        return Ok(None);
+    };
+
+    let block = match &tx.block_hash {
+        Some(block_hash) => crate::repository::block::get_block_by_hash(db.clone(), block_hash).await?,
+        None => None,
+    };
+    let events = crate::repository::event::get_tx_events(db, tx_hash_value).await?;
+
+    Ok(Some(DecodedTransaction::decode(&tx, block.as_ref(), events)?))
+}
+
 /// Get transaction by block
 pub async fn get_txs_by_block(db: Arc<DbPool>, block_hash_value: &str) -> Result<Vec<TransactionModel>> {
     use crate::schema::transactions::dsl::*;
@@ -67,3 +81,113 @@ pub async fn get_txs_by_block(db: Arc<DbPool>, block_hash_value: &str) -> Result
 
     Ok(txs)
 }
+
+/// Get the transactions of several transaction hashes at once, unknown hashes are skipped
+pub async fn get_txs_by_hashes(db: Arc<DbPool>, tx_hashes: &[String]) -> Result<Vec<TransactionModel>> {
+    use crate::schema::transactions::dsl::*;
+
+    let mut conn = db.get().await?;
+    let txs =
+        transactions.filter(tx_hash.eq_any(tx_hashes)).select(TransactionModel::as_select()).load(&mut conn).await?;
+    Ok(txs)
+}
+
+/// Get the transactions of several blocks at once, ordered by hash within each block, with
+/// `limit` and `offset` applied to each block.
+pub async fn get_txs_by_blocks(
+    db: Arc<DbPool>,
+    block_hashes: &[String],
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<TransactionModel>> {
+    use diesel::sql_types::{Array, BigInt, Text};
+
+    let mut conn = db.get().await?;
+    let txs = diesel::sql_query(
+        r#"
+        SELECT t.* FROM unnest($1::text[]) AS b(hash)
+        CROSS JOIN LATERAL (
+            SELECT * FROM transactions WHERE block_hash = b.hash ORDER BY tx_hash LIMIT $2 OFFSET $3
+        ) t
+        "#,
+    )
+    .bind::<Array<Text>, _>(block_hashes)
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .load(&mut conn)
+    .await?;
+    Ok(txs)
+}
+
+/// Delete the transactions of blocks with a timestamp in `range`.
+pub async fn delete_txs_in_range(conn: &mut diesel_async::AsyncPgConnection, range: &BlockRange) -> Result<usize> {
+    let (from, to) = range.as_datetimes();
+    let deleted = diesel::sql_query(
+        r#"
+        DELETE FROM transactions
+        WHERE block_hash IN (SELECT hash FROM blocks WHERE timestamp BETWEEN $1 AND $2)
+        "#,
+    )
+    .bind::<diesel::sql_types::Timestamp, _>(from)
+    .bind::<diesel::sql_types::Timestamp, _>(to)
+    .execute(conn)
+    .await?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::test_utils::{cleanup, create_test_pool, insert_test_block};
+
+    const BLOCKS: [&str; 2] = ["test-txs-by-blocks-1", "test-txs-by-blocks-2"];
+
+    fn tx(tx_hash: &str, block_hash: &str) -> TransactionModel {
+        TransactionModel {
+            tx_hash: tx_hash.to_string(),
+            unsigned: serde_json::json!({}),
+            script_execution_ok: true,
+            contract_inputs: serde_json::json!([]),
+            generated_outputs: serde_json::json!([]),
+            input_signatures: vec![],
+            script_signatures: vec![],
+            block_hash: Some(block_hash.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database"]
+    async fn test_txs_by_blocks_paginated_per_block() {
+        let db = create_test_pool().await;
+        cleanup(&db, &["transactions"], &BLOCKS).await;
+        for block in BLOCKS {
+            insert_test_block(&db, block, true).await;
+        }
+        let txs = ["a", "b", "c"]
+            .iter()
+            .flat_map(|suffix| BLOCKS.iter().map(move |block| tx(&format!("{block}-{suffix}"), block)))
+            .collect();
+        insert_txs_to_db(&mut db.get().await.unwrap(), txs).await.unwrap();
+
+        let blocks: Vec<String> = BLOCKS.iter().map(|b| b.to_string()).collect();
+        let mut hashes: Vec<String> =
+            get_txs_by_blocks(db.clone(), &blocks, 2, 1).await.unwrap().into_iter().map(|tx| tx.tx_hash).collect();
+        hashes.sort();
+        assert_eq!(
+            hashes,
+            vec![
+                "test-txs-by-blocks-1-b",
+                "test-txs-by-blocks-1-c",
+                "test-txs-by-blocks-2-b",
+                "test-txs-by-blocks-2-c"
+            ]
+        );
+
+        let found = get_txs_by_hashes(db.clone(), &["test-txs-by-blocks-2-a".to_string(), "unknown".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+
+        cleanup(&db, &["transactions"], &BLOCKS).await;
+    }
+}
@@ -0,0 +1,270 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDateTime, Utc};
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use crate::models::webhook::{
+    NewWebhookDeliveryModel, NewWebhookSubscriptionModel, WebhookDeadLetterModel, WebhookDeliveryModel,
+    WebhookSubscriptionModel,
+};
+use crate::schema::{webhook_dead_letters, webhook_deliveries, webhook_subscriptions};
+use crate::DbPool;
+
+/// Prefix of generated webhook secrets.
+pub const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+
+/// Generate a new random webhook secret.
+pub fn generate_webhook_secret() -> String {
+    format!("{}{}", WEBHOOK_SECRET_PREFIX, uuid::Uuid::new_v4().simple())
+}
+
+/// Create a subscription with a generated secret, returned in the model.
+pub async fn create_webhook_subscription(
+    db: Arc<DbPool>,
+    subscription: NewWebhookSubscriptionModel,
+) -> Result<WebhookSubscriptionModel> {
+    let subscription = NewWebhookSubscriptionModel { secret: generate_webhook_secret(), ..subscription };
+
+    let mut conn = db.get().await?;
+    let model = insert_into(webhook_subscriptions::table)
+        .values(&subscription)
+        .returning(WebhookSubscriptionModel::as_returning())
+        .get_result(&mut conn)
+        .await?;
+    Ok(model)
+}
+
+/// List subscriptions, disabled ones included, ordered by name
+pub async fn list_webhook_subscriptions(db: Arc<DbPool>) -> Result<Vec<WebhookSubscriptionModel>> {
+    let mut conn = db.get().await?;
+    let models = webhook_subscriptions::table
+        .order(webhook_subscriptions::name.asc())
+        .select(WebhookSubscriptionModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(models)
+}
+
+/// Get the subscription named `name`
+pub async fn get_webhook_subscription(db: Arc<DbPool>, name: &str) -> Result<Option<WebhookSubscriptionModel>> {
+    let mut conn = db.get().await?;
+    let model = webhook_subscriptions::table
+        .filter(webhook_subscriptions::name.eq(name))
+        .select(WebhookSubscriptionModel::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?;
+    Ok(model)
+}
+
+/// Subscriptions that are not disabled
+pub async fn get_active_webhook_subscriptions(db: &Arc<DbPool>) -> Result<Vec<WebhookSubscriptionModel>> {
+    let mut conn = db.get().await?;
+    let models = webhook_subscriptions::table
+        .filter(webhook_subscriptions::disabled_at.is_null())
+        .select(WebhookSubscriptionModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(models)
+}
+
+/// Subscriptions by id, disabled ones included
+pub async fn get_webhook_subscriptions_by_ids(db: &Arc<DbPool>, ids: &[i64]) -> Result<Vec<WebhookSubscriptionModel>> {
+    let mut conn = db.get().await?;
+    let models = webhook_subscriptions::table
+        .filter(webhook_subscriptions::id.eq_any(ids))
+        .select(WebhookSubscriptionModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(models)
+}
+
+/// Disable the subscription named `name`, its pending deliveries are dropped
+pub async fn disable_webhook_subscription(db: Arc<DbPool>, name: &str) -> Result<()> {
+    let mut conn = db.get().await?;
+    let disabled: Option<i64> = conn
+        .transaction(|conn| {
+            async move {
+                let disabled = diesel::update(webhook_subscriptions::table)
+                    .filter(webhook_subscriptions::name.eq(name))
+                    .filter(webhook_subscriptions::disabled_at.is_null())
+                    .set(webhook_subscriptions::disabled_at.eq(Utc::now().naive_utc()))
+                    .returning(webhook_subscriptions::id)
+                    .get_result(conn)
+                    .await
+                    .optional()?;
+                if let Some(id) = disabled {
+                    diesel::delete(webhook_deliveries::table.filter(webhook_deliveries::subscription_id.eq(id)))
+                        .execute(conn)
+                        .await?;
+                }
+                diesel::result::QueryResult::Ok(disabled)
+            }
+            .scope_boxed()
+        })
+        .await?;
+    if disabled.is_none() {
+        return Err(anyhow!("No active webhook subscription named {name}"));
+    }
+    Ok(())
+}
+
+/// Enqueue deliveries, skipping notifications already pending for their subscription. Runs on
+/// a caller provided connection so the deliveries commit with the output they notify.
+pub async fn enqueue_webhook_deliveries(
+    conn: &mut AsyncPgConnection,
+    deliveries: &[NewWebhookDeliveryModel],
+) -> Result<usize> {
+    if deliveries.is_empty() {
+        return Ok(0);
+    }
+    let inserted = insert_into(webhook_deliveries::table)
+        .values(deliveries)
+        .on_conflict((webhook_deliveries::subscription_id, webhook_deliveries::notification_key))
+        .do_nothing()
+        .execute(conn)
+        .await?;
+    Ok(inserted)
+}
+
+/// Claim up to `limit` due deliveries by moving their next attempt to `lease_until`, so other
+/// dispatchers skip them while they are delivered. A dispatcher dying mid-delivery leaves the
+/// delivery to be retried once the lease expires.
+pub async fn claim_webhook_deliveries(
+    db: &Arc<DbPool>,
+    limit: i64,
+    lease_until: NaiveDateTime,
+) -> Result<Vec<WebhookDeliveryModel>> {
+    let mut conn = db.get().await?;
+    let now = Utc::now().naive_utc();
+    let deliveries = conn
+        .transaction(|conn| {
+            async move {
+                let ids: Vec<i64> = webhook_deliveries::table
+                    .filter(webhook_deliveries::next_attempt_at.le(now))
+                    .order(webhook_deliveries::next_attempt_at.asc())
+                    .limit(limit)
+                    .select(webhook_deliveries::id)
+                    .for_update()
+                    .skip_locked()
+                    .load(conn)
+                    .await?;
+
+                diesel::update(webhook_deliveries::table.filter(webhook_deliveries::id.eq_any(&ids)))
+                    .set(webhook_deliveries::next_attempt_at.eq(lease_until))
+                    .returning(WebhookDeliveryModel::as_returning())
+                    .get_results(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await?;
+    Ok(deliveries)
+}
+
+/// Remove a delivered delivery
+pub async fn complete_webhook_delivery(db: &Arc<DbPool>, id: i64) -> Result<()> {
+    let mut conn = db.get().await?;
+    diesel::delete(webhook_deliveries::table.filter(webhook_deliveries::id.eq(id))).execute(&mut conn).await?;
+    Ok(())
+}
+
+/// Record a failed attempt and schedule the next one
+pub async fn retry_webhook_delivery(
+    db: &Arc<DbPool>,
+    id: i64,
+    error: &str,
+    next_attempt_at: NaiveDateTime,
+) -> Result<()> {
+    let mut conn = db.get().await?;
+    diesel::update(webhook_deliveries::table.filter(webhook_deliveries::id.eq(id)))
+        .set((
+            webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+            webhook_deliveries::last_error.eq(error),
+            webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Move a delivery that failed its last attempt to the dead letters
+pub async fn dead_letter_webhook_delivery(
+    db: &Arc<DbPool>,
+    delivery: &WebhookDeliveryModel,
+    error: &str,
+) -> Result<()> {
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            insert_into(webhook_dead_letters::table)
+                .values((
+                    webhook_dead_letters::subscription_id.eq(delivery.subscription_id),
+                    webhook_dead_letters::notification_key.eq(&delivery.notification_key),
+                    webhook_dead_letters::payload.eq(&delivery.payload),
+                    webhook_dead_letters::attempts.eq(delivery.attempts + 1),
+                    webhook_dead_letters::last_error.eq(error),
+                    webhook_dead_letters::created_at.eq(delivery.created_at),
+                ))
+                .execute(conn)
+                .await?;
+            diesel::delete(webhook_deliveries::table.filter(webhook_deliveries::id.eq(delivery.id)))
+                .execute(conn)
+                .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Latest dead letters of a subscription
+pub async fn list_webhook_dead_letters(
+    db: Arc<DbPool>,
+    subscription_id: i64,
+    limit: i64,
+) -> Result<Vec<WebhookDeadLetterModel>> {
+    let mut conn = db.get().await?;
+    let models = webhook_dead_letters::table
+        .filter(webhook_dead_letters::subscription_id.eq(subscription_id))
+        .order(webhook_dead_letters::failed_at.desc())
+        .limit(limit)
+        .select(WebhookDeadLetterModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(models)
+}
+
+/// Enqueue the dead letters of a subscription again, returning how many were requeued
+pub async fn replay_webhook_dead_letters(db: Arc<DbPool>, subscription_id: i64) -> Result<usize> {
+    let mut conn = db.get().await?;
+    let replayed = conn
+        .transaction(|conn| {
+            async move {
+                diesel::sql_query(
+                    r#"
+                    INSERT INTO webhook_deliveries (subscription_id, notification_key, payload)
+                    SELECT subscription_id, notification_key, payload FROM webhook_dead_letters
+                    WHERE subscription_id = $1
+                    ON CONFLICT (subscription_id, notification_key) DO NOTHING
+                    "#,
+                )
+                .bind::<BigInt, _>(subscription_id)
+                .execute(conn)
+                .await?;
+                diesel::delete(
+                    webhook_dead_letters::table.filter(webhook_dead_letters::subscription_id.eq(subscription_id)),
+                )
+                .execute(conn)
+                .await
+            }
+            .scope_boxed()
+        })
+        .await?;
+    Ok(replayed)
+}
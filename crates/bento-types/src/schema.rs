@@ -1,5 +1,71 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    address_inputs (output_key, block_hash) {
+        output_key -> Text,
+        tx_hash -> Text,
+        block_hash -> Text,
+        address -> Text,
+        atto_alph_amount -> Numeric,
+        tokens -> Jsonb,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    address_outputs (output_key, block_hash) {
+        output_key -> Text,
+        tx_hash -> Text,
+        block_hash -> Text,
+        address -> Text,
+        hint -> Int4,
+        atto_alph_amount -> Numeric,
+        tokens -> Jsonb,
+        lock_time -> Int8,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> Int8,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        requests_per_minute -> Nullable<Int4>,
+        created_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    backfill_ranges (job, from_ts, to_ts) {
+        #[max_length = 100]
+        job -> Varchar,
+        from_ts -> Int8,
+        to_ts -> Int8,
+        claimed_by -> Nullable<Text>,
+        claimed_at -> Nullable<Timestamp>,
+        completed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    block_rewards (block_hash) {
+        block_hash -> Text,
+        tx_hash -> Text,
+        chain_from -> Int8,
+        chain_to -> Int8,
+        height -> Int8,
+        miner_address -> Text,
+        reward -> Numeric,
+        lock_time -> Int8,
+        uncle_count -> Int4,
+        timestamp -> Timestamp,
+    }
+}
+
 diesel::table! {
     blocks (hash) {
         hash -> Text,
@@ -26,6 +92,19 @@ diesel::table! {
         contract_address -> Text,
         event_index -> Int4,
         fields -> Jsonb,
+        position -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    ghost_uncles (block_hash, uncle_hash) {
+        block_hash -> Text,
+        uncle_hash -> Text,
+        chain_from -> Int8,
+        chain_to -> Int8,
+        miner_address -> Text,
+        reward -> Nullable<Numeric>,
+        timestamp -> Timestamp,
     }
 }
 
@@ -62,6 +141,45 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    token_inputs (output_key, token_id, block_hash) {
+        output_key -> Text,
+        token_id -> Text,
+        tx_hash -> Text,
+        block_hash -> Text,
+        address -> Text,
+        amount -> Numeric,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    token_outputs (output_key, token_id, block_hash) {
+        output_key -> Text,
+        token_id -> Text,
+        tx_hash -> Text,
+        block_hash -> Text,
+        address -> Text,
+        amount -> Numeric,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tokens (token_id) {
+        token_id -> Text,
+        first_seen_tx_hash -> Text,
+        first_seen_block_hash -> Text,
+        first_seen_at -> Timestamp,
+        name -> Nullable<Text>,
+        symbol -> Nullable<Text>,
+        decimals -> Nullable<Int4>,
+        metadata_fetched_at -> Nullable<Timestamp>,
+        metadata_attempts -> Int4,
+        metadata_retry_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     transactions (tx_hash) {
         tx_hash -> Text,
@@ -78,11 +196,73 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    webhook_dead_letters (id) {
+        id -> Int8,
+        subscription_id -> Int8,
+        #[max_length = 64]
+        notification_key -> Varchar,
+        payload -> Jsonb,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamp,
+        failed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Int8,
+        subscription_id -> Int8,
+        #[max_length = 64]
+        notification_key -> Varchar,
+        payload -> Jsonb,
+        attempts -> Int4,
+        next_attempt_at -> Timestamp,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    webhook_subscriptions (id) {
+        id -> Int8,
+        #[max_length = 100]
+        name -> Varchar,
+        url -> Text,
+        #[max_length = 100]
+        secret -> Varchar,
+        #[max_length = 32]
+        notification_type -> Nullable<Varchar>,
+        contract_address -> Nullable<Text>,
+        event_index -> Nullable<Int4>,
+        address -> Nullable<Text>,
+        topic -> Nullable<Text>,
+        created_at -> Timestamp,
+        disabled_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::joinable!(webhook_dead_letters -> webhook_subscriptions (subscription_id));
+diesel::joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
+
 diesel::allow_tables_to_appear_in_same_query!(
+    address_inputs,
+    address_outputs,
+    api_keys,
+    backfill_ranges,
+    block_rewards,
     blocks,
     events,
+    ghost_uncles,
     loan_actions,
     loan_details,
     processor_status,
+    token_inputs,
+    token_outputs,
+    tokens,
     transactions,
+    webhook_dead_letters,
+    webhook_deliveries,
+    webhook_subscriptions,
 );
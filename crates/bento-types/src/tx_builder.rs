@@ -0,0 +1,198 @@
+//! Request and response models of the node endpoints building unsigned transactions:
+//! `transactions/build`, `contracts/unsigned-tx/execute-script` and
+//! `transactions/decode-unsigned-tx`. Amounts are strings of atto units, like the rest of the
+//! node API.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetInput, FixedAssetOutput, Token};
+
+/// An output of a transfer.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Destination {
+    pub address: String,
+    pub atto_alph_amount: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<Token>,
+    /// Milliseconds before which the output cannot be spent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_time: Option<i64>,
+    /// Hex encoded message attached to the output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl Destination {
+    pub fn new(address: impl Into<String>, atto_alph_amount: impl Into<String>) -> Self {
+        Self { address: address.into(), atto_alph_amount: atto_alph_amount.into(), ..Default::default() }
+    }
+
+    pub fn with_token(mut self, id: impl Into<String>, amount: impl Into<String>) -> Self {
+        self.tokens.push(Token { id: id.into(), amount: amount.into() });
+        self
+    }
+}
+
+/// Body of `POST /transactions/build`. Inputs are selected by the node unless `utxos` is set.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildTransferTx {
+    /// Hex encoded compressed public key of the sender
+    pub from_public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_public_key_type: Option<String>,
+    pub destinations: Vec<Destination>,
+    /// Outputs to spend, as `{ hint, key }` references
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxos: Option<Vec<crate::OutputRef>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_amount: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_block_hash: Option<String>,
+    /// Group of a groupless sender
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<i32>,
+}
+
+/// Response of `POST /transactions/build`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildTransferTxResult {
+    /// Hex encoded unsigned transaction, to sign and submit
+    pub unsigned_tx: String,
+    pub gas_amount: i32,
+    pub gas_price: String,
+    pub tx_id: String,
+    pub from_group: i32,
+    pub to_group: i32,
+}
+
+/// Body of `POST /contracts/unsigned-tx/execute-script`.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildExecuteScriptTx {
+    /// Hex encoded compressed public key of the caller
+    pub from_public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_public_key_type: Option<String>,
+    /// Hex encoded bytecode of the compiled script
+    pub bytecode: String,
+    /// ALPH approved to the script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atto_alph_amount: Option<String>,
+    /// Tokens approved to the script
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<Token>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_amount: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_block_hash: Option<String>,
+    /// Factor applied to the estimated gas, e.g. 1.5
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_estimation_multiplier: Option<f64>,
+    /// Group of a groupless caller
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<i32>,
+}
+
+/// Response of `POST /contracts/unsigned-tx/execute-script`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildExecuteScriptTxResult {
+    pub from_group: i32,
+    pub to_group: i32,
+    /// Hex encoded unsigned transaction, to sign and submit
+    pub unsigned_tx: String,
+    pub gas_amount: i32,
+    pub gas_price: String,
+    pub tx_id: String,
+    /// Contract inputs and generated outputs of the simulated execution
+    #[serde(default)]
+    pub simulation_result: Option<serde_json::Value>,
+}
+
+/// Body of `POST /transactions/decode-unsigned-tx`.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeUnsignedTx {
+    pub unsigned_tx: String,
+}
+
+/// Response of `POST /transactions/decode-unsigned-tx`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeUnsignedTxResult {
+    pub from_group: i32,
+    pub to_group: i32,
+    pub unsigned_tx: DecodedUnsignedTx,
+}
+
+/// An unsigned transaction decoded by the node. Unlike [`crate::UnsignedTx`] of fetched blocks,
+/// inputs are references to the spent outputs.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedUnsignedTx {
+    pub tx_id: String,
+    pub version: i32,
+    pub network_id: i32,
+    pub script_opt: Option<String>,
+    pub gas_amount: i32,
+    pub gas_price: String,
+    pub inputs: Vec<AssetInput>,
+    pub fixed_outputs: Vec<FixedAssetOutput>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_transfer_tx_serialization() {
+        let request = BuildTransferTx {
+            from_public_key: "02bf67".to_string(),
+            destinations: vec![Destination::new("1EJCtZP3", "1000").with_token("token", "5")],
+            gas_amount: Some(20000),
+            ..Default::default()
+        };
+
+        // Unset fields are left to the node
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "fromPublicKey": "02bf67",
+                "destinations": [
+                    { "address": "1EJCtZP3", "attoAlphAmount": "1000", "tokens": [{ "id": "token", "amount": "5" }] }
+                ],
+                "gasAmount": 20000
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_unsigned_tx_deser() {
+        let result: DecodeUnsignedTxResult = serde_json::from_value(json!({
+            "fromGroup": 0,
+            "toGroup": 1,
+            "unsignedTx": {
+                "txId": "tx",
+                "version": 0,
+                "networkId": 1,
+                "gasAmount": 20000,
+                "gasPrice": "100000000000",
+                "inputs": [{ "outputRef": { "hint": 1, "key": "key" }, "unlockScript": "00" }],
+                "fixedOutputs": []
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(result.to_group, 1);
+        assert_eq!(result.unsigned_tx.inputs[0].output_ref.key, "key");
+        assert!(result.unsigned_tx.script_opt.is_none());
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::{Context, Result};
+
+/// Address type prefix of pay-to-contract addresses.
+const P2C_PREFIX: u8 = 0x03;
+
+/// Encode a contract id (hex) as its base58 contract address.
+pub fn address_from_contract_id(contract_id: &str) -> Result<String> {
+    let id = hex::decode(contract_id).with_context(|| format!("Invalid contract id {contract_id}"))?;
+    let mut bytes = Vec::with_capacity(1 + id.len());
+    bytes.push(P2C_PREFIX);
+    bytes.extend_from_slice(&id);
+    Ok(bs58::encode(bytes).into_string())
+}
+
+/// The group a contract lives in, derived from the last byte of its id.
+pub fn group_of_contract_id(contract_id: &str, group_num: u32) -> Result<u32> {
+    let id = hex::decode(contract_id).with_context(|| format!("Invalid contract id {contract_id}"))?;
+    let last = id.last().with_context(|| format!("Empty contract id {contract_id}"))?;
+    Ok(*last as u32 % group_num)
+}
+
+/// Decode a hex encoded `ByteVec` into a UTF-8 string, replacing invalid sequences.
+pub fn hex_to_utf8_lossy(value: &str) -> Result<String> {
+    let bytes = hex::decode(value).with_context(|| format!("Invalid hex string {value}"))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_from_contract_id() {
+        let id = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+        let address = address_from_contract_id(id).unwrap();
+        let decoded = bs58::decode(address).into_vec().unwrap();
+        assert_eq!(decoded[0], P2C_PREFIX);
+        assert_eq!(hex::encode(&decoded[1..]), id);
+
+        assert!(address_from_contract_id("not hex").is_err());
+    }
+
+    #[test]
+    fn test_group_of_contract_id() {
+        let id = "0000000000000000000000000000000000000000000000000000000000000007";
+        assert_eq!(group_of_contract_id(id, 4).unwrap(), 3);
+        assert!(group_of_contract_id("", 4).is_err());
+    }
+
+    #[test]
+    fn test_hex_to_utf8_lossy() {
+        assert_eq!(hex_to_utf8_lossy("414c5048").unwrap(), "ALPH");
+    }
+}
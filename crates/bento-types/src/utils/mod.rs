@@ -1,3 +1,5 @@
+pub mod contract;
 pub mod time;
 
+pub use contract::*;
 pub use time::*;
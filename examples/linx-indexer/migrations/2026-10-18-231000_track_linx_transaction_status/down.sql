@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS idx_linx_transactions_tracking;
+
+ALTER TABLE linx_transactions
+    DROP COLUMN from_group,
+    DROP COLUMN to_group,
+    DROP COLUMN status,
+    DROP COLUMN block_hash,
+    DROP COLUMN confirmations,
+    DROP COLUMN checked_at;
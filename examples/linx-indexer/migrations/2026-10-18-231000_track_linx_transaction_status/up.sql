@@ -0,0 +1,10 @@
+ALTER TABLE linx_transactions
+    ADD COLUMN from_group SMALLINT,
+    ADD COLUMN to_group SMALLINT,
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'pending',
+    ADD COLUMN block_hash TEXT,
+    ADD COLUMN confirmations INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN checked_at TIMESTAMP;
+
+-- Pending transactions and those not yet final are polled by the tracker
+CREATE INDEX idx_linx_transactions_tracking ON linx_transactions (status, confirmations);
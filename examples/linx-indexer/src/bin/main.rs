@@ -1,18 +1,15 @@
+use bento_cli::RunOptions;
 use linx_indexer::{
-    config::AppConfig,
-    get_processor_factories,
+    MIGRATIONS,
+    config::APP_CONFIG,
+    get_export_tables, get_processor_factories,
     routers::{AccountTransactionsRouter, LendingRouter, PointsRouter, StatsRouter, TransactionsRouter},
 };
-use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
-    // Load application configuration from environment variables
-    let app_config = AppConfig::from_env()?;
-    let app_config = Some(Arc::new(app_config) as Arc<dyn bento_types::config::AppConfigTrait>);
-
     // Get processor factories from shared function
     let processor_factories = get_processor_factories();
 
@@ -23,7 +20,12 @@ async fn main() -> anyhow::Result<()> {
         .merge(StatsRouter::register())
         .merge(TransactionsRouter::register());
 
-    bento_cli::run_command(processor_factories, Some(router), true, app_config).await?;
+    let options = RunOptions::new(processor_factories)
+        .with_router(router)
+        .with_app_config(APP_CONFIG)
+        .with_migrations(&MIGRATIONS)
+        .with_export_tables(get_export_tables());
+    bento_cli::run_command(options).await?;
 
     Ok(())
 }
@@ -1,4 +1,4 @@
-use bento_cli::{get_database_url, get_network, load_config};
+use bento_cli::get_database_url;
 use bento_core::new_db_pool;
 use chrono::NaiveDate;
 use linx_indexer::config::AppConfig;
@@ -12,16 +12,13 @@ async fn main() -> anyhow::Result<()> {
 
     tracing_subscriber::fmt::init();
 
-    // Load application configuration from environment variables
-    let app_config = AppConfig::from_env()?;
-
-    let config_path = "config.toml";
-    let config = load_config(config_path).expect("Failed to load config");
+    // Load the config file, with the environment layered over it
+    let (config, app_config) = AppConfig::load_file("config.toml")?;
 
     let database_url = get_database_url().expect("DATABASE_URL must be set in environment");
     let db_pool = new_db_pool(&database_url, None).await?;
 
-    let network = get_network().expect("NETWORK must be set in environment");
+    let network = config.resolve_network()?;
 
     let price_service = Arc::new(TokenService::new(
         network,
@@ -1,23 +1,26 @@
 use std::sync::Arc;
 
-use bento_cli::{get_database_url, get_network};
+use bento_cli::get_database_url;
 use bento_core::new_db_pool;
 use chrono::DateTime;
 use linx_indexer::config::AppConfig;
-use linx_indexer::jobs::{PeriodicJob, run_job_forever};
+use linx_indexer::jobs::{PeriodicJob, run_job_forever, run_job_once};
 use linx_indexer::repository::LendingRepository;
 use linx_indexer::services::price::token_service::TokenService;
-use linx_indexer::services::{MarketStateSnapshotService, PositionSnapshotService, StatsSnapshotService};
+use linx_indexer::services::{
+    MarketStateSnapshotService, PositionSnapshotService, StatsSnapshotService, TransactionStatusService,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
     tracing_subscriber::fmt::init();
 
-    let app_config = AppConfig::from_env()?;
+    let config_path = std::env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string());
+    let (config, app_config) = AppConfig::load_file(&config_path)?;
     let database_url = get_database_url().expect("DATABASE_URL must be set in environment");
     let db_pool = new_db_pool(&database_url, None).await?;
-    let network = get_network().expect("NETWORK must be set in environment");
+    let network = config.resolve_network()?;
 
     let token_service = Arc::new(TokenService::new(
         network.clone(),
@@ -32,7 +35,7 @@ async fn main() -> anyhow::Result<()> {
         (Some("run"), None) => {
             let mut set = tokio::task::JoinSet::new();
             for job in jobs {
-                set.spawn(run_job_forever(job));
+                set.spawn(run_job_forever(job, db_pool.clone()));
             }
             while let Some(res) = set.join_next().await {
                 res??;
@@ -41,7 +44,7 @@ async fn main() -> anyhow::Result<()> {
         (Some("run-once"), Some(name)) => {
             let job =
                 jobs.into_iter().find(|j| j.name() == name).ok_or_else(|| anyhow::anyhow!("unknown job: {name}"))?;
-            job.tick().await?;
+            run_job_once(job.as_ref(), &db_pool).await?;
         }
         (Some("list"), None) => {
             for j in &jobs {
@@ -103,7 +106,7 @@ fn build_jobs(
     vec![
         Arc::new(PositionSnapshotService::new(
             db_pool.clone(),
-            client,
+            client.clone(),
             token_service.clone(),
             app_config.linx_address.clone(),
             app_config.linx_group,
@@ -115,7 +118,8 @@ fn build_jobs(
             app_config.linx_address.clone(),
             app_config.linx_group,
         )),
-        Arc::new(StatsSnapshotService::new(db_pool)),
+        Arc::new(StatsSnapshotService::new(db_pool.clone())),
+        Arc::new(TransactionStatusService::new(db_pool, client)),
     ]
 }
 
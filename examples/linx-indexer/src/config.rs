@@ -1,8 +1,43 @@
-use anyhow::Result;
-use bento_types::config::AppConfigTrait;
 use std::collections::HashSet;
+use std::sync::Arc;
+
+use bento_cli::config::{AppConfigSource, ConfigError, ConfigErrors};
+use bento_cli::types::Config;
+use bento_types::config::AppConfigTrait;
+use serde::Deserialize;
+
+/// Environment variables read before the settings moved to the config file, kept so existing
+/// deployments keep working.
+const ENV_ALIASES: &[(&str, &str)] = &[
+    ("GAS_PAYER_ADDRESSES", "processors.transfers.gas_payer_addresses"),
+    ("LINX_ADDRESS", "processors.lending.linx_address"),
+    ("LINX_GROUP", "processors.lending.linx_group"),
+    ("DIA_ORACLE_ADDRESS", "processors.lending.dia_oracle_address"),
+    ("LINX_API_URL", "price_service.linx_api_url"),
+];
+
+/// Passed to `bento_cli::run_command` to build the [`AppConfig`] from the layered config.
+pub const APP_CONFIG: AppConfigSource =
+    AppConfigSource { env_aliases: ENV_ALIASES, sections: &["price_service", "points"], load: AppConfig::load };
+
+/// `[processors.transfers]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransfersSettings {
+    #[serde(default)]
+    pub gas_payer_addresses: HashSet<String>,
+}
 
-/// Application-level configuration loaded from environment variables
+/// `[processors.lending]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LendingSettings {
+    pub linx_address: String,
+    pub linx_group: u32,
+    pub dia_oracle_address: String,
+}
+
+/// Application-level configuration built from the config file sections
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub gas_payer_addresses: HashSet<String>,
@@ -19,26 +54,42 @@ impl AppConfigTrait for AppConfig {
 }
 
 impl AppConfig {
-    /// Load configuration from environment variables
+    /// Build the configuration from the `[processors.transfers]`, `[processors.lending]` and
+    /// `[price_service]` sections, reporting every invalid section.
     /// This should be called at application startup to fail-fast if config is missing
-    pub fn from_env() -> Result<Self> {
-        let gas_payer_addresses: HashSet<String> =
-            std::env::var("GAS_PAYER_ADDRESSES").ok().and_then(|v| serde_json::from_str(&v).ok()).unwrap_or_default();
-
-        let linx_address =
-            std::env::var("LINX_ADDRESS").map_err(|_| anyhow::anyhow!("LINX_ADDRESS environment variable not set"))?;
+    pub fn from_config(config: &Config) -> Result<Self, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
 
-        let linx_group: u32 = std::env::var("LINX_GROUP")
-            .map_err(|_| anyhow::anyhow!("LINX_GROUP environment variable not set"))?
-            .parse()
-            .map_err(|_| anyhow::anyhow!("LINX_GROUP must be a valid u32"))?;
+        let transfers = config.processor_section::<TransfersSettings>("transfers").map_err(|e| errors.push(e)).ok();
+        let lending = config.processor_section::<LendingSettings>("lending").map_err(|e| errors.push(e)).ok();
+        let linx_api_url = match &config.price_service {
+            Some(price_service) => Some(price_service.linx_api_url.clone()),
+            None => {
+                errors.push(ConfigError::new("price_service.linx_api_url", "missing field `linx_api_url`"));
+                None
+            }
+        };
 
-        let dia_oracle_address = std::env::var("DIA_ORACLE_ADDRESS")
-            .map_err(|_| anyhow::anyhow!("DIA_ORACLE_ADDRESS environment variable not set"))?;
+        match (transfers, lending, linx_api_url) {
+            (Some(transfers), Some(lending), Some(linx_api_url)) => Ok(Self {
+                gas_payer_addresses: transfers.gas_payer_addresses,
+                linx_address: lending.linx_address,
+                linx_group: lending.linx_group,
+                dia_oracle_address: lending.dia_oracle_address,
+                linx_api_url,
+            }),
+            _ => Err(errors),
+        }
+    }
 
-        let linx_api_url =
-            std::env::var("LINX_API_URL").map_err(|_| anyhow::anyhow!("LINX_API_URL environment variable not set"))?;
+    /// Load the config file at `path` with the environment layered over it.
+    pub fn load_file(path: &str) -> anyhow::Result<(Config, Self)> {
+        let config = bento_cli::config::ConfigLoader::new(path).env_aliases(ENV_ALIASES).load()?;
+        let app_config = Self::from_config(&config)?;
+        Ok((config, app_config))
+    }
 
-        Ok(Self { gas_payer_addresses, linx_address, linx_group, dia_oracle_address, linx_api_url })
+    fn load(config: &Config) -> Result<Arc<dyn AppConfigTrait>, ConfigErrors> {
+        Ok(Arc::new(Self::from_config(config)?))
     }
 }
@@ -1,5 +1,7 @@
 /// Alephium cryptographic utilities for signing and verifying messages
 ///
+/// Addresses are derived with `bento_core::signer`, which also signs transactions.
+///
 /// This module provides functions for:
 /// - Generating Alephium addresses from private keys
 /// - Signing messages with private keys
@@ -16,34 +18,11 @@ use anyhow;
 /// # Returns
 /// * Base58-encoded Alephium address
 pub fn address_from_private_key(private_key_bytes: &[u8], address_type: AddressType) -> anyhow::Result<String> {
-    use blake2::{Blake2b, Digest};
-    use secp256k1::{PublicKey, Secp256k1, SecretKey};
-
-    if private_key_bytes.len() != 32 {
-        return Err(anyhow::anyhow!("Private key must be 32 bytes"));
-    }
-
-    // Create secret key
-    let secret_key =
-        SecretKey::from_slice(private_key_bytes).map_err(|e| anyhow::anyhow!("Invalid private key: {}", e))?;
-
-    // Get public key
-    let secp = Secp256k1::new();
-    let public_key = PublicKey::from_secret_key(&secp, &secret_key);
-    let pubkey_bytes = public_key.serialize();
-
-    // Hash the public key with Blake2b-256
-    let mut hasher = Blake2b::<blake2::digest::consts::U32>::new();
-    hasher.update(pubkey_bytes);
-    let hash = hasher.finalize();
-
-    // Create address: [type_byte][blake2b_hash]
-    // No checksum - Alephium uses just type byte + hash
-    let mut address_bytes = Vec::with_capacity(1 + hash.len());
-    address_bytes.push(address_type as u8);
-    address_bytes.extend_from_slice(&hash);
+    use bento_core::signer::{PrivateKeySigner, Signer, address_from_public_key};
 
-    Ok(bs58::encode(address_bytes).into_string())
+    let signer = PrivateKeySigner::new(private_key_bytes)?;
+    let public_key = hex::decode(signer.public_key())?;
+    Ok(address_from_public_key(&public_key, address_type as u8))
 }
 
 /// Sign a message with a private key (Alephium format)
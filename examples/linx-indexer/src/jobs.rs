@@ -2,6 +2,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use bento_core::{DbPool, notify::Notifier};
+use bento_types::notifications::IndexedNotification;
 
 #[async_trait]
 pub trait PeriodicJob: Send + Sync {
@@ -10,12 +12,29 @@ pub trait PeriodicJob: Send + Sync {
     async fn tick(&self) -> anyhow::Result<()>;
 }
 
-pub async fn run_job_forever(job: Arc<dyn PeriodicJob>) -> anyhow::Result<()> {
+/// Topic of the notification published after each successful tick of the job `name`, used by
+/// the server to drop the responses computed from the job's output.
+pub fn job_topic(name: &str) -> String {
+    format!("jobs.{name}")
+}
+
+/// Run one tick of `job` and notify the servers when it succeeds.
+pub async fn run_job_once(job: &dyn PeriodicJob, db_pool: &Arc<DbPool>) -> anyhow::Result<()> {
+    job.tick().await?;
+
+    let notification = IndexedNotification::Custom { topic: job_topic(job.name()), data: serde_json::Value::Null };
+    if let Err(e) = Notifier::new(true).publish(db_pool, vec![notification]).await {
+        tracing::warn!(job = job.name(), error = %e, "failed to publish job completion");
+    }
+    Ok(())
+}
+
+pub async fn run_job_forever(job: Arc<dyn PeriodicJob>, db_pool: Arc<DbPool>) -> anyhow::Result<()> {
     let mut timer = tokio::time::interval(job.interval());
     tracing::info!(job = job.name(), interval_secs = job.interval().as_secs(), "starting periodic job");
     loop {
         timer.tick().await;
-        if let Err(e) = job.tick().await {
+        if let Err(e) = run_job_once(job.as_ref(), &db_pool).await {
             tracing::error!(job = job.name(), error = %e, "periodic job tick failed");
         }
     }
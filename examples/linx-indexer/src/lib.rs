@@ -1,5 +1,7 @@
 use bento_core::ProcessorFactory;
+use bento_types::export::{ExportTable, ExportTables};
 use bigdecimal::BigDecimal;
+use diesel_migrations::{EmbeddedMigrations, embed_migrations};
 use rand::RngCore;
 use std::collections::HashMap;
 
@@ -15,6 +17,9 @@ pub mod schema;
 pub mod services;
 pub mod share_image;
 
+/// App migrations, applied by the worker after the framework migrations
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
 /// Register all custom processor factories
 pub fn get_processor_factories() -> HashMap<String, ProcessorFactory> {
     let mut processor_factories = HashMap::new();
@@ -28,6 +33,14 @@ pub fn get_processor_factories() -> HashMap<String, ProcessorFactory> {
     processor_factories
 }
 
+/// Tables analysts can export, next to the core ones
+pub fn get_export_tables() -> ExportTables {
+    ExportTables::core()
+        .table(ExportTable::new("account_transactions", "timestamp"))
+        .table(ExportTable::new("lending_events", "block_time"))
+        .table(ExportTable::new("points_snapshots", "snapshot_date"))
+}
+
 pub enum AddressType {
     P2PKH = 0x00,
     P2MPKH = 0x01,
@@ -11,6 +11,14 @@ pub struct LinxTransaction {
     pub user_address: String,
     #[schema(value_type = String)]
     pub created_at: NaiveDateTime,
+    pub from_group: Option<i16>,
+    pub to_group: Option<i16>,
+    /// See [`LinxTransactionStatus`]
+    pub status: String,
+    pub block_hash: Option<String>,
+    pub confirmations: i32,
+    #[schema(value_type = Option<String>)]
+    pub checked_at: Option<NaiveDateTime>,
 }
 
 #[derive(Insertable, Debug, Clone)]
@@ -18,4 +26,41 @@ pub struct LinxTransaction {
 pub struct NewLinxTransaction {
     pub tx_id: String,
     pub user_address: String,
+    pub from_group: Option<i16>,
+    pub to_group: Option<i16>,
+}
+
+/// Status of a submitted transaction, as tracked by the transaction status job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinxTransactionStatus {
+    /// In the mempool, or not seen by the node yet
+    Pending,
+    /// In a block and its script executed
+    Confirmed,
+    /// In a block but its script failed, `script_execution_ok` is false
+    Failed,
+    /// Unknown to the node long after submission
+    Dropped,
+}
+
+impl LinxTransactionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Confirmed => "confirmed",
+            Self::Failed => "failed",
+            Self::Dropped => "dropped",
+        }
+    }
+}
+
+/// Status recorded by one check of a transaction
+#[derive(AsChangeset, Debug, Clone)]
+#[diesel(table_name = crate::schema::linx_transactions)]
+#[diesel(treat_none_as_null = true)]
+pub struct LinxTransactionStatusUpdate {
+    pub status: String,
+    pub block_hash: Option<String>,
+    pub confirmations: i32,
+    pub checked_at: NaiveDateTime,
 }
@@ -8,6 +8,7 @@ use bento_types::{
     BlockAndEvents, CustomProcessorOutput, RichBlockEntry, Transaction, processors::ProcessorOutput,
     utils::timestamp_millis_to_naive_datetime,
 };
+use diesel_async::AsyncPgConnection;
 
 use crate::{
     models::{ContractCallDetails, NewAccountTransaction},
@@ -16,7 +17,6 @@ use crate::{
 };
 pub struct ContractCallProcessor {
     connection_pool: Arc<DbPool>,
-    repository: AccountTransactionRepository,
     classifier: TransactionClassifier,
 }
 
@@ -29,9 +29,8 @@ pub fn processor_factory() -> ProcessorFactory {
 impl ContractCallProcessor {
     pub fn new(connection_pool: Arc<DbPool>, _config: Option<Arc<dyn bento_types::config::AppConfigTrait>>) -> Self {
         tracing::debug!("Initialized ContractCallProcessor");
-        let repository = AccountTransactionRepository::new(connection_pool.clone());
         let classifier = TransactionClassifier::new(HashSet::new());
-        Self { connection_pool, repository, classifier }
+        Self { connection_pool, classifier }
     }
 }
 
@@ -86,12 +85,12 @@ impl ProcessorTrait for ContractCallProcessor {
         Ok(ProcessorOutput::Custom(Arc::new(ContractCallProcessorOutput { contract_calls })))
     }
 
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()> {
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
         if let ProcessorOutput::Custom(custom) = output {
             if let Some(contract_call_output) = custom.as_any().downcast_ref::<ContractCallProcessorOutput>() {
                 let contract_calls = &contract_call_output.contract_calls;
                 if !contract_calls.is_empty() {
-                    self.repository.insert_transactions(contract_calls).await?;
+                    AccountTransactionRepository::insert_transactions(conn, contract_calls).await?;
                     tracing::info!("Inserted {} contract calls", contract_calls.len());
                 }
             } else {
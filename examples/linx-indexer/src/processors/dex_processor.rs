@@ -5,10 +5,11 @@ use async_trait::async_trait;
 use bento_core::{ProcessorFactory, db::DbPool};
 use bento_trait::processor::ProcessorTrait;
 use bento_types::{
-    BlockAndEvents, ContractEventByBlockHash, CustomProcessorOutput, EventField, RichBlockEntry,
-    processors::ProcessorOutput, utils::timestamp_millis_to_naive_datetime,
+    BlockAndEvents, BlockRange, ContractEventByBlockHash, CustomProcessorOutput, EventField, RichBlockEntry,
+    notifications::IndexedNotification, processors::ProcessorOutput, utils::timestamp_millis_to_naive_datetime,
 };
 use bigdecimal::Zero;
+use diesel_async::AsyncPgConnection;
 
 use crate::{
     address_from_contract_id,
@@ -28,16 +29,14 @@ pub fn processor_factory() -> ProcessorFactory {
 
 pub struct DexProcessor {
     connection_pool: Arc<DbPool>,
-    swap_repository: AccountTransactionRepository,
     pool_repository: PoolRepository,
 }
 
 impl DexProcessor {
     pub fn new(connection_pool: Arc<DbPool>, _config: Option<Arc<dyn bento_types::config::AppConfigTrait>>) -> Self {
-        let swap_repository = AccountTransactionRepository::new(connection_pool.clone());
         let pool_repository = PoolRepository::new(connection_pool.clone());
 
-        Self { connection_pool, swap_repository, pool_repository }
+        Self { connection_pool, pool_repository }
     }
 
     fn extract_new_pools(&self, events: &[ContractEventByBlockHash]) -> Vec<NewPoolDto> {
@@ -433,6 +432,22 @@ impl CustomProcessorOutput for DexProcessorOutput {
     fn clone_box(&self) -> Box<dyn CustomProcessorOutput> {
         Box::new(self.clone())
     }
+
+    /// One `dex.swap` notification per aggregated swap, addressed to the trader
+    fn notifications(&self) -> Vec<IndexedNotification> {
+        self.swaps
+            .iter()
+            .map(|swap| IndexedNotification::Custom {
+                topic: format!("dex.{}", swap.tx_type),
+                data: serde_json::json!({
+                    "address": swap.address,
+                    "tx_id": swap.tx_id,
+                    "timestamp": swap.timestamp.and_utc().timestamp_millis(),
+                    "details": swap.details,
+                }),
+            })
+            .collect()
+    }
 }
 
 impl Debug for DexProcessor {
@@ -479,20 +494,27 @@ impl ProcessorTrait for DexProcessor {
         Ok(ProcessorOutput::Custom(Arc::new(DexProcessorOutput { new_pools, swaps: processed_swaps })))
     }
 
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()> {
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
         if let ProcessorOutput::Custom(custom) = output
             && let Some(dex_output) = custom.as_any().downcast_ref::<DexProcessorOutput>()
         {
             if !dex_output.new_pools.is_empty() {
-                self.pool_repository.insert_pools(&dex_output.new_pools).await?;
+                PoolRepository::insert_pools(conn, &dex_output.new_pools).await?;
                 tracing::info!("Inserted {} new pools", dex_output.new_pools.len());
             }
 
             if !dex_output.swaps.is_empty() {
-                self.swap_repository.insert_transactions(&dex_output.swaps).await?;
+                AccountTransactionRepository::insert_transactions(conn, &dex_output.swaps).await?;
                 tracing::info!("Inserted {} swaps", dex_output.swaps.len());
             }
         }
         Ok(())
     }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        // Pools are only ever created, reprocessing the range registers them again
+        let deleted = AccountTransactionRepository::delete_transactions_in_range(conn, "swap", range).await?;
+        tracing::info!("Purged {} swaps", deleted);
+        Ok(())
+    }
 }
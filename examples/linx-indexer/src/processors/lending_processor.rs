@@ -5,10 +5,11 @@ use async_trait::async_trait;
 use bento_core::{DbPool, ProcessorFactory};
 use bento_trait::processor::ProcessorTrait;
 use bento_types::{
-    BlockAndEvents, ContractEventByBlockHash, CustomProcessorOutput, EventField, RichBlockEntry,
-    config::AppConfigTrait, processors::ProcessorOutput,
+    BlockAndEvents, BlockRange, ContractEventByBlockHash, CustomProcessorOutput, EventField, RichBlockEntry,
+    config::AppConfigTrait, notifications::IndexedNotification, processors::ProcessorOutput,
 };
 use bigdecimal::BigDecimal;
+use diesel_async::AsyncPgConnection;
 
 use crate::{
     config::AppConfig,
@@ -37,6 +38,28 @@ impl CustomProcessorOutput for LendingProcessorOutput {
     fn clone_box(&self) -> Box<dyn CustomProcessorOutput> {
         Box::new(self.clone())
     }
+
+    /// One `lending.<EventType>` notification per event, e.g. `lending.Liquidate`, addressed to
+    /// the account acted on behalf of
+    fn notifications(&self) -> Vec<IndexedNotification> {
+        self.events
+            .iter()
+            .map(|event| IndexedNotification::Custom {
+                topic: format!("lending.{}", event.event_type),
+                data: serde_json::json!({
+                    "address": event.on_behalf,
+                    "market_id": event.market_id,
+                    "event_type": event.event_type,
+                    "token_id": event.token_id,
+                    "amount": event.amount.to_string(),
+                    "shares": event.shares.to_string(),
+                    "transaction_id": event.transaction_id,
+                    "event_index": event.event_index,
+                    "block_time": event.block_time.and_utc().timestamp_millis(),
+                }),
+            })
+            .collect()
+    }
 }
 
 impl Debug for LendingProcessor {
@@ -86,21 +109,27 @@ impl ProcessorTrait for LendingProcessor {
         Ok(ProcessorOutput::Custom(Arc::new(LendingProcessorOutput { markets: new_markets, events })))
     }
 
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()> {
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
         if let ProcessorOutput::Custom(custom) = output
             && let Some(lending_output) = custom.as_any().downcast_ref::<LendingProcessorOutput>()
         {
             if !lending_output.markets.is_empty() {
-                self.lending_repository.insert_markets(&lending_output.markets).await?;
+                LendingRepository::insert_markets(conn, &lending_output.markets).await?;
                 tracing::info!("Inserted {} new markets", lending_output.markets.len());
             }
             if !lending_output.events.is_empty() {
-                self.lending_repository.insert_lending_events(&lending_output.events).await?;
+                LendingRepository::insert_lending_events(conn, &lending_output.events).await?;
                 tracing::info!("Inserted {} new events", lending_output.events.len());
             }
         }
         Ok(())
     }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = LendingRepository::delete_lending_events_in_range(conn, range).await?;
+        tracing::info!("Purged {} lending events", deleted);
+        Ok(())
+    }
 }
 
 pub struct LendingProcessor {
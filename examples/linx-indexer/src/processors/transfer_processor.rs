@@ -8,8 +8,9 @@ use async_trait::async_trait;
 use bento_core::ProcessorFactory;
 use bento_core::db::DbPool;
 use bento_trait::processor::ProcessorTrait;
-use bento_types::{BlockAndEvents, processors::ProcessorOutput, utils::timestamp_millis_to_naive_datetime};
+use bento_types::{BlockAndEvents, BlockRange, processors::ProcessorOutput, utils::timestamp_millis_to_naive_datetime};
 use bigdecimal::BigDecimal;
+use diesel_async::AsyncPgConnection;
 
 use crate::config::AppConfig;
 use crate::constants::{ALPH_TOKEN_ID, DUST_AMOUNT};
@@ -24,7 +25,6 @@ pub fn processor_factory() -> ProcessorFactory {
 pub struct TransferProcessor {
     connection_pool: Arc<DbPool>,
     gas_payer_addresses: HashSet<String>,
-    repository: AccountTransactionRepository,
     classifier: TransactionClassifier,
 }
 
@@ -36,9 +36,8 @@ impl TransferProcessor {
             .and_then(|c| c.as_any().downcast_ref::<AppConfig>())
             .map(|c| c.gas_payer_addresses.clone())
             .unwrap_or_default();
-        let repository = AccountTransactionRepository::new(connection_pool.clone());
         let classifier = TransactionClassifier::new(HashSet::new());
-        Self { connection_pool, gas_payer_addresses, repository, classifier }
+        Self { connection_pool, gas_payer_addresses, classifier }
     }
 }
 
@@ -93,12 +92,12 @@ impl ProcessorTrait for TransferProcessor {
         Ok(ProcessorOutput::Custom(Arc::new(TransferProcessorOutput { transfers: all_transfers })))
     }
 
-    async fn store_output(&self, output: ProcessorOutput) -> Result<()> {
+    async fn store_output(&self, conn: &mut AsyncPgConnection, output: ProcessorOutput) -> Result<()> {
         if let ProcessorOutput::Custom(custom) = output {
             if let Some(transfer_output) = custom.as_any().downcast_ref::<TransferProcessorOutput>() {
                 let transfers = &transfer_output.transfers;
                 if !transfers.is_empty() {
-                    self.repository.insert_transactions(transfers).await?;
+                    AccountTransactionRepository::insert_transactions(conn, transfers).await?;
                     tracing::info!("Inserted {} token transfers", transfers.len());
                 }
             } else {
@@ -110,6 +109,12 @@ impl ProcessorTrait for TransferProcessor {
 
         Ok(())
     }
+
+    async fn purge_range(&self, conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<()> {
+        let deleted = AccountTransactionRepository::delete_transactions_in_range(conn, "transfer", range).await?;
+        tracing::info!("Purged {} token transfers", deleted);
+        Ok(())
+    }
 }
 
 fn extract_token_transfers(
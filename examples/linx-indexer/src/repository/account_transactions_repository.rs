@@ -1,10 +1,10 @@
 use crate::models::{AccountTransaction, NewAccountTransaction, SwapDetails, SwapTransaction};
 use anyhow::Result;
 use async_trait::async_trait;
-use bento_types::DbPool;
+use bento_types::{BlockRange, DbPool};
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
 #[cfg(test)]
 use mockall::automock;
 use std::sync::Arc;
@@ -28,23 +28,45 @@ impl AccountTransactionRepository {
         Self { db_pool }
     }
 
-    /// Generic insert method for any transaction type
-    /// Uses ON CONFLICT DO NOTHING for idempotency
-    pub async fn insert_transactions(&self, transactions: &[NewAccountTransaction]) -> Result<()> {
-        if transactions.is_empty() {
-            return Ok(());
-        }
+    /// Delete the transactions of `tx_type` with a timestamp in `range`, on a caller provided
+    /// connection so it can run inside a reindex transaction.
+    pub async fn delete_transactions_in_range(
+        conn: &mut AsyncPgConnection,
+        tx_type: &str,
+        range: &BlockRange,
+    ) -> Result<usize> {
+        use crate::schema::account_transactions;
 
-        let mut conn = self.db_pool.get().await?;
+        let (from, to) = range.as_datetimes();
+        let deleted = diesel::delete(
+            account_transactions::table
+                .filter(account_transactions::tx_type.eq(tx_type))
+                .filter(account_transactions::timestamp.between(from, to)),
+        )
+        .execute(conn)
+        .await?;
+        Ok(deleted)
+    }
 
+    /// Generic insert method for any transaction type
+    /// Uses ON CONFLICT DO NOTHING for idempotency. Each row is inserted in its own savepoint,
+    /// so a rejected row doesn't abort the caller's transaction.
+    pub async fn insert_transactions(
+        conn: &mut AsyncPgConnection,
+        transactions: &[NewAccountTransaction],
+    ) -> Result<()> {
         use crate::schema::account_transactions;
 
         for tx in transactions {
-            let result = diesel::insert_into(account_transactions::table)
-                .values(tx)
-                .on_conflict(account_transactions::tx_key)
-                .do_nothing()
-                .execute(&mut conn)
+            let result = conn
+                .transaction(|conn| {
+                    diesel::insert_into(account_transactions::table)
+                        .values(tx)
+                        .on_conflict(account_transactions::tx_key)
+                        .do_nothing()
+                        .execute(conn)
+                        .scope_boxed()
+                })
                 .await;
 
             if let Err(e) = result {
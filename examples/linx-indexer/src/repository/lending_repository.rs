@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use bento_types::DbPool;
+use bento_types::{BlockRange, DbPool};
 use bigdecimal::BigDecimal;
 use chrono::NaiveDateTime;
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
@@ -17,7 +17,7 @@ use crate::{
     },
     schema::{self},
 };
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 
 #[cfg_attr(test, automock)]
 #[async_trait]
@@ -63,17 +63,15 @@ impl LendingRepository {
         Ok(markets)
     }
 
-    pub async fn insert_markets(&self, markets: &[Market]) -> Result<()> {
+    pub async fn insert_markets(conn: &mut AsyncPgConnection, markets: &[Market]) -> Result<()> {
         if markets.is_empty() {
             return Ok(());
         }
 
-        let mut conn = self.db_pool.get().await?;
-
         diesel::insert_into(schema::lending_markets::table)
             .values(markets)
             .on_conflict_do_nothing()
-            .execute(&mut conn)
+            .execute(conn)
             .await?;
 
         Ok(())
@@ -118,17 +116,26 @@ impl LendingRepository {
         Ok(events)
     }
 
-    pub async fn insert_lending_events(&self, events: &[NewLendingEvent]) -> Result<()> {
+    /// Delete the lending events with a block time in `range`, on a caller provided connection
+    /// so it can run inside a reindex transaction. Markets are kept.
+    pub async fn delete_lending_events_in_range(conn: &mut AsyncPgConnection, range: &BlockRange) -> Result<usize> {
+        let (from, to) = range.as_datetimes();
+        let deleted =
+            diesel::delete(schema::lending_events::table.filter(schema::lending_events::block_time.between(from, to)))
+                .execute(conn)
+                .await?;
+        Ok(deleted)
+    }
+
+    pub async fn insert_lending_events(conn: &mut AsyncPgConnection, events: &[NewLendingEvent]) -> Result<()> {
         if events.is_empty() {
             return Ok(());
         }
 
-        let mut conn = self.db_pool.get().await?;
-
         diesel::insert_into(schema::lending_events::table)
             .values(events)
             .on_conflict_do_nothing()
-            .execute(&mut conn)
+            .execute(conn)
             .await?;
 
         Ok(())
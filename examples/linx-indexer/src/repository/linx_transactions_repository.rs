@@ -1,4 +1,4 @@
-use crate::models::{LinxTransaction, NewLinxTransaction};
+use crate::models::{LinxTransaction, LinxTransactionStatus, LinxTransactionStatusUpdate, NewLinxTransaction};
 use crate::schema::linx_transactions;
 use anyhow::Result;
 use bento_types::DbPool;
@@ -37,4 +37,50 @@ impl LinxTransactionsRepository {
 
         Ok(count > 0)
     }
+
+    /// Get a tracked transaction by its ID
+    pub async fn get_linx_transaction(&self, tx_id: &str) -> Result<Option<LinxTransaction>> {
+        let mut conn = self.db_pool.get().await?;
+
+        let transaction = linx_transactions::table
+            .filter(linx_transactions::tx_id.eq(tx_id))
+            .select(LinxTransaction::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(transaction)
+    }
+
+    /// Transactions whose status can still change: pending ones, and those in a block with
+    /// fewer than `final_confirmations`. The least recently checked come first.
+    pub async fn get_tracked_transactions(&self, final_confirmations: i32, limit: i64) -> Result<Vec<LinxTransaction>> {
+        let mut conn = self.db_pool.get().await?;
+
+        let pending = linx_transactions::status.eq(LinxTransactionStatus::Pending.as_str());
+        let not_final = linx_transactions::status
+            .eq_any([LinxTransactionStatus::Confirmed.as_str(), LinxTransactionStatus::Failed.as_str()])
+            .and(linx_transactions::confirmations.lt(final_confirmations));
+        let transactions = linx_transactions::table
+            .filter(pending.or(not_final))
+            .order(linx_transactions::checked_at.asc().nulls_first())
+            .limit(limit)
+            .select(LinxTransaction::as_select())
+            .load(&mut conn)
+            .await?;
+
+        Ok(transactions)
+    }
+
+    /// Record the result of a status check
+    pub async fn update_transaction_status(&self, id: i64, update: &LinxTransactionStatusUpdate) -> Result<()> {
+        let mut conn = self.db_pool.get().await?;
+
+        diesel::update(linx_transactions::table.filter(linx_transactions::id.eq(id)))
+            .set(update)
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
 }
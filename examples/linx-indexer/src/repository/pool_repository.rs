@@ -2,7 +2,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Result;
 use bento_core::DbPool;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 
 use crate::{
     models::{NewPoolDto, Pool},
@@ -18,17 +18,16 @@ impl PoolRepository {
         Self { db_pool }
     }
 
-    pub async fn insert_pools(&self, pools: &[NewPoolDto]) -> Result<()> {
+    pub async fn insert_pools(conn: &mut AsyncPgConnection, pools: &[NewPoolDto]) -> Result<()> {
         if pools.is_empty() {
             return Ok(());
         }
 
-        let mut conn = self.db_pool.get().await?;
         diesel::insert_into(schema::pools::table)
             .values(pools)
             .on_conflict(schema::pools::address)
             .do_nothing()
-            .execute(&mut conn)
+            .execute(conn)
             .await?;
 
         Ok(())
@@ -4,11 +4,12 @@ use axum::{
     response::IntoResponse,
     routing::get,
 };
-use bento_server::{AppState, error::AppError};
+use bento_server::{AppState, cache::CachePolicy, error::AppError};
 use bigdecimal::{BigDecimal, Zero};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
 use utoipa::{IntoParams, ToSchema};
 use utoipa_axum::router::OpenApiRouter;
 
@@ -16,6 +17,7 @@ use axum_extra::extract::WithRejection;
 
 use crate::{
     constants::WAD,
+    jobs::job_topic,
     models::{
         LendingEvent, Market, MarketStatePoint, MarketStateSnapshot, Position, SeriesBucket, Timeframe,
         UserPositionHistoryPoint,
@@ -34,8 +36,17 @@ impl LendingRouter {
             .route("/lending/v1/earn-activity", get(get_earn_activity))
             .route("/lending/v1/positions", get(get_positions))
             .route("/lending/v1/history/user-positions", get(get_user_position_history))
-            .route("/lending/v1/stats", get(get_lending_stats))
-            .route("/lending/v1/stats/series", get(get_lending_stats_series))
+            .merge(
+                // Recomputed from the snapshots, which only change when the snapshot jobs run
+                CachePolicy::new(Duration::from_secs(300))
+                    .invalidated_by(job_topic("market-state-snapshots"))
+                    .invalidated_by(job_topic("lending-stats-snapshots"))
+                    .apply(
+                        OpenApiRouter::new()
+                            .route("/lending/v1/stats", get(get_lending_stats))
+                            .route("/lending/v1/stats/series", get(get_lending_stats_series)),
+                    ),
+            )
     }
 }
 
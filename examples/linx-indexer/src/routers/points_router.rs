@@ -5,9 +5,15 @@ use axum::{
     response::IntoResponse,
     routing::{get, post},
 };
-use bento_server::{AppState, error::AppError};
+use bento_server::{
+    AppState,
+    auth::{RateLimit, RoutePolicy},
+    cache::CachePolicy,
+    error::AppError,
+};
 // use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use utoipa::{IntoParams, ToSchema};
 use utoipa_axum::router::OpenApiRouter;
 
@@ -18,13 +24,23 @@ pub struct PointsRouter;
 
 impl PointsRouter {
     pub fn register() -> OpenApiRouter<AppState> {
+        // Writes and image rendering are the expensive routes, limit each caller
+        let limited = RoutePolicy::default().rate_limit(RateLimit::per_minute(20)).apply(
+            OpenApiRouter::new()
+                .route("/points/v1/apply-referral", post(apply_referral_handler))
+                .route("/points/v1/share/{referral_code}", get(get_share_image_handler)),
+        );
+
+        // Points are recalculated once a day by the points calculator
+        let cached = CachePolicy::new(Duration::from_secs(300))
+            .apply(OpenApiRouter::new().route("/points/v1/leaderboard", get(get_leaderboard_handler)));
+
         OpenApiRouter::new()
-            .route("/points/v1/leaderboard", get(get_leaderboard_handler))
             .route("/points/v1/season", get(get_current_season_handler))
-            .route("/points/v1/apply-referral", post(apply_referral_handler))
             .route("/points/v1/referrals/{address}", get(get_referral_details_handler))
-            .route("/points/v1/share/{referral_code}", get(get_share_image_handler))
             .route("/points/v1/{address}", get(get_user_points_handler))
+            .merge(limited)
+            .merge(cached)
     }
 }
 
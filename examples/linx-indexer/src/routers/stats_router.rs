@@ -1,6 +1,7 @@
 use axum::{Json, extract::State, response::IntoResponse, routing::get};
-use bento_server::{AppState, error::AppError};
+use bento_server::{AppState, cache::CachePolicy, error::AppError};
 use serde::Serialize;
+use std::time::Duration;
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 
@@ -10,7 +11,8 @@ pub struct StatsRouter;
 
 impl StatsRouter {
     pub fn register() -> OpenApiRouter<AppState> {
-        OpenApiRouter::new().route("/stats/v1", get(get_stats_handler))
+        // Counting the users scans the points tables, a short TTL is fresh enough
+        CachePolicy::new(Duration::from_secs(60)).apply(OpenApiRouter::new().route("/stats/v1", get(get_stats_handler)))
     }
 }
 
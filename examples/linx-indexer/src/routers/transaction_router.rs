@@ -1,17 +1,34 @@
-use axum::{Json, extract::State, response::IntoResponse, routing::post};
-use bento_server::{AppState, error::AppError};
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, post},
+};
+use bento_server::{
+    AppState,
+    auth::{RateLimit, RoutePolicy},
+    error::AppError,
+};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 
 use crate::models::NewLinxTransaction;
 use crate::repository::LinxTransactionsRepository;
+use crate::services::FINAL_CONFIRMATIONS;
 
 pub struct TransactionsRouter;
 
 impl TransactionsRouter {
     pub fn register() -> OpenApiRouter<AppState> {
-        OpenApiRouter::new().route("/transactions/v1/submit", post(submit_swap_handler))
+        let limited = RoutePolicy::default()
+            .rate_limit(RateLimit::per_minute(10))
+            .apply(OpenApiRouter::new().route("/transactions/v1/submit", post(submit_swap_handler)));
+
+        // Polled by the frontend while a transaction confirms, so not limited like submissions
+        OpenApiRouter::new()
+            .route("/transactions/v1/{tx_id}/status", get(get_transaction_status_handler))
+            .merge(limited)
     }
 }
 
@@ -74,8 +91,12 @@ pub async fn submit_swap_handler(
     // 3. Track in database (only on successful blockchain submission)
     let linx_tx_repo = LinxTransactionsRepository::new(state.db.clone());
 
-    let new_transaction =
-        NewLinxTransaction { tx_id: submit_result.tx_id.clone(), user_address: request.user_address.clone() };
+    let new_transaction = NewLinxTransaction {
+        tx_id: submit_result.tx_id.clone(),
+        user_address: request.user_address.clone(),
+        from_group: i16::try_from(submit_result.from_group).ok(),
+        to_group: i16::try_from(submit_result.to_group).ok(),
+    };
 
     linx_tx_repo
         .insert_linx_transaction(new_transaction)
@@ -99,6 +120,60 @@ pub async fn submit_swap_handler(
     }))
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransactionStatusResponse {
+    pub tx_id: String,
+
+    /// pending, confirmed, failed (in a block but the script failed) or dropped
+    #[schema(example = "confirmed")]
+    pub status: String,
+
+    /// Block containing the transaction, once confirmed or failed
+    pub block_hash: Option<String>,
+
+    /// Confirmations of the block on its chain
+    pub confirmations: i32,
+
+    /// Confirmations after which the status no longer changes
+    pub final_confirmations: i32,
+
+    /// When the node was last asked, in milliseconds, null until the first check
+    pub checked_at: Option<i64>,
+}
+
+/// Get the status of a submitted transaction
+///
+/// Statuses are refreshed every few seconds from the node until the transaction has
+/// `final_confirmations` confirmations or is dropped.
+#[utoipa::path(
+    get,
+    path = "/transactions/v1/{tx_id}/status",
+    tag = "Transactions",
+    params(("tx_id" = String, Path, description = "Transaction ID returned on submission")),
+    responses(
+        (status = 200, description = "Status of the transaction", body = TransactionStatusResponse),
+        (status = 404, description = "Transaction not submitted through this API")
+    )
+)]
+pub async fn get_transaction_status_handler(
+    State(state): State<AppState>,
+    Path(tx_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let transaction = LinxTransactionsRepository::new(state.db.clone())
+        .get_linx_transaction(&tx_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", tx_id)))?;
+
+    Ok(Json(TransactionStatusResponse {
+        tx_id: transaction.tx_id,
+        status: transaction.status,
+        block_hash: transaction.block_hash,
+        confirmations: transaction.confirmations,
+        final_confirmations: FINAL_CONFIRMATIONS,
+        checked_at: transaction.checked_at.map(|at| at.and_utc().timestamp_millis()),
+    }))
+}
+
 fn validate_request(request: &SubmitSwapRequest) -> Result<(), AppError> {
     // Validate unsigned_tx
     if request.unsigned_tx.is_empty() {
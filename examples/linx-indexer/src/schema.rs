@@ -108,6 +108,12 @@ diesel::table! {
         tx_id -> Text,
         user_address -> Text,
         created_at -> Timestamp,
+        from_group -> Nullable<Int2>,
+        to_group -> Nullable<Int2>,
+        status -> Text,
+        block_hash -> Nullable<Text>,
+        confirmations -> Int4,
+        checked_at -> Nullable<Timestamp>,
     }
 }
 
@@ -1,14 +1,14 @@
-pub mod gap_detection_service;
 pub mod market_state_snapshot_service;
 pub mod points_calculator_service;
 pub mod position_snapshot_service;
 pub mod stats_snapshot_service;
+pub mod transaction_status_service;
 
-pub use gap_detection_service::*;
 pub use market_state_snapshot_service::*;
 pub use points_calculator_service::*;
 pub use position_snapshot_service::*;
 pub use stats_snapshot_service::*;
+pub use transaction_status_service::*;
 
 pub mod price {
     pub mod linx_price_service;
@@ -8,7 +8,7 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use bento_core::{Client, DbPool};
 use bento_trait::stage::ContractsProvider;
-use bento_types::{CallContractParams, CallContractResultType, network::Network};
+use bento_types::{CallContractParams, CallContractResult, network::Network};
 use bigdecimal::{BigDecimal, Zero};
 use chrono::{DateTime, NaiveDateTime};
 use std::str::FromStr;
@@ -40,9 +40,16 @@ impl MarketStateSnapshotService {
         let markets = self.lending_repository.get_all_markets().await?;
         tracing::info!("Generating market state snapshots for {} markets", markets.len());
 
+        let market_ids: Vec<&str> = markets.iter().map(|m| m.id.as_str()).collect();
+        let states = fetch_market_states(&self.client, &self.linx_address, self.linx_group, &market_ids).await;
+
         let mut snapshots = Vec::new();
-        for market in markets {
-            match self.build_snapshot(&market, snapshot_time).await {
+        for (market, state) in markets.iter().zip(states) {
+            let snapshot = match state {
+                Ok(state) => self.build_snapshot(market, &state, snapshot_time).await,
+                Err(e) => Err(e),
+            };
+            match snapshot {
                 Ok(snap) => snapshots.push(snap),
                 Err(e) => tracing::error!("Failed to build snapshot for market {}: {}", market.id, e),
             }
@@ -56,9 +63,12 @@ impl MarketStateSnapshotService {
         Ok(())
     }
 
-    async fn build_snapshot(&self, market: &Market, snapshot_time: NaiveDateTime) -> Result<NewMarketStateSnapshot> {
-        let state = fetch_market_state(&self.client, &self.linx_address, self.linx_group, &market.id).await?;
-
+    async fn build_snapshot(
+        &self,
+        market: &Market,
+        state: &RawMarketState,
+        snapshot_time: NaiveDateTime,
+    ) -> Result<NewMarketStateSnapshot> {
         let market_address = crate::address_from_contract_id(&market.market_contract_id);
         let contract_state =
             self.client.get_contract_state(&market_address).await.context("get_contract_state failed")?;
@@ -84,7 +94,7 @@ impl MarketStateSnapshotService {
         let total_borrow_usd = loan_info.convert_to_decimal(&state.total_borrow_assets) * &loan_price;
         let total_collateral_usd = coll_info.convert_to_decimal(&raw_collateral) * &coll_price;
 
-        let borrow_rate_per_second = self.get_borrow_rate(market, &state).await?;
+        let borrow_rate_per_second = self.get_borrow_rate(market, state).await?;
         let borrow_apy = rate_per_second_to_apy(&borrow_rate_per_second);
 
         let epoch = DateTime::from_timestamp(0, 0).unwrap().naive_utc();
@@ -101,10 +111,10 @@ impl MarketStateSnapshotService {
 
         Ok(NewMarketStateSnapshot {
             market_id: market.id.clone(),
-            total_supply_assets: state.total_supply_assets,
-            total_supply_shares: state.total_supply_shares,
-            total_borrow_assets: state.total_borrow_assets,
-            total_borrow_shares: state.total_borrow_shares,
+            total_supply_assets: state.total_supply_assets.clone(),
+            total_supply_shares: state.total_supply_shares.clone(),
+            total_borrow_assets: state.total_borrow_assets.clone(),
+            total_borrow_shares: state.total_borrow_shares.clone(),
             snapshot_timestamp: snapshot_time,
             total_collateral_assets: raw_collateral,
             total_supply_usd: total_supply_usd.with_scale(2),
@@ -148,28 +158,7 @@ impl MarketStateSnapshotService {
         };
 
         let result = self.client.call_contract(params).await.context("borrowRateView call failed")?;
-
-        match result.result_type {
-            CallContractResultType::CallContractFailed => anyhow::bail!(
-                "borrowRateView failed for market {} (irm {}): {}",
-                market.id,
-                market.irm,
-                result.error.as_deref().unwrap_or("unknown error")
-            ),
-            CallContractResultType::CallContractSucceeded => {}
-        }
-
-        let returns = result.returns.ok_or_else(|| anyhow::anyhow!("no returns from borrowRateView"))?;
-        if returns.is_empty() {
-            anyhow::bail!("borrowRateView returned no values for market {}", market.id);
-        }
-
-        let raw = returns[0]
-            .as_object()
-            .and_then(|o| o.get("value"))
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("invalid borrowRateView return: {}", returns[0]))?;
-        BigDecimal::from_str(raw).map_err(|e| anyhow::anyhow!("borrow rate not a decimal: {}", e))
+        result.value(0).with_context(|| format!("borrowRateView failed for market {} (irm {})", market.id, market.irm))
     }
 }
 
@@ -201,57 +190,82 @@ pub struct RawMarketState {
     pub fee: BigDecimal,
 }
 
-pub async fn fetch_market_state(
+/// Fetch the state of every market in `market_ids` through `multicall-contract`, in order.
+/// A market whose call failed has an error, the others are still returned. If the multicall
+/// itself fails, the markets are called one by one so a single bad market does not fail them all.
+pub async fn fetch_market_states(
     client: &Client,
     linx_address: &str,
     linx_group: u32,
-    market_id: &str,
-) -> Result<RawMarketState> {
-    let params = CallContractParams {
+    market_ids: &[&str],
+) -> Vec<Result<RawMarketState>> {
+    let call = |market_id: &str| CallContractParams {
         tx_id: Some(random_tx_id()),
         group: linx_group,
         address: linx_address.to_string(),
         method_index: 4,
-        args: Some(vec![serde_json::json!({ "type": "ByteVec", "value": market_id })]),
+        args: Some(vec![bytevec_arg(market_id)]),
         world_state_block_hash: None,
         interested_contracts: None,
         input_assets: None,
     };
 
-    let result = client.call_contract(params).await.context("getMarketState call failed")?;
-
-    match result.result_type {
-        CallContractResultType::CallContractFailed => {
-            anyhow::bail!("getMarketState failed for market {}", market_id);
+    let results = match client.multicall_contract(market_ids.iter().map(|id| call(id)).collect()).await {
+        Ok(results) if results.len() == market_ids.len() => results.into_iter().map(Ok).collect(),
+        Ok(results) => {
+            tracing::warn!(
+                "getMarketState multicall returned {} results for {} markets, calling markets one by one",
+                results.len(),
+                market_ids.len()
+            );
+            call_one_by_one(client, market_ids, call).await
+        }
+        Err(e) => {
+            tracing::warn!("getMarketState multicall failed, calling markets one by one: {:#}", e);
+            call_one_by_one(client, market_ids, call).await
         }
-        CallContractResultType::CallContractSucceeded => {}
+    };
+
+    results
+        .into_iter()
+        .zip(market_ids)
+        .map(|(result, market_id)| {
+            result
+                .and_then(|result| parse_market_state(&result))
+                .with_context(|| format!("getMarketState failed for market {}", market_id))
+        })
+        .collect()
+}
+
+async fn call_one_by_one(
+    client: &Client,
+    market_ids: &[&str],
+    call: impl Fn(&str) -> CallContractParams,
+) -> Vec<Result<CallContractResult>> {
+    let mut results = Vec::with_capacity(market_ids.len());
+    for market_id in market_ids {
+        results.push(client.call_contract(call(market_id)).await);
     }
+    results
+}
 
-    let returns = result.returns.ok_or_else(|| anyhow::anyhow!("getMarketState returned no values"))?;
+fn parse_market_state(result: &CallContractResult) -> Result<RawMarketState> {
+    let returns = result.values()?;
     if returns.len() != 6 {
         anyhow::bail!("expected 6 returns from getMarketState, got {}", returns.len());
     }
 
     Ok(RawMarketState {
-        total_supply_assets: extract_bigdecimal(&returns[0])?,
-        total_supply_shares: extract_bigdecimal(&returns[1])?,
-        total_borrow_assets: extract_bigdecimal(&returns[2])?,
-        total_borrow_shares: extract_bigdecimal(&returns[3])?,
-        last_update: extract_bigdecimal(&returns[4])?,
-        fee: extract_bigdecimal(&returns[5])?,
+        total_supply_assets: returns[0].get()?,
+        total_supply_shares: returns[1].get()?,
+        total_borrow_assets: returns[2].get()?,
+        total_borrow_shares: returns[3].get()?,
+        last_update: returns[4].get()?,
+        // A fee that does not decode is read as zero rather than failing the market
+        fee: returns[5].get().unwrap_or_default(),
     })
 }
 
-fn extract_bigdecimal(value: &serde_json::Value) -> Result<BigDecimal> {
-    value
-        .as_object()
-        .and_then(|obj| obj.get("value"))
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Invalid contract return structure: {}", value))?
-        .parse::<BigDecimal>()
-        .map_err(|e| anyhow::anyhow!("Failed to parse BigDecimal: {}", e))
-}
-
 fn bytevec_arg(value: &str) -> serde_json::Value {
     serde_json::json!({ "type": "ByteVec", "value": value })
 }
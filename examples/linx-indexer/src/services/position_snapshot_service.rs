@@ -1,14 +1,12 @@
 use crate::constants::{VIRTUAL_ASSETS, VIRTUAL_SHARES};
 use crate::jobs::PeriodicJob;
 use crate::models::NewPositionSnapshot;
+use crate::repository::LendingRepository;
+use crate::services::fetch_market_states;
 use crate::services::price::token_service::TokenService;
-use crate::{models::MarketState, random_tx_id, repository::LendingRepository};
-use anyhow::Context;
 use async_trait::async_trait;
 use bento_core::{Client, DbPool};
-use bento_trait::stage::ContractsProvider;
-use bento_types::{CallContractParams, CallContractResultType, utils::timestamp_millis_to_naive_datetime};
-use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use bigdecimal::{BigDecimal, Zero};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -33,7 +31,10 @@ impl PositionSnapshotService {
 
     pub async fn generate_snapshots(&self) -> anyhow::Result<()> {
         let markets = self.lending_repository.get_all_markets().await?;
-        for market in markets {
+        let market_ids: Vec<&str> = markets.iter().map(|m| m.id.as_str()).collect();
+        let market_states = fetch_market_states(&self.client, &self.linx_address, self.linx_group, &market_ids).await;
+
+        for (market, market_state) in markets.iter().zip(market_states) {
             tracing::info!("Calculating position snapshots for market {}", market.id);
 
             // Get token info for the loan token
@@ -90,7 +91,7 @@ impl PositionSnapshotService {
                 }
             };
 
-            let market_state = match self.get_market_state(&market.id).await {
+            let market_state = match market_state {
                 Ok(state) => state,
                 Err(e) => {
                     tracing::error!("Failed to fetch market state for market {}: {}", market.id, e);
@@ -171,92 +172,6 @@ impl PositionSnapshotService {
         }
         Ok(())
     }
-
-    async fn get_market_state(&self, market_id: &str) -> anyhow::Result<MarketState> {
-        let method_index = 4;
-        let tx_id = random_tx_id();
-        let params = CallContractParams {
-            tx_id: Some(tx_id.clone()),
-            group: self.linx_group,
-            address: self.linx_address.clone(),
-            method_index,
-            args: Some(vec![serde_json::json!({
-                "type": "ByteVec",
-                "value": market_id,
-            })]),
-            world_state_block_hash: None,
-            interested_contracts: None,
-            input_assets: None,
-        };
-
-        let result = match self.client.call_contract(params).await {
-            Ok(res) => res,
-            Err(e) => {
-                return Err(anyhow::anyhow!("Failed to fetch market state: {}", e));
-            }
-        };
-
-        match result.result_type {
-            CallContractResultType::CallContractFailed => {
-                anyhow::bail!("Contract call failed for market {}", market_id);
-            }
-            CallContractResultType::CallContractSucceeded => {
-                let returns = result
-                    .returns
-                    .ok_or_else(|| anyhow::anyhow!("No returns in contract call for market {}", market_id))?;
-                if returns.len() != 6 {
-                    anyhow::bail!(
-                        "Expected 6 return values for market {}, got {}, values: {}",
-                        market_id,
-                        returns.len(),
-                        serde_json::to_string(&returns).unwrap_or_default(),
-                    );
-                }
-                let total_supply_assets = self
-                    .extract_bigdecimal_from_object(&returns[0])
-                    .with_context(|| format!("Failed to extract total_supply_assets for market {}", market_id))?;
-
-                let total_supply_shares = self
-                    .extract_bigdecimal_from_object(&returns[1])
-                    .with_context(|| format!("Failed to extract total_supply_shares for market {}", market_id))?;
-
-                let total_borrow_assets = self
-                    .extract_bigdecimal_from_object(&returns[2])
-                    .with_context(|| format!("Failed to extract total_borrow_assets for market {}", market_id))?;
-
-                let total_borrow_shares = self
-                    .extract_bigdecimal_from_object(&returns[3])
-                    .with_context(|| format!("Failed to extract total_borrow_shares for market {}", market_id))?;
-
-                let timestamp = self
-                    .extract_bigdecimal_from_object(&returns[4])
-                    .with_context(|| format!("Failed to extract timestamp for market {}", market_id))?
-                    .to_i64()
-                    .ok_or_else(|| anyhow::anyhow!("Failed to convert timestamp to i64 for market {}", market_id))?;
-                let last_update = timestamp_millis_to_naive_datetime(timestamp);
-                let fee = self.extract_bigdecimal_from_object(&returns[5]).unwrap_or_default();
-
-                Ok(MarketState {
-                    total_supply_assets,
-                    total_supply_shares,
-                    total_borrow_assets,
-                    total_borrow_shares,
-                    last_update,
-                    fee,
-                })
-            }
-        }
-    }
-
-    fn extract_bigdecimal_from_object(&self, value: &serde_json::Value) -> anyhow::Result<BigDecimal> {
-        value
-            .as_object()
-            .and_then(|obj| obj.get("value"))
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Invalid object structure"))?
-            .parse::<BigDecimal>()
-            .map_err(|e| anyhow::anyhow!("Failed to parse BigDecimal: {}", e))
-    }
 }
 
 #[async_trait]
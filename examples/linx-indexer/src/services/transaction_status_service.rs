@@ -0,0 +1,192 @@
+use crate::jobs::PeriodicJob;
+use crate::models::{LinxTransaction, LinxTransactionStatus, LinxTransactionStatusUpdate};
+use crate::repository::LinxTransactionsRepository;
+use anyhow::Context;
+use async_trait::async_trait;
+use bento_core::{Client, DbPool};
+use bento_trait::stage::TransactionProvider;
+use bento_types::TxStatus;
+use chrono::NaiveDateTime;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Confirmations after which a transaction is no longer tracked
+pub const FINAL_CONFIRMATIONS: i32 = 10;
+
+/// How long after submission a transaction unknown to the node is considered dropped
+const DROPPED_AFTER: chrono::Duration = chrono::Duration::minutes(30);
+
+/// Transactions checked per tick
+const BATCH_SIZE: i64 = 200;
+
+/// Polls the node for the status of the transactions submitted through
+/// `/transactions/v1/submit` until they are final or dropped.
+pub struct TransactionStatusService {
+    repository: LinxTransactionsRepository,
+    client: Client,
+}
+
+impl TransactionStatusService {
+    pub fn new(db_pool: Arc<DbPool>, client: Client) -> Self {
+        Self { repository: LinxTransactionsRepository::new(db_pool), client }
+    }
+
+    pub async fn check_transactions(&self) -> anyhow::Result<()> {
+        let transactions = self.repository.get_tracked_transactions(FINAL_CONFIRMATIONS, BATCH_SIZE).await?;
+        for transaction in &transactions {
+            // A failed check is retried on the next tick
+            if let Err(e) = self.check_transaction(transaction).await {
+                tracing::warn!(tx_id = %transaction.tx_id, error = %e, "Failed to check transaction status");
+            }
+        }
+        tracing::debug!("Checked the status of {} transactions", transactions.len());
+        Ok(())
+    }
+
+    async fn check_transaction(&self, transaction: &LinxTransaction) -> anyhow::Result<()> {
+        let status = self
+            .client
+            .get_transaction_status(
+                &transaction.tx_id,
+                transaction.from_group.map(i32::from),
+                transaction.to_group.map(i32::from),
+            )
+            .await?;
+
+        // The script result only changes with the block, e.g. after a reorg
+        let script_execution_ok = match &status {
+            TxStatus::Confirmed { block_hash, .. } if !in_block(transaction, block_hash) => Some(
+                self.client
+                    .get_tx_by_hash(&transaction.tx_id)
+                    .await?
+                    .with_context(|| format!("No details for confirmed transaction {}", transaction.tx_id))?
+                    .script_execution_ok,
+            ),
+            _ => None,
+        };
+
+        let update = next_status(transaction, status, script_execution_ok, chrono::Utc::now().naive_utc());
+        if update.status != transaction.status {
+            tracing::info!(tx_id = %transaction.tx_id, status = %update.status, "Transaction status changed");
+        }
+        self.repository.update_transaction_status(transaction.id, &update).await
+    }
+}
+
+/// Whether the transaction was already recorded in `block_hash`
+fn in_block(transaction: &LinxTransaction, block_hash: &str) -> bool {
+    transaction.status != LinxTransactionStatus::Pending.as_str()
+        && transaction.block_hash.as_deref() == Some(block_hash)
+}
+
+/// Status to record for `transaction` given the node's `status`. `script_execution_ok` is only
+/// fetched when the transaction entered a new block, otherwise the recorded status is kept.
+fn next_status(
+    transaction: &LinxTransaction,
+    status: TxStatus,
+    script_execution_ok: Option<bool>,
+    now: NaiveDateTime,
+) -> LinxTransactionStatusUpdate {
+    let (status, block_hash, confirmations) = match status {
+        TxStatus::Confirmed { block_hash, chain_confirmations, .. } => {
+            let status = match script_execution_ok {
+                Some(true) => LinxTransactionStatus::Confirmed.as_str(),
+                Some(false) => LinxTransactionStatus::Failed.as_str(),
+                None => transaction.status.as_str(),
+            };
+            (status.to_string(), Some(block_hash), chain_confirmations)
+        }
+        TxStatus::MemPooled => (LinxTransactionStatus::Pending.as_str().to_string(), None, 0),
+        TxStatus::TxNotFound => {
+            let status = match now - transaction.created_at > DROPPED_AFTER {
+                true => LinxTransactionStatus::Dropped,
+                false => LinxTransactionStatus::Pending,
+            };
+            (status.as_str().to_string(), None, 0)
+        }
+    };
+
+    LinxTransactionStatusUpdate { status, block_hash, confirmations, checked_at: now }
+}
+
+#[async_trait]
+impl PeriodicJob for TransactionStatusService {
+    fn name(&self) -> &'static str {
+        "transaction-status"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    async fn tick(&self) -> anyhow::Result<()> {
+        self.check_transactions().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn transaction(status: LinxTransactionStatus, block_hash: Option<&str>) -> LinxTransaction {
+        LinxTransaction {
+            id: 1,
+            tx_id: "tx".to_string(),
+            user_address: "user".to_string(),
+            created_at: DateTime::from_timestamp(0, 0).unwrap().naive_utc(),
+            from_group: Some(0),
+            to_group: Some(0),
+            status: status.as_str().to_string(),
+            block_hash: block_hash.map(str::to_string),
+            confirmations: 0,
+            checked_at: None,
+        }
+    }
+
+    fn confirmed(block_hash: &str, confirmations: i32) -> TxStatus {
+        TxStatus::Confirmed {
+            block_hash: block_hash.to_string(),
+            tx_index: 0,
+            chain_confirmations: confirmations,
+            from_group_confirmations: confirmations,
+            to_group_confirmations: confirmations,
+        }
+    }
+
+    fn at_minute(minutes: i64) -> NaiveDateTime {
+        DateTime::from_timestamp(minutes * 60, 0).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn test_next_status() {
+        let pending = transaction(LinxTransactionStatus::Pending, None);
+
+        let update = next_status(&pending, TxStatus::MemPooled, None, at_minute(1));
+        assert_eq!(update.status, "pending");
+
+        let update = next_status(&pending, confirmed("block", 2), Some(true), at_minute(1));
+        assert_eq!(
+            (update.status.as_str(), update.block_hash.as_deref(), update.confirmations),
+            ("confirmed", Some("block"), 2)
+        );
+
+        let update = next_status(&pending, confirmed("block", 1), Some(false), at_minute(1));
+        assert_eq!(update.status, "failed");
+
+        // Not found: pending shortly after submission, dropped later
+        assert_eq!(next_status(&pending, TxStatus::TxNotFound, None, at_minute(5)).status, "pending");
+        assert_eq!(next_status(&pending, TxStatus::TxNotFound, None, at_minute(31)).status, "dropped");
+
+        // In the same block the recorded status is kept
+        let failed = transaction(LinxTransactionStatus::Failed, Some("block"));
+        assert!(in_block(&failed, "block"));
+        assert!(!in_block(&failed, "other"));
+        let update = next_status(&failed, confirmed("block", 5), None, at_minute(2));
+        assert_eq!((update.status.as_str(), update.confirmations), ("failed", 5));
+
+        // Reorged back to the mempool
+        let update = next_status(&failed, TxStatus::MemPooled, None, at_minute(2));
+        assert_eq!((update.status.as_str(), update.block_hash), ("pending", None));
+    }
+}